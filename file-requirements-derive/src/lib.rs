@@ -0,0 +1,176 @@
+//! `#[derive(ToFileRequirement)]` for the `file-requirements` crate; enable
+//! its `derive` feature rather than depending on this crate directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Error, Fields, LitStr, Type, parse_macro_input};
+
+/// Implement `file_requirements::ToFileRequirement` for a struct whose
+/// path fields are annotated with `#[require]`.
+///
+/// Fields may be `PathBuf`s (or anything `AsRef<Path>`), `Option`s of them,
+/// which are required only when set, or `Vec`s of them, which are all
+/// required. The attribute takes at most one option:
+///
+/// - `#[require]`: a file term named after the field
+/// - `#[require(dir)]`: a directory term
+/// - `#[require(with = "ctab, ssi")]`: every extension of the prefix
+/// - `#[require(any_with = "sshash, ssi")]`: any one extension of the prefix
+#[proc_macro_derive(ToFileRequirement, attributes(require))]
+pub fn derive_to_file_requirement(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+enum Term {
+    File,
+    Dir,
+    With(Vec<String>),
+    AnyWith(Vec<String>),
+}
+
+enum Shape {
+    Single,
+    Optional,
+    Many,
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new(
+                    input.ident.span(),
+                    "ToFileRequirement needs a struct with named fields",
+                ));
+            }
+        },
+        _ => {
+            return Err(Error::new(
+                input.ident.span(),
+                "ToFileRequirement can only be derived for structs",
+            ));
+        }
+    };
+
+    let mut terms = Vec::new();
+    for field in fields {
+        let mut term = None;
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("require"))
+        {
+            if term.is_some() {
+                return Err(Error::new(attr.span(), "duplicate `#[require]` attribute"));
+            }
+            term = Some(parse_term(attr)?);
+        }
+        let Some(term) = term else {
+            continue;
+        };
+        let ident = field.ident.as_ref().expect("named field");
+        let name = ident.to_string();
+        let add = match term {
+            Term::File => quote! { builder.require_file_named(#name, path)?; },
+            Term::Dir => quote! { builder.require_dir(path)?; },
+            Term::With(extensions) => {
+                quote! { builder.require_with_extensions(path, [#(#extensions),*])?; }
+            }
+            Term::AnyWith(extensions) => {
+                quote! { builder.require_any_with_extensions(path, [#(#extensions),*])?; }
+            }
+        };
+        terms.push(match shape(&field.ty) {
+            Shape::Single => quote! {
+                {
+                    let path = &self.#ident;
+                    #add
+                }
+            },
+            Shape::Optional => quote! {
+                if let ::core::option::Option::Some(path) = &self.#ident {
+                    #add
+                }
+            },
+            Shape::Many => quote! {
+                for path in &self.#ident {
+                    #add
+                }
+            },
+        });
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::file_requirements::ToFileRequirement for #ident #ty_generics #where_clause {
+            fn to_file_requirement(
+                &self,
+            ) -> ::core::result::Result<
+                ::file_requirements::FileRequirement,
+                ::file_requirements::FileRequirementBuildError,
+            > {
+                let mut builder = ::file_requirements::FileRequirementBuilder::new();
+                #(#terms)*
+                ::core::result::Result::Ok(builder.build())
+            }
+        }
+    })
+}
+
+fn parse_term(attr: &syn::Attribute) -> syn::Result<Term> {
+    if matches!(attr.meta, syn::Meta::Path(_)) {
+        return Ok(Term::File);
+    }
+    let mut term = None;
+    attr.parse_nested_meta(|meta| {
+        if term.is_some() {
+            return Err(meta.error("`#[require]` takes at most one option"));
+        }
+        term = Some(if meta.path.is_ident("dir") {
+            Term::Dir
+        } else if meta.path.is_ident("with") {
+            Term::With(extensions(&meta.value()?.parse()?)?)
+        } else if meta.path.is_ident("any_with") {
+            Term::AnyWith(extensions(&meta.value()?.parse()?)?)
+        } else {
+            return Err(meta.error("expected `dir`, `with = \"..\"` or `any_with = \"..\"`"));
+        });
+        Ok(())
+    })?;
+    Ok(term.unwrap_or(Term::File))
+}
+
+/// The comma-separated extensions in `list`.
+fn extensions(list: &LitStr) -> syn::Result<Vec<String>> {
+    let extensions: Vec<String> = list
+        .value()
+        .split(',')
+        .map(|extension| extension.trim().to_owned())
+        .collect();
+    if extensions.iter().any(String::is_empty) {
+        return Err(Error::new(
+            list.span(),
+            "expected comma-separated extensions, such as \"ctab, ssi\"",
+        ));
+    }
+    Ok(extensions)
+}
+
+/// Whether `ty` is an `Option` or `Vec` of paths, judged by its name.
+fn shape(ty: &Type) -> Shape {
+    let Type::Path(path) = ty else {
+        return Shape::Single;
+    };
+    match path.path.segments.last() {
+        Some(segment) if segment.ident == "Option" => Shape::Optional,
+        Some(segment) if segment.ident == "Vec" => Shape::Many,
+        _ => Shape::Single,
+    }
+}
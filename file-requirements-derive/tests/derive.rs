@@ -0,0 +1,38 @@
+use std::path::PathBuf;
+
+use file_requirements::{FileRequirementBuildError, ToFileRequirement};
+
+#[derive(ToFileRequirement)]
+struct QuantArgs {
+    #[require(any_with = "sshash, ssi")]
+    index: PathBuf,
+    #[require]
+    reads: Vec<PathBuf>,
+    #[require]
+    whitelist: Option<PathBuf>,
+    #[require(dir)]
+    output: PathBuf,
+    #[allow(dead_code)]
+    threads: usize,
+}
+
+#[test]
+fn annotated_fields_become_terms() {
+    let mut args = QuantArgs {
+        index: PathBuf::from("idx"),
+        reads: vec![PathBuf::from("r1.fq"), PathBuf::from("r2.fq")],
+        whitelist: None,
+        output: PathBuf::from("out"),
+        threads: 8,
+    };
+    assert_eq!(
+        args.to_file_requirement().unwrap().to_string(),
+        r#"((idx.sshash OR idx.ssi) AND named("reads", r1.fq) AND named("reads", r2.fq) AND dir(out))"#
+    );
+
+    args.whitelist = Some(PathBuf::from("r1.fq"));
+    assert!(matches!(
+        args.to_file_requirement(),
+        Err(FileRequirementBuildError::DuplicateFile { .. })
+    ));
+}
@@ -0,0 +1,159 @@
+use std::fs;
+use std::io::{self, Read, Seek};
+use std::path::Path;
+
+use crate::FileProvider;
+
+/// Archive formats recognized from the archive's file name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Tar,
+    TarGz,
+    Zip,
+}
+
+impl Format {
+    fn of(archive: &Path) -> io::Result<Self> {
+        let name = archive
+            .file_name()
+            .map(|name| name.to_string_lossy().to_ascii_lowercase())
+            .unwrap_or_default();
+        if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Ok(Format::TarGz)
+        } else if name.ends_with(".tar") {
+            Ok(Format::Tar)
+        } else if name.ends_with(".zip") {
+            Ok(Format::Zip)
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{} is not a .tar, .tar.gz, .tgz or .zip archive",
+                    archive.display()
+                ),
+            ))
+        }
+    }
+}
+
+/// A member name without a leading `./` or trailing `/`, so `./data/` and
+/// `data` name the same directory entry.
+fn normalized(name: &str) -> &str {
+    let name = name.trim_end_matches('/');
+    name.strip_prefix("./").unwrap_or(name)
+}
+
+/// Whether the archive read from `provider` contains `member`.
+///
+/// Zip archives are buffered in memory, since [`FileProvider::open`] readers
+/// cannot seek to the central directory.
+pub(crate) fn contains<P: FileProvider + ?Sized>(
+    provider: &P,
+    archive: &Path,
+    member: &str,
+) -> io::Result<bool> {
+    let format = Format::of(archive)?;
+    let mut reader = provider.open(archive)?;
+    match format {
+        Format::Zip => {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes)?;
+            zip_contains(io::Cursor::new(bytes), member)
+        }
+        _ => tar_contains(reader, format, member),
+    }
+}
+
+/// Whether the local archive at `archive` contains `member`, reading only the
+/// central directory of zip archives.
+pub(crate) fn file_contains(archive: &Path, member: &str) -> io::Result<bool> {
+    let format = Format::of(archive)?;
+    let file = fs::File::open(archive)?;
+    match format {
+        Format::Zip => zip_contains(file, member),
+        _ => tar_contains(io::BufReader::new(file), format, member),
+    }
+}
+
+fn zip_contains<R: Read + Seek>(reader: R, member: &str) -> io::Result<bool> {
+    let archive = zip::ZipArchive::new(reader).map_err(io::Error::from)?;
+    let member = normalized(member);
+    Ok(archive.file_names().any(|name| normalized(name) == member))
+}
+
+/// Tar archives have no index, so headers are read until `member` is found;
+/// the contents of earlier entries are skipped, not decoded.
+fn tar_contains<R: Read>(reader: R, format: Format, member: &str) -> io::Result<bool> {
+    let reader: Box<dyn Read> = match format {
+        Format::TarGz => Box::new(flate2::read::MultiGzDecoder::new(reader)),
+        _ => Box::new(reader),
+    };
+    let member = normalized(member);
+    for entry in tar::Archive::new(reader).entries()? {
+        let entry = entry?;
+        if normalized(&entry.path()?.to_string_lossy()) == member {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FileRequirementBuilder, LeafOutcome};
+    use std::fs;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    #[test]
+    fn members_are_found_in_tar_gz_and_zip_archives() {
+        let td = tempdir().unwrap();
+        let tarball = td.path().join("bundle.tar.gz");
+        let gz = flate2::write::GzEncoder::new(
+            fs::File::create(&tarball).unwrap(),
+            flate2::Compression::fast(),
+        );
+        let mut tar = tar::Builder::new(gz);
+        let mut header = tar::Header::new_gnu();
+        header.set_size(2);
+        header.set_cksum();
+        tar.append_data(&mut header, "./bundle/info.json", &b"{}"[..])
+            .unwrap();
+        tar.into_inner().unwrap().finish().unwrap();
+
+        let zipped = td.path().join("bundle.zip");
+        let mut zip = zip::ZipWriter::new(fs::File::create(&zipped).unwrap());
+        zip.start_file("index/ref.fa", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(b">chr1\n").unwrap();
+        zip.finish().unwrap();
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_archive_member(&tarball, "bundle/info.json")
+            .unwrap();
+        b.require_archive_member(&tarball, "bundle/versions.yml")
+            .unwrap();
+        b.require_archive_member(&zipped, "./index/ref.fa").unwrap();
+        let req = b.build();
+        assert_eq!(
+            req.to_string().parse::<crate::FileRequirement>().unwrap(),
+            req
+        );
+
+        let report = req.check_report();
+        let outcomes: Vec<_> = report.leaves().iter().map(|l| l.outcome()).collect();
+        assert_eq!(
+            outcomes,
+            [
+                &LeafOutcome::Present,
+                &LeafOutcome::MemberMissing,
+                &LeafOutcome::Present
+            ]
+        );
+        let err = req.check().unwrap_err().to_string();
+        assert!(err.contains(&format!(
+            "missing archive members: bundle/versions.yml (in {})",
+            tarball.display()
+        )));
+    }
+}
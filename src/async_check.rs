@@ -0,0 +1,309 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::Poll;
+use std::time::Duration;
+
+use tokio::sync::Semaphore;
+
+use crate::metrics::{Metered, timed};
+use crate::{
+    CancelToken, CheckReport, FileRequirement, FileRequirementCheckError, LeafOutcome, StdFs,
+};
+
+impl FileRequirement {
+    /// Validate this requirement expression without blocking the async executor.
+    ///
+    /// Must be called from within a tokio runtime.
+    pub async fn check_async(&self) -> Result<(), FileRequirementCheckError> {
+        let report = self.check_report_async().await;
+        if report.is_satisfied() {
+            Ok(())
+        } else {
+            Err(FileRequirementCheckError::from_report(&report))
+        }
+    }
+
+    /// Async counterpart of [`FileRequirement::check_report`].
+    ///
+    /// Every distinct file term is stat'ed concurrently via `tokio::fs`, and other
+    /// leaf terms run on the blocking pool; the results are then aggregated exactly
+    /// as in the blocking check, so reports and error messages are identical.
+    pub async fn check_report_async(&self) -> CheckReport {
+        self.check_report_async_until(None, None).await
+    }
+
+    /// Like [`FileRequirement::check_async`], stopping early once `token` is
+    /// cancelled.
+    pub async fn check_async_with_cancel(
+        &self,
+        token: &CancelToken,
+    ) -> Result<(), FileRequirementCheckError> {
+        let report = self.check_report_async_with_cancel(token).await;
+        if report.is_satisfied() {
+            Ok(())
+        } else {
+            Err(FileRequirementCheckError::from_report(&report))
+        }
+    }
+
+    /// Like [`FileRequirement::check_report_async`], stopping early once
+    /// `token` is cancelled.
+    ///
+    /// Terms still pending at that point are abandoned and reported as
+    /// [`LeafOutcome::Cancelled`]; see [`CheckReport::is_cancelled`].
+    pub async fn check_report_async_with_cancel(&self, token: &CancelToken) -> CheckReport {
+        self.check_report_async_until(Some(token), None).await
+    }
+
+    /// Like [`FileRequirement::check_async`], with at most `max_in_flight`
+    /// terms being checked at any time.
+    pub async fn check_async_limited(
+        &self,
+        max_in_flight: usize,
+    ) -> Result<(), FileRequirementCheckError> {
+        let report = self.check_report_async_limited(max_in_flight).await;
+        if report.is_satisfied() {
+            Ok(())
+        } else {
+            Err(FileRequirementCheckError::from_report(&report))
+        }
+    }
+
+    /// Like [`FileRequirement::check_report_async`], with at most
+    /// `max_in_flight` terms being checked at any time.
+    ///
+    /// Suits high-latency remote storage, where dozens of requests in flight
+    /// help but thousands overwhelm the server. Terms are started round-robin
+    /// across the alternatives of each group, so one large group does not hold
+    /// up the others. A limit of zero is treated as one.
+    pub async fn check_report_async_limited(&self, max_in_flight: usize) -> CheckReport {
+        self.check_report_async_until(None, Some(max_in_flight))
+            .await
+    }
+
+    async fn check_report_async_until(
+        &self,
+        cancel: Option<&CancelToken>,
+        max_in_flight: Option<usize>,
+    ) -> CheckReport {
+        self.check_report_async_with(cancel, max_in_flight, local_task)
+            .await
+    }
+
+    /// Check every distinct leaf term with the task `task` makes for it,
+    /// started round-robin across group alternatives with at most
+    /// `max_in_flight` running, until `cancel` is cancelled.
+    pub(crate) async fn check_report_async_with<T>(
+        &self,
+        cancel: Option<&CancelToken>,
+        max_in_flight: Option<usize>,
+        task: T,
+    ) -> CheckReport
+    where
+        T: Fn(&FileRequirement, Arc<Metered<'static, StdFs>>) -> LeafTask,
+    {
+        let metered = Arc::new(Metered::new(StdFs));
+        let permits = max_in_flight.map(|limit| Arc::new(Semaphore::new(limit.max(1))));
+        let mut outcomes = HashMap::new();
+        let mut pending = Vec::new();
+        for leaf in interleaved_leaves(self) {
+            let permit = match &permits {
+                Some(permits) => {
+                    let mut acquire = Box::pin(Arc::clone(permits).acquire_owned());
+                    match unless_cancelled(&mut acquire, cancel).await {
+                        Some(permit) => Some(permit.expect("the semaphore is never closed")),
+                        None => {
+                            outcomes.insert(leaf, (LeafOutcome::Cancelled, Duration::ZERO));
+                            continue;
+                        }
+                    }
+                }
+                None => None,
+            };
+            let handle = match task(leaf, Arc::clone(&metered)) {
+                LeafTask::Async(check) => tokio::spawn(async move {
+                    let _permit = permit;
+                    check.await
+                }),
+                LeafTask::Blocking(check) => tokio::task::spawn_blocking(move || {
+                    let _permit = permit;
+                    check()
+                }),
+            };
+            pending.push((leaf, handle));
+        }
+
+        for (leaf, mut handle) in pending {
+            let outcome = match unless_cancelled(&mut handle, cancel).await {
+                Some(Ok(outcome)) => outcome,
+                Some(Err(e)) => {
+                    let error = std::io::Error::other(e.to_string());
+                    (LeafOutcome::from_io_error(error), Duration::ZERO)
+                }
+                None => {
+                    handle.abort();
+                    (LeafOutcome::Cancelled, Duration::ZERO)
+                }
+            };
+            outcomes.insert(leaf, outcome);
+        }
+
+        metered.report(self.evaluate_outcomes(&outcomes))
+    }
+}
+
+/// How one leaf term of an async check is resolved, with how long it took.
+pub(crate) enum LeafTask {
+    /// Awaited on the runtime.
+    Async(Pin<Box<dyn Future<Output = (LeafOutcome, Duration)> + Send>>),
+    /// Run on the blocking pool.
+    Blocking(Box<dyn FnOnce() -> (LeafOutcome, Duration) + Send>),
+}
+
+/// Stat file terms via `tokio::fs`, and check other terms on the blocking
+/// pool.
+fn local_task(leaf: &FileRequirement, metered: Arc<Metered<'static, StdFs>>) -> LeafTask {
+    match leaf.unwrapped() {
+        FileRequirement::File(path) => {
+            let path = path.clone();
+            LeafTask::Async(Box::pin(async move {
+                metered.record_stat();
+                let start = std::time::Instant::now();
+                let exists = tokio::fs::try_exists(path).await;
+                (LeafOutcome::from_exists(exists), start.elapsed())
+            }))
+        }
+        _ => LeafTask::blocking(leaf, metered),
+    }
+}
+
+impl LeafTask {
+    /// Check `leaf` on the blocking pool against the local filesystem.
+    pub(crate) fn blocking(leaf: &FileRequirement, metered: Arc<Metered<'static, StdFs>>) -> Self {
+        let owned = leaf.clone();
+        LeafTask::Blocking(Box::new(move || timed(|| owned.evaluate_leaf(&*metered))))
+    }
+}
+
+/// Await `future`, or give up with `None` once `cancel` is cancelled.
+async fn unless_cancelled<F>(future: &mut F, cancel: Option<&CancelToken>) -> Option<F::Output>
+where
+    F: Future + Unpin,
+{
+    std::future::poll_fn(|cx| match cancel {
+        Some(token) if token.is_cancelled() => Poll::Ready(None),
+        Some(token) => {
+            token.register(cx.waker());
+            Pin::new(&mut *future).poll(cx).map(Some)
+        }
+        None => Pin::new(&mut *future).poll(cx).map(Some),
+    })
+    .await
+}
+
+/// Distinct leaf terms of `node`, taking one from each child of every group in
+/// turn.
+fn interleaved_leaves(node: &FileRequirement) -> Vec<&FileRequirement> {
+    fn interleave(node: &FileRequirement) -> Vec<&FileRequirement> {
+        let Some((_, children)) = node.group() else {
+            return vec![node];
+        };
+        let mut queues: Vec<VecDeque<&FileRequirement>> = children
+            .iter()
+            .map(|child| interleave(child).into())
+            .collect();
+        let mut out = Vec::new();
+        while queues.iter().any(|queue| !queue.is_empty()) {
+            out.extend(queues.iter_mut().filter_map(VecDeque::pop_front));
+        }
+        out
+    }
+    let mut seen = HashSet::new();
+    interleave(node)
+        .into_iter()
+        .filter(|leaf| seen.insert(*leaf))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FileRequirementBuilder;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn async_check_matches_blocking_check() {
+        let td = tempdir().unwrap();
+        let base = td.path().join("idx");
+        fs::write(base.with_extension("ctab"), "").unwrap();
+        fs::write(base.with_extension("ssi"), "").unwrap();
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_file(base.with_extension("ctab")).unwrap();
+        b.require_any(|any| {
+            any.require_file(base.with_extension("sshash"))?;
+            any.require_all(|all| {
+                all.require_file(base.with_extension("ssi"))?;
+                all.require_file(base.with_extension("ssi.mphf"))?;
+                Ok(())
+            })?;
+            Ok(())
+        })
+        .unwrap();
+        let req = b.build();
+
+        let blocking = req.check().unwrap_err().to_string();
+        let not_blocking = req.check_async().await.unwrap_err().to_string();
+        assert_eq!(blocking, not_blocking);
+
+        fs::write(base.with_extension("ssi.mphf"), "").unwrap();
+        assert!(req.check_async().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn cancelled_async_checks_report_pending_terms() {
+        let mut b = FileRequirementBuilder::new();
+        b.require_file("a.txt").unwrap();
+        b.require_file("b.txt").unwrap();
+        let req = b.build();
+
+        let token = crate::CancelToken::new();
+        token.cancel();
+        let report = req.check_report_async_with_cancel(&token).await;
+        assert!(report.is_cancelled());
+        assert_eq!(
+            req.check_async_with_cancel(&token)
+                .await
+                .unwrap_err()
+                .to_string(),
+            "Required input files were missing or incomplete (not checked before cancellation: a.txt, b.txt)"
+        );
+    }
+
+    #[tokio::test]
+    async fn limited_async_check_matches_blocking_check() {
+        let td = tempdir().unwrap();
+        let mut b = FileRequirementBuilder::new();
+        b.require_any(|any| {
+            for i in 0..5 {
+                any.require_file(td.path().join(format!("shard_{}.bin", i)))?;
+            }
+            Ok(())
+        })
+        .unwrap();
+        b.require_file(td.path().join("ref.fa")).unwrap();
+        let req = b.build();
+
+        let order: Vec<_> = super::interleaved_leaves(&req)
+            .into_iter()
+            .map(|leaf| leaf.leaf_path().file_name().unwrap().to_owned())
+            .collect();
+        assert_eq!(order[..2], ["shard_0.bin", "ref.fa"]);
+
+        let blocking = req.check().unwrap_err().to_string();
+        let limited = req.check_async_limited(2).await.unwrap_err().to_string();
+        assert_eq!(blocking, limited);
+    }
+}
@@ -0,0 +1,144 @@
+//! `file-req`: check a requirement spec from the command line.
+//!
+//! The spec is read as JSON, YAML or TOML by its extension, in the `serde`
+//! form of [`FileRequirement`], or otherwise as the textual expression syntax.
+//! Exits with 0 when the requirement is satisfied, 1 when it is not, and 2 on
+//! usage errors or an unreadable spec.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+
+use clap::Parser;
+use file_requirements::{CheckOptions, FileRequirement, WaitError};
+
+/// Check that the files described by a requirement spec exist.
+#[derive(Debug, Parser)]
+#[command(name = "file-req", version)]
+struct Args {
+    /// Requirement spec: a `.json`, `.yaml` / `.yml` or `.toml` file, or a file
+    /// holding an expression such as `(a.txt AND (b.txt OR c.txt))`.
+    spec: PathBuf,
+    /// Resolve relative paths and glob patterns against this directory.
+    #[arg(long, value_name = "DIR")]
+    base_dir: Option<PathBuf>,
+    /// Print the report as JSON on stdout instead of a tree.
+    #[arg(long)]
+    json: bool,
+    /// Wait for the requirement to be satisfied, watching for file changes.
+    #[arg(long)]
+    watch: bool,
+    /// How long `--watch` waits before giving up.
+    #[arg(long, value_name = "SECS", default_value_t = 600, requires = "watch")]
+    timeout: u64,
+}
+
+/// Read `path` in the format named by its extension.
+fn load_spec(path: &Path) -> Result<FileRequirement, String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("json") => serde_json::from_str(&text).map_err(|e| e.to_string()),
+        // Read through a JSON value, since `serde_yaml` expects enum variants as
+        // `!tags` rather than the single-key maps the other formats use.
+        Some("yaml" | "yml") => serde_yaml::from_str::<serde_json::Value>(&text)
+            .and_then(|value| serde_json::from_value(value).map_err(serde::de::Error::custom))
+            .map_err(|e| e.to_string()),
+        Some("toml") => toml::from_str(&text).map_err(|e| e.to_string()),
+        _ => text
+            .trim()
+            .parse()
+            .map_err(|e: file_requirements::FileRequirementParseError| e.to_string()),
+    }
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let requirement = match load_spec(&args.spec) {
+        Ok(requirement) => requirement,
+        Err(message) => {
+            eprintln!(
+                "file-req: cannot load `{}`: {}",
+                args.spec.display(),
+                message
+            );
+            return ExitCode::from(2);
+        }
+    };
+    // Watching resolves paths against the working directory.
+    if let Some(dir) = &args.base_dir
+        && let Err(e) = std::env::set_current_dir(dir)
+    {
+        eprintln!("file-req: cannot enter `{}`: {}", dir.display(), e);
+        return ExitCode::from(2);
+    }
+
+    if args.watch {
+        match requirement.wait_until_satisfied(Duration::from_secs(args.timeout)) {
+            Ok(()) | Err(WaitError::Unsatisfied(_)) => {}
+            Err(e) => {
+                eprintln!("file-req: {}", e);
+                return ExitCode::from(2);
+            }
+        }
+    }
+    let report = requirement.check_report_with_options(&CheckOptions::new());
+
+    if args.json {
+        if let Err(e) = serde_json::to_writer_pretty(io::stdout(), &report) {
+            eprintln!("file-req: {}", e);
+            return ExitCode::from(2);
+        }
+        println!();
+    } else {
+        print!("{}", report.render_tree_for(&io::stdout()));
+        for finding in report.findings() {
+            eprintln!("{}", finding);
+        }
+    }
+    if report.is_satisfied() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn specs_are_read_by_extension() {
+        let td = tempdir().unwrap();
+        let specs = [
+            (
+                "spec.json",
+                r#"{"all": [{"file": "a.txt"}, {"glob": {"pattern": "*.fq", "min_matches": 1}}]}"#,
+            ),
+            (
+                "spec.yaml",
+                "all:\n  - file: a.txt\n  - glob: {pattern: '*.fq', min_matches: 1}\n",
+            ),
+            (
+                "spec.toml",
+                "[[all]]\nfile = \"a.txt\"\n\n[[all]]\nglob = { pattern = \"*.fq\", min_matches = 1 }\n",
+            ),
+            ("spec.req", "(a.txt AND glob(\"*.fq\", 1))\n"),
+        ];
+        for (name, text) in specs {
+            let path = td.path().join(name);
+            fs::write(&path, text).unwrap();
+            assert_eq!(
+                load_spec(&path).unwrap().to_string(),
+                r#"(a.txt AND glob("*.fq", 1))"#,
+                "{}",
+                name
+            );
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::metrics::{Metered, timed};
+use crate::{CheckReport, FileMetadata, FileRequirement, FileRequirementCheckError, StdFs};
+
+/// The result of checking one requirement expression.
+pub type CheckResult = Result<(), FileRequirementCheckError>;
+
+/// Stat results memoized by path, shared between checks.
+///
+/// Checking many requirement trees over overlapping files with one cache stats
+/// each path once. Only existing and not-found results are kept; other errors
+/// are retried. The cache never expires entries on its own, so
+/// [`clear`](Self::clear) it (or make a new one) once the filesystem may have
+/// changed.
+#[derive(Debug, Default)]
+pub struct CheckCache {
+    exists: Mutex<HashMap<PathBuf, bool>>,
+    metadata: Mutex<HashMap<(PathBuf, bool), Option<FileMetadata>>>,
+}
+
+impl CheckCache {
+    /// An empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of memoized stat results.
+    pub fn len(&self) -> usize {
+        self.exists.lock().unwrap().len() + self.metadata.lock().unwrap().len()
+    }
+
+    /// Whether nothing has been memoized yet.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forget every memoized result.
+    pub fn clear(&self) {
+        self.exists.lock().unwrap().clear();
+        self.metadata.lock().unwrap().clear();
+    }
+
+    /// Forget every memoized result for `path`.
+    pub(crate) fn forget(&self, path: &Path) {
+        self.exists.lock().unwrap().remove(path);
+        self.metadata
+            .lock()
+            .unwrap()
+            .retain(|(cached, _), _| cached != path);
+    }
+
+    pub(crate) fn exists(&self, path: &Path) -> Option<bool> {
+        self.exists.lock().unwrap().get(path).copied()
+    }
+
+    pub(crate) fn insert_exists(&self, path: &Path, exists: bool) {
+        self.exists
+            .lock()
+            .unwrap()
+            .insert(path.to_path_buf(), exists);
+    }
+
+    /// Memoized metadata of `path`: `Some(None)` for a path known to be missing.
+    pub(crate) fn metadata(&self, path: &Path, follow: bool) -> Option<Option<FileMetadata>> {
+        self.metadata
+            .lock()
+            .unwrap()
+            .get(&(path.to_path_buf(), follow))
+            .cloned()
+    }
+
+    pub(crate) fn insert_metadata(&self, path: &Path, follow: bool, meta: Option<FileMetadata>) {
+        self.metadata
+            .lock()
+            .unwrap()
+            .insert((path.to_path_buf(), follow), meta);
+    }
+}
+
+impl FileRequirement {
+    /// Validate this requirement expression, answering repeated stats of a path
+    /// from `cache`.
+    pub fn check_with_cache(&self, cache: &CheckCache) -> Result<(), FileRequirementCheckError> {
+        let report = self.check_report_with_cache(cache);
+        if report.is_satisfied() {
+            Ok(())
+        } else {
+            Err(FileRequirementCheckError::from_report(&report))
+        }
+    }
+
+    /// Validate many requirement expressions in one pass, returning one result
+    /// per requirement, in order.
+    ///
+    /// Paths shared between requirements are stat'ed once, so an orchestrator
+    /// can validate every task of a workflow up front.
+    pub fn check_all(requirements: &[&FileRequirement]) -> Vec<CheckResult> {
+        let cache = CheckCache::new();
+        requirements
+            .iter()
+            .map(|requirement| requirement.check_with_cache(&cache))
+            .collect()
+    }
+
+    /// Like [`FileRequirement::check_report`], answering repeated stats of a
+    /// path from `cache`.
+    pub fn check_report_with_cache(&self, cache: &CheckCache) -> CheckReport {
+        let metered = Metered::shared(StdFs, cache);
+        let root = self.evaluate(&mut |leaf| timed(|| leaf.evaluate_leaf(&metered)));
+        metered.report(root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CheckCache;
+    use crate::FileRequirementBuilder;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn shared_cache_spares_stats_of_overlapping_trees() {
+        let td = tempdir().unwrap();
+        fs::write(td.path().join("ref.fa"), "").unwrap();
+
+        let mut first = FileRequirementBuilder::new();
+        first.require_file(td.path().join("ref.fa")).unwrap();
+        first.require_file(td.path().join("idx.ctab")).unwrap();
+        let mut second = FileRequirementBuilder::new();
+        second.require_file(td.path().join("ref.fa")).unwrap();
+
+        let cache = CheckCache::new();
+        let report = first.build().check_report_with_cache(&cache);
+        assert_eq!(report.metrics().stat_calls(), 2);
+        assert_eq!(cache.len(), 2);
+
+        let report = second.build().check_report_with_cache(&cache);
+        assert!(report.is_satisfied());
+        assert_eq!(report.metrics().stat_calls(), 0);
+        assert_eq!(report.metrics().cache_hits(), 1);
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn check_all_returns_one_result_per_requirement() {
+        let td = tempdir().unwrap();
+        fs::write(td.path().join("ref.fa"), "").unwrap();
+
+        let mut first = FileRequirementBuilder::new();
+        first.require_file(td.path().join("ref.fa")).unwrap();
+        let mut second = FileRequirementBuilder::new();
+        second.require_file(td.path().join("ref.fa")).unwrap();
+        second.require_file(td.path().join("idx.ctab")).unwrap();
+        let (first, second) = (first.build(), second.build());
+
+        let results = crate::FileRequirement::check_all(&[&first, &second]);
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_ok());
+        assert_eq!(
+            results[1].as_ref().unwrap_err().missing_files(),
+            [td.path().join("idx.ctab")]
+        );
+    }
+}
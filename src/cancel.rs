@@ -0,0 +1,51 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::Waker;
+
+/// A flag for aborting a running check from another thread or task.
+///
+/// Clones share the flag. Pass a clone to
+/// [`CheckOptions::cancel_token`](crate::CheckOptions::cancel_token) or
+/// [`FileRequirement::check_async_with_cancel`](crate::FileRequirement::check_async_with_cancel),
+/// then call [`cancel`](Self::cancel): terms not yet checked are reported as
+/// [`LeafOutcome::Cancelled`](crate::LeafOutcome::Cancelled). A filesystem call
+/// already in progress is not interrupted.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    inner: Arc<Shared>,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    cancelled: AtomicBool,
+    wakers: Mutex<Vec<Waker>>,
+}
+
+impl CancelToken {
+    /// A token that has not been cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel every check using this token.
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        for waker in self.inner.wakers.lock().unwrap().drain(..) {
+            waker.wake();
+        }
+    }
+
+    /// Whether [`cancel`](Self::cancel) has been called.
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Wake `waker` when the token is cancelled.
+    #[cfg(feature = "tokio")]
+    pub(crate) fn register(&self, waker: &Waker) {
+        let mut wakers = self.inner.wakers.lock().unwrap();
+        if !wakers.iter().any(|w| w.will_wake(waker)) {
+            wakers.push(waker.clone());
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+use md5::Md5;
+use sha2::Sha256;
+use thiserror::Error;
+
+use crate::{FileConstraints, FileRequirement, FileRequirementBuildError, FileRequirementBuilder};
+
+/// An expected content digest for a file term.
+///
+/// Hex digests are normalized to lowercase.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum Digest {
+    /// SHA-256, as 64 hex digits.
+    Sha256(String),
+    /// MD5, as 32 hex digits.
+    Md5(String),
+}
+
+impl Digest {
+    /// An expected SHA-256 digest.
+    pub fn sha256(hex: &str) -> Result<Self, FileRequirementBuildError> {
+        validate_hex(hex, 64).map(Digest::Sha256)
+    }
+
+    /// An expected MD5 digest.
+    pub fn md5(hex: &str) -> Result<Self, FileRequirementBuildError> {
+        validate_hex(hex, 32).map(Digest::Md5)
+    }
+
+    /// The algorithm name, e.g. `sha256`.
+    pub fn algorithm(&self) -> &'static str {
+        match self {
+            Digest::Sha256(_) => "sha256",
+            Digest::Md5(_) => "md5",
+        }
+    }
+
+    /// The expected digest as lowercase hex.
+    pub fn hex(&self) -> &str {
+        match self {
+            Digest::Sha256(hex) | Digest::Md5(hex) => hex,
+        }
+    }
+
+    /// Hash everything `reader` yields with this digest's algorithm.
+    pub(crate) fn compute<R: Read>(&self, reader: R) -> io::Result<String> {
+        match self {
+            Digest::Sha256(_) => hash_reader::<Sha256, _>(reader),
+            Digest::Md5(_) => hash_reader::<Md5, _>(reader),
+        }
+    }
+
+    /// Parse the textual `sha256=HEX` / `md5=HEX` argument form.
+    pub(crate) fn parse_arg(arg: &str) -> Option<Result<Self, FileRequirementBuildError>> {
+        if let Some(hex) = arg.strip_prefix("sha256=") {
+            return Some(Digest::sha256(hex));
+        }
+        arg.strip_prefix("md5=").map(Digest::md5)
+    }
+}
+
+/// Renders the textual argument form, e.g. `sha256=9f86...`.
+impl fmt::Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.algorithm(), self.hex())
+    }
+}
+
+/// Errors produced while loading a checksum manifest.
+#[derive(Debug, Error)]
+pub enum ChecksumManifestError {
+    /// The manifest file could not be read.
+    #[error("Failed to read checksum manifest `{path}`: {source}")]
+    Io {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+    /// A manifest line could not be parsed.
+    #[error("Malformed checksum manifest line {line}: {message}")]
+    Malformed { line: usize, message: String },
+    /// The manifest lists a file more than once, or has an invalid digest.
+    #[error("Checksum manifest line {line}: {source}")]
+    Build {
+        line: usize,
+        #[source]
+        source: FileRequirementBuildError,
+    },
+}
+
+impl FileRequirement {
+    /// Build a requirement from an `md5sum` / `sha256sum` manifest file.
+    ///
+    /// Listed paths are resolved relative to the manifest's directory. See
+    /// [`FileRequirement::from_checksum_manifest`] for the accepted format.
+    pub fn from_checksum_file<P: AsRef<Path>>(
+        manifest: P,
+    ) -> Result<FileRequirement, ChecksumManifestError> {
+        let manifest = manifest.as_ref();
+        let text = fs::read_to_string(manifest).map_err(|source| ChecksumManifestError::Io {
+            path: manifest.display().to_string(),
+            source,
+        })?;
+        let base = manifest.parent().unwrap_or_else(|| Path::new(""));
+        Self::from_checksum_manifest(&text, base)
+    }
+
+    /// Build a requirement from the text of a checksum manifest.
+    ///
+    /// Each listed file becomes a required file term with a digest constraint,
+    /// joined under one `AND`. Both the GNU form (`<hex>  <path>`, or
+    /// `<hex> *<path>` in binary mode) and the BSD tagged form
+    /// (`SHA256 (<path>) = <hex>`) are accepted; for the GNU form the algorithm is
+    /// inferred from the digest length. Blank lines and `#` comments are skipped,
+    /// and relative paths are joined onto `base`.
+    pub fn from_checksum_manifest(
+        text: &str,
+        base: &Path,
+    ) -> Result<FileRequirement, ChecksumManifestError> {
+        let mut builder = FileRequirementBuilder::new();
+        for (idx, raw) in text.lines().enumerate() {
+            let line = idx + 1;
+            let entry = raw.trim_end_matches('\r');
+            if entry.trim().is_empty() || entry.starts_with('#') {
+                continue;
+            }
+            let (digest, path) = parse_manifest_line(entry)
+                .ok_or_else(|| ChecksumManifestError::Malformed {
+                    line,
+                    message: format!("expected `<digest>  <path>`, got `{}`", entry),
+                })?
+                .map_err(|source| ChecksumManifestError::Build { line, source })?;
+            builder
+                .require_file_with(base.join(path), FileConstraints::new().digest(digest))
+                .map_err(|source| ChecksumManifestError::Build { line, source })?;
+        }
+        Ok(builder.build())
+    }
+}
+
+type ManifestEntry<'a> = Result<(Digest, &'a str), FileRequirementBuildError>;
+
+fn parse_manifest_line(line: &str) -> Option<ManifestEntry<'_>> {
+    // BSD tagged form: `SHA256 (path) = hex`.
+    if let Some(rest) = line.strip_prefix("SHA256 (") {
+        let (path, hex) = rest.rsplit_once(") = ")?;
+        return Some(Digest::sha256(hex.trim()).map(|digest| (digest, path)));
+    }
+    if let Some(rest) = line.strip_prefix("MD5 (") {
+        let (path, hex) = rest.rsplit_once(") = ")?;
+        return Some(Digest::md5(hex.trim()).map(|digest| (digest, path)));
+    }
+
+    // GNU form: `hex  path` (text mode) or `hex *path` (binary mode).
+    let (hex, rest) = line.split_once(' ')?;
+    let path = rest.strip_prefix(' ').or_else(|| rest.strip_prefix('*'))?;
+    if path.is_empty() {
+        return None;
+    }
+    let digest = match hex.len() {
+        32 => Digest::md5(hex),
+        _ => Digest::sha256(hex),
+    };
+    Some(digest.map(|digest| (digest, path)))
+}
+
+fn validate_hex(hex: &str, len: usize) -> Result<String, FileRequirementBuildError> {
+    if hex.len() != len || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(FileRequirementBuildError::InvalidDigest {
+            digest: hex.to_string(),
+            message: format!("expected {} hex digits", len),
+        });
+    }
+    Ok(hex.to_ascii_lowercase())
+}
+
+fn hash_reader<D: sha2::Digest, R: Read>(mut reader: R) -> io::Result<String> {
+    let mut hasher = D::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChecksumManifestError, Digest};
+    use crate::{FileRequirement, FileRequirementBuildError, FileRequirementBuilder};
+    use std::fs;
+    use tempfile::tempdir;
+
+    const HELLO_SHA256: &str = "2CF24DBA5FB0A30E26E83B2AC5B9E29E1B161E5C1FA7425E73043362938B9824";
+
+    #[test]
+    fn sha256_constraint_reports_expected_and_actual_digest() {
+        let td = tempdir().unwrap();
+        let fasta = td.path().join("ref.fa");
+        fs::write(&fasta, "hello").unwrap();
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_file_with_sha256(&fasta, HELLO_SHA256).unwrap();
+        let req = b.build();
+        assert!(req.check().is_ok());
+        assert_eq!(
+            req.to_string(),
+            format!(
+                "(file({}, sha256={}))",
+                fasta.display(),
+                HELLO_SHA256.to_lowercase()
+            )
+        );
+
+        fs::write(&fasta, "hellO").unwrap();
+        let rendered = req.check().unwrap_err().to_string();
+        assert!(rendered.contains(&format!(
+            "checksum mismatch: {} (expected sha256 {}, got ",
+            fasta.display(),
+            HELLO_SHA256.to_lowercase()
+        )));
+    }
+
+    #[test]
+    fn malformed_digests_are_rejected_at_build_time() {
+        assert!(matches!(
+            Digest::md5("not-hex"),
+            Err(FileRequirementBuildError::InvalidDigest { .. })
+        ));
+        assert_eq!(
+            Digest::md5("5D41402ABC4B2A76B9719D911017C592").unwrap(),
+            Digest::Md5("5d41402abc4b2a76b9719d911017c592".to_string())
+        );
+    }
+
+    #[test]
+    fn checksum_manifest_builds_digest_constrained_terms() {
+        let td = tempdir().unwrap();
+        fs::create_dir(td.path().join("reads")).unwrap();
+        fs::write(td.path().join("ref.fa"), "hello").unwrap();
+        fs::write(td.path().join("reads").join("r1.fq"), "hellO").unwrap();
+        let manifest = td.path().join("SHA256SUMS");
+        fs::write(
+            &manifest,
+            format!(
+                "# dataset v1\n{}  ref.fa\n\nSHA256 (reads/r1.fq) = {}\n",
+                HELLO_SHA256.to_lowercase(),
+                HELLO_SHA256
+            ),
+        )
+        .unwrap();
+
+        let req = FileRequirement::from_checksum_file(&manifest).unwrap();
+        assert_eq!(req.paths().len(), 2);
+        let rendered = req.check().unwrap_err().to_string();
+        assert!(rendered.contains("checksum mismatch"));
+        assert!(rendered.contains("r1.fq"));
+        assert!(!rendered.contains("ref.fa"));
+
+        let err = FileRequirement::from_checksum_manifest("abc ref.fa", td.path()).unwrap_err();
+        assert!(matches!(
+            err,
+            ChecksumManifestError::Malformed { line: 1, .. }
+        ));
+        let err = FileRequirement::from_checksum_manifest(
+            "5d41402abc4b2a76b9719d911017c592  a\n5d41402abc4b2a76b9719d911017c592 *a",
+            td.path(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ChecksumManifestError::Build {
+                line: 2,
+                source: FileRequirementBuildError::DuplicateFile { .. }
+            }
+        ));
+    }
+}
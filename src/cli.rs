@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::path::PathBuf;
+
+use clap::builder::TypedValueParser;
+use clap::error::ErrorKind;
+use clap::parser::MatchesError;
+use clap::{Arg, ArgMatches, Command};
+use thiserror::Error;
+
+use crate::{FileRequirement, FileRequirementBuildError, FileRequirementBuilder};
+
+/// A `clap` value parser that accepts a path argument only if it satisfies a
+/// requirement, so CLIs reject a bad `--index` before doing any work.
+///
+/// The requirement's `{path}` placeholders are substituted with the argument,
+/// as by [`FileRequirement::resolve`]:
+///
+/// ```
+/// use clap::{Arg, Command};
+/// use file_requirements::{FileRequirement, RequirementParser};
+///
+/// let index: FileRequirement = "(dir({path}) AND {path}/info.json)".parse().unwrap();
+/// let cmd = Command::new("quant")
+///     .arg(Arg::new("index").long("index").value_parser(RequirementParser::new(index)));
+/// let err = cmd.try_get_matches_from(["quant", "--index", "no/such/idx"]).unwrap_err();
+/// assert!(err.to_string().contains("no/such/idx/info.json"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RequirementParser {
+    requirement: FileRequirement,
+}
+
+impl RequirementParser {
+    /// Check each argument value against `requirement`, with `{path}`
+    /// standing for the value.
+    pub fn new(requirement: FileRequirement) -> Self {
+        Self { requirement }
+    }
+}
+
+impl TypedValueParser for RequirementParser {
+    type Value = PathBuf;
+
+    fn parse_ref(
+        &self,
+        cmd: &Command,
+        arg: Option<&Arg>,
+        value: &OsStr,
+    ) -> Result<PathBuf, clap::Error> {
+        let invalid = |message: String| {
+            let arg = arg.map_or_else(|| "...".to_owned(), |arg| arg.get_id().to_string());
+            clap::Error::raw(
+                ErrorKind::ValueValidation,
+                format!(
+                    "invalid value '{}' for '{}': {}\n",
+                    value.to_string_lossy(),
+                    arg,
+                    message
+                ),
+            )
+            .with_cmd(cmd)
+        };
+        let path = value
+            .to_str()
+            .ok_or_else(|| invalid("the path is not valid UTF-8".to_owned()))?;
+        let vars = HashMap::from([("path".to_owned(), path.to_owned())]);
+        let requirement = self
+            .requirement
+            .resolve(&vars)
+            .map_err(|e| invalid(e.to_string()))?;
+        requirement.check().map_err(|e| invalid(e.to_string()))?;
+        Ok(PathBuf::from(value))
+    }
+}
+
+/// Errors produced while building a requirement from parsed arguments.
+#[derive(Debug, Error)]
+pub enum ArgMatchesError {
+    /// An id is not an argument of the command, or its values are not
+    /// [`PathBuf`]s.
+    #[error("Cannot read paths of argument `{id}`: {source}")]
+    Matches {
+        id: String,
+        #[source]
+        source: MatchesError,
+    },
+    /// The paths violate a builder invariant.
+    #[error(transparent)]
+    Build(#[from] FileRequirementBuildError),
+}
+
+impl FileRequirement {
+    /// A requirement that every path given to the arguments `ids` exists,
+    /// each named after its argument, such as `reads (r1.fq)`.
+    ///
+    /// The arguments must be parsed as [`PathBuf`]s; absent optional arguments
+    /// are skipped. An id that is not an argument of the command, such as a
+    /// misspelling, or one parsed as another type fails with
+    /// [`ArgMatchesError::Matches`]. Use this to validate several arguments
+    /// together, then report a failed check through [`Command::error`].
+    pub fn from_arg_matches(
+        matches: &ArgMatches,
+        ids: &[&str],
+    ) -> Result<FileRequirement, ArgMatchesError> {
+        let mut builder = FileRequirementBuilder::new();
+        for id in ids {
+            let paths =
+                matches
+                    .try_get_many::<PathBuf>(id)
+                    .map_err(|source| ArgMatchesError::Matches {
+                        id: (*id).to_owned(),
+                        source,
+                    })?;
+            let Some(paths) = paths else {
+                continue;
+            };
+            for path in paths {
+                builder.require_file_named(*id, path)?;
+            }
+        }
+        Ok(builder.build())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::ArgAction;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn arguments_are_checked_against_the_requirement() {
+        let td = tempdir().unwrap();
+        let sample = td.path().join("sample.fq");
+        fs::write(&sample, "").unwrap();
+        let non_empty: FileRequirement = "file({path}, size>=1)".parse().unwrap();
+        let cmd = Command::new("quant").arg(
+            Arg::new("reads")
+                .long("reads")
+                .value_parser(RequirementParser::new(non_empty)),
+        );
+
+        let err = cmd
+            .clone()
+            .try_get_matches_from(["quant", "--reads", sample.to_str().unwrap()])
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ValueValidation);
+        assert!(err.to_string().contains("for 'reads': "));
+        assert!(err.to_string().contains("size constraint violated"));
+
+        fs::write(&sample, "@r1\n").unwrap();
+        let matches = cmd
+            .try_get_matches_from(["quant", "--reads", sample.to_str().unwrap()])
+            .unwrap();
+        assert_eq!(matches.get_one::<PathBuf>("reads"), Some(&sample));
+    }
+
+    #[test]
+    fn requirements_are_built_from_several_arguments() {
+        let cmd = Command::new("quant")
+            .arg(
+                Arg::new("reads")
+                    .long("reads")
+                    .action(ArgAction::Append)
+                    .value_parser(clap::value_parser!(PathBuf)),
+            )
+            .arg(
+                Arg::new("whitelist")
+                    .long("whitelist")
+                    .value_parser(clap::value_parser!(PathBuf)),
+            );
+        let matches = cmd
+            .try_get_matches_from(["quant", "--reads", "a.fq", "--reads", "b.fq"])
+            .unwrap();
+        let req = FileRequirement::from_arg_matches(&matches, &["reads", "whitelist"]).unwrap();
+        assert_eq!(
+            req.to_string(),
+            r#"(named("reads", a.fq) AND named("reads", b.fq))"#
+        );
+
+        let err = FileRequirement::from_arg_matches(&matches, &["raeds"]).unwrap_err();
+        assert!(matches!(
+            &err,
+            ArgMatchesError::Matches { id, source: MatchesError::UnknownArgument { .. } }
+                if id == "raeds"
+        ));
+    }
+}
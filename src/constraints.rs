@@ -0,0 +1,787 @@
+use std::fmt;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{Access, FileMetadata, FileProvider, LeafOutcome};
+
+/// Bounds on the size of a file, in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum Size {
+    /// At least this many bytes.
+    AtLeast(u64),
+    /// At most this many bytes.
+    AtMost(u64),
+    /// Between the two bounds, inclusive.
+    Between(u64, u64),
+}
+
+impl Size {
+    /// Whether `len` bytes satisfies the bound.
+    pub fn contains(&self, len: u64) -> bool {
+        match *self {
+            Size::AtLeast(min) => len >= min,
+            Size::AtMost(max) => len <= max,
+            Size::Between(min, max) => min <= len && len <= max,
+        }
+    }
+
+    fn parse_arg(arg: &str) -> Option<Self> {
+        if let Some(min) = arg.strip_prefix("size>=") {
+            return min.parse().ok().map(Size::AtLeast);
+        }
+        if let Some(max) = arg.strip_prefix("size<=") {
+            return max.parse().ok().map(Size::AtMost);
+        }
+        let (min, max) = arg.strip_prefix("size=")?.split_once("..")?;
+        Some(Size::Between(min.parse().ok()?, max.parse().ok()?))
+    }
+
+    fn write_arg(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Size::AtLeast(min) => write!(f, "size>={}", min),
+            Size::AtMost(max) => write!(f, "size<={}", max),
+            Size::Between(min, max) => write!(f, "size={}..{}", min, max),
+        }
+    }
+}
+
+/// Renders the bound in prose, e.g. `at least 1 byte(s)`.
+impl fmt::Display for Size {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Size::AtLeast(min) => write!(f, "at least {} byte(s)", min),
+            Size::AtMost(max) => write!(f, "at most {} byte(s)", max),
+            Size::Between(min, max) => write!(f, "between {} and {} byte(s)", min, max),
+        }
+    }
+}
+
+/// Bounds on the age of a file, measured from its modification time to the
+/// time of the check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum Age {
+    /// Modified within this duration (the file is fresh).
+    AtMost(Duration),
+    /// Not modified within this duration (the file is settled or stale).
+    AtLeast(Duration),
+}
+
+impl Age {
+    /// Whether a file of age `age` satisfies the bound.
+    pub fn contains(&self, age: Duration) -> bool {
+        match *self {
+            Age::AtMost(max) => age <= max,
+            Age::AtLeast(min) => age >= min,
+        }
+    }
+
+    fn parse_arg(arg: &str) -> Option<Self> {
+        if let Some(max) = arg.strip_prefix("age<=") {
+            return parse_duration(max).map(Age::AtMost);
+        }
+        parse_duration(arg.strip_prefix("age>=")?).map(Age::AtLeast)
+    }
+
+    fn write_arg(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Age::AtMost(max) => write!(f, "age<={}", DisplayDuration(*max)),
+            Age::AtLeast(min) => write!(f, "age>={}", DisplayDuration(*min)),
+        }
+    }
+}
+
+/// Renders the bound in prose, e.g. `modified within 3600s`.
+impl fmt::Display for Age {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Age::AtMost(max) => write!(f, "modified within {}", DisplayDuration(*max)),
+            Age::AtLeast(min) => write!(f, "not modified within {}", DisplayDuration(*min)),
+        }
+    }
+}
+
+/// Formats a duration as seconds, e.g. `90s` or `1.500000000s`.
+pub(crate) struct DisplayDuration(pub(crate) Duration);
+
+impl fmt::Display for DisplayDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.subsec_nanos() {
+            0 => write!(f, "{}s", self.0.as_secs()),
+            nanos => write!(f, "{}.{:09}s", self.0.as_secs(), nanos),
+        }
+    }
+}
+
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.strip_suffix('s')?;
+    let (secs, nanos) = match value.split_once('.') {
+        Some((secs, nanos)) if nanos.len() == 9 => (secs, nanos.parse().ok()?),
+        Some(_) => return None,
+        None => (value, 0),
+    };
+    Some(Duration::new(secs.parse().ok()?, nanos))
+}
+
+/// Formats a timestamp as RFC 3339 in UTC, e.g. `2024-05-01T12:00:00Z`.
+pub(crate) struct DisplayTime(pub(crate) SystemTime);
+
+impl fmt::Display for DisplayTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Ok(since_epoch) = self.0.duration_since(UNIX_EPOCH) else {
+            return f.write_str("before 1970-01-01T00:00:00Z");
+        };
+        let secs = since_epoch.as_secs();
+        let (days, secs_of_day) = (secs / 86_400, secs % 86_400);
+        // Civil-from-days conversion (Howard Hinnant's algorithm).
+        let z = days as i64 + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + i64::from(month <= 2);
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            year,
+            month,
+            day,
+            secs_of_day / 3_600,
+            secs_of_day % 3_600 / 60,
+            secs_of_day % 60
+        )
+    }
+}
+
+/// Leading bytes a file's contents must start with.
+///
+/// Named formats match the container signature only; e.g. a BAM file is
+/// recognized by its BGZF header and is not decompressed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum Magic {
+    /// A gzip stream (`1f 8b`).
+    Gzip,
+    /// A BGZF-compressed BAM file (`1f 8b 08 04`).
+    Bam,
+    /// A CRAM file (`CRAM`).
+    Cram,
+    /// Arbitrary leading bytes.
+    Bytes(Vec<u8>),
+}
+
+impl Magic {
+    /// The byte signature the file must start with.
+    pub fn bytes(&self) -> &[u8] {
+        match self {
+            Magic::Gzip => &[0x1f, 0x8b],
+            Magic::Bam => &[0x1f, 0x8b, 0x08, 0x04],
+            Magic::Cram => b"CRAM",
+            Magic::Bytes(bytes) => bytes,
+        }
+    }
+
+    /// Read the leading bytes of `reader`, returning `None` if they match.
+    pub(crate) fn sniff<R: Read>(&self, reader: R) -> io::Result<Option<Vec<u8>>> {
+        let expected = self.bytes();
+        let mut found = Vec::with_capacity(expected.len());
+        reader.take(expected.len() as u64).read_to_end(&mut found)?;
+        Ok((found != expected).then_some(found))
+    }
+
+    fn parse_arg(arg: &str) -> Option<Self> {
+        match arg.strip_prefix("magic=")? {
+            "gzip" => Some(Magic::Gzip),
+            "bam" => Some(Magic::Bam),
+            "cram" => Some(Magic::Cram),
+            hex => {
+                if hex.is_empty() || hex.len() % 2 != 0 {
+                    return None;
+                }
+                (0..hex.len())
+                    .step_by(2)
+                    .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+                    .collect::<Option<Vec<u8>>>()
+                    .map(Magic::Bytes)
+            }
+        }
+    }
+
+    fn write_arg(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Magic::Gzip => f.write_str("magic=gzip"),
+            Magic::Bam => f.write_str("magic=bam"),
+            Magic::Cram => f.write_str("magic=cram"),
+            Magic::Bytes(bytes) => write!(f, "magic={}", DisplayBytes(bytes)),
+        }
+    }
+}
+
+/// Renders the format name, or the signature as hex for [`Magic::Bytes`].
+impl fmt::Display for Magic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Magic::Gzip => f.write_str("gzip"),
+            Magic::Bam => f.write_str("BAM"),
+            Magic::Cram => f.write_str("CRAM"),
+            Magic::Bytes(bytes) => write!(f, "bytes {}", DisplayBytes(bytes)),
+        }
+    }
+}
+
+impl From<&[u8]> for Magic {
+    fn from(bytes: &[u8]) -> Self {
+        Magic::Bytes(bytes.to_vec())
+    }
+}
+
+impl<const N: usize> From<&[u8; N]> for Magic {
+    fn from(bytes: &[u8; N]) -> Self {
+        Magic::Bytes(bytes.to_vec())
+    }
+}
+
+/// Renders bytes as lowercase hex.
+pub(crate) struct DisplayBytes<'a>(pub(crate) &'a [u8]);
+
+impl fmt::Display for DisplayBytes<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+fn mtime_unavailable() -> LeafOutcome {
+    LeafOutcome::from_io_error(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "modification time is not available",
+    ))
+}
+
+/// Extra conditions a file term must satisfy beyond existing.
+///
+/// Constraints are combined with the chaining methods, e.g.
+/// `FileConstraints::new().regular_file()`.
+///
+/// Deserializing rejects unknown keys, and keys of constraints whose feature
+/// is disabled with an error naming the feature.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct FileConstraints {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_false"))]
+    pub(crate) regular_file: bool,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_false"))]
+    pub(crate) readable: bool,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_false"))]
+    pub(crate) writable: bool,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_false"))]
+    pub(crate) executable: bool,
+    #[cfg(feature = "unix")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub(crate) owner: Option<crate::Owner>,
+    #[cfg(feature = "unix")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub(crate) group: Option<u32>,
+    #[cfg(all(feature = "serde", not(feature = "unix")))]
+    #[serde(
+        rename = "owner",
+        skip_serializing,
+        deserialize_with = "owner_disabled"
+    )]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    owner_disabled: (),
+    #[cfg(all(feature = "serde", not(feature = "unix")))]
+    #[serde(
+        rename = "group",
+        skip_serializing,
+        deserialize_with = "group_disabled"
+    )]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    group_disabled: (),
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub(crate) size: Option<Size>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub(crate) age: Option<Age>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub(crate) newer_than: Option<PathBuf>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub(crate) magic: Option<Magic>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub(crate) stable_for: Option<Duration>,
+    #[cfg(feature = "checksum")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub(crate) digest: Option<crate::Digest>,
+    #[cfg(all(feature = "serde", not(feature = "checksum")))]
+    #[serde(
+        rename = "digest",
+        skip_serializing,
+        deserialize_with = "digest_disabled"
+    )]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    digest_disabled: (),
+    #[cfg(feature = "gzip")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_false"))]
+    pub(crate) intact_gzip: bool,
+    #[cfg(all(feature = "serde", not(feature = "gzip")))]
+    #[serde(
+        rename = "intact_gzip",
+        skip_serializing,
+        deserialize_with = "intact_gzip_disabled"
+    )]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    intact_gzip_disabled: (),
+}
+
+#[cfg(feature = "serde")]
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+/// Reject the constraint `key`, which needs the disabled `feature`.
+#[cfg(all(
+    feature = "serde",
+    not(all(feature = "unix", feature = "checksum", feature = "gzip"))
+))]
+fn disabled<E: serde::de::Error>(key: &str, feature: &str) -> Result<(), E> {
+    Err(E::custom(format!(
+        "the `{}` constraint requires the `{}` feature",
+        key, feature
+    )))
+}
+
+#[cfg(all(feature = "serde", not(feature = "unix")))]
+fn owner_disabled<'de, D: serde::Deserializer<'de>>(_: D) -> Result<(), D::Error> {
+    disabled("owner", "unix")
+}
+
+#[cfg(all(feature = "serde", not(feature = "unix")))]
+fn group_disabled<'de, D: serde::Deserializer<'de>>(_: D) -> Result<(), D::Error> {
+    disabled("group", "unix")
+}
+
+#[cfg(all(feature = "serde", not(feature = "checksum")))]
+fn digest_disabled<'de, D: serde::Deserializer<'de>>(_: D) -> Result<(), D::Error> {
+    disabled("digest", "checksum")
+}
+
+#[cfg(all(feature = "serde", not(feature = "gzip")))]
+fn intact_gzip_disabled<'de, D: serde::Deserializer<'de>>(_: D) -> Result<(), D::Error> {
+    disabled("intact_gzip", "gzip")
+}
+
+impl FileConstraints {
+    /// No constraints beyond existence.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require the path to be a regular file, not a directory or special file.
+    pub fn regular_file(mut self) -> Self {
+        self.regular_file = true;
+        self
+    }
+
+    /// Require the current user to be able to read the path.
+    pub fn readable(mut self) -> Self {
+        self.readable = true;
+        self
+    }
+
+    /// Require the current user to be able to write the path.
+    pub fn writable(mut self) -> Self {
+        self.writable = true;
+        self
+    }
+
+    /// Require the path to be executable.
+    pub fn executable(mut self) -> Self {
+        self.executable = true;
+        self
+    }
+
+    /// Require the path to be owned by `owner`.
+    #[cfg(feature = "unix")]
+    pub fn owner(mut self, owner: crate::Owner) -> Self {
+        self.owner = Some(owner);
+        self
+    }
+
+    /// Require the path to belong to the group with id `gid`.
+    #[cfg(feature = "unix")]
+    pub fn group(mut self, gid: u32) -> Self {
+        self.group = Some(gid);
+        self
+    }
+
+    /// Require the file size to fall within `size`.
+    pub fn size(mut self, size: Size) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// Require the file to contain at least one byte.
+    pub fn non_empty(self) -> Self {
+        self.size(Size::AtLeast(1))
+    }
+
+    /// Require the file's age (time since last modification) to fall within `age`.
+    pub fn age(mut self, age: Age) -> Self {
+        self.age = Some(age);
+        self
+    }
+
+    /// Require the file to be at least as new as `dependency`, make-style.
+    ///
+    /// The check fails as stale when `dependency` was modified more recently.
+    pub fn newer_than<P: AsRef<Path>>(mut self, dependency: P) -> Self {
+        self.newer_than = Some(dependency.as_ref().to_path_buf());
+        self
+    }
+
+    /// Require the file contents to start with the `magic` signature.
+    pub fn magic<M: Into<Magic>>(mut self, magic: M) -> Self {
+        self.magic = Some(magic.into());
+        self
+    }
+
+    /// Require the file's size and modification time to stay unchanged over
+    /// `interval`, so a file still being transferred is not consumed.
+    ///
+    /// The check samples the metadata twice, sleeping `interval` in between.
+    pub fn stable_for(mut self, interval: Duration) -> Self {
+        self.stable_for = Some(interval);
+        self
+    }
+
+    /// Require the file contents to hash to `digest`.
+    #[cfg(feature = "checksum")]
+    pub fn digest(mut self, digest: crate::Digest) -> Self {
+        self.digest = Some(digest);
+        self
+    }
+
+    /// Require the file to be a complete gzip stream, such as a `.fastq.gz`, or
+    /// a BGZF file, such as a `.bam`, ending with its EOF block.
+    ///
+    /// Every member is decompressed to check its CRC and length, which catches
+    /// truncated downloads that still exist and have a plausible size.
+    #[cfg(feature = "gzip")]
+    pub fn intact_gzip(mut self) -> Self {
+        self.intact_gzip = true;
+        self
+    }
+
+    /// Whether the path must be a regular file.
+    pub fn requires_regular_file(&self) -> bool {
+        self.regular_file
+    }
+
+    /// The permissions the current user must hold, in [`Access::ALL`] order.
+    pub fn required_access(&self) -> Vec<Access> {
+        Access::ALL
+            .into_iter()
+            .filter(|access| match access {
+                Access::Read => self.readable,
+                Access::Write => self.writable,
+                Access::Execute => self.executable,
+            })
+            .collect()
+    }
+
+    /// The required owning user, if any.
+    #[cfg(feature = "unix")]
+    pub fn required_owner(&self) -> Option<crate::Owner> {
+        self.owner
+    }
+
+    /// The required owning group id, if any.
+    #[cfg(feature = "unix")]
+    pub fn required_group(&self) -> Option<u32> {
+        self.group
+    }
+
+    /// The required size bounds, if any.
+    pub fn size_bounds(&self) -> Option<Size> {
+        self.size
+    }
+
+    /// The required age bounds, if any.
+    pub fn age_bounds(&self) -> Option<Age> {
+        self.age
+    }
+
+    /// The file this file must be newer than, if any.
+    pub fn dependency(&self) -> Option<&Path> {
+        self.newer_than.as_deref()
+    }
+
+    /// The required leading-byte signature, if any.
+    pub fn expected_magic(&self) -> Option<&Magic> {
+        self.magic.as_ref()
+    }
+
+    /// The interval over which the file must stay unchanged, if any.
+    pub fn stability_interval(&self) -> Option<Duration> {
+        self.stable_for
+    }
+
+    /// The expected content digest, if any.
+    #[cfg(feature = "checksum")]
+    pub fn expected_digest(&self) -> Option<&crate::Digest> {
+        self.digest.as_ref()
+    }
+
+    /// Whether the file must be an intact gzip or BGZF stream.
+    #[cfg(feature = "gzip")]
+    pub fn requires_intact_gzip(&self) -> bool {
+        self.intact_gzip
+    }
+
+    /// Check an existing path's metadata against every constraint, in order.
+    pub(crate) fn evaluate<P: FileProvider + ?Sized>(
+        &self,
+        path: &Path,
+        meta: &FileMetadata,
+        provider: &P,
+    ) -> LeafOutcome {
+        if self.regular_file && !meta.is_file() {
+            return LeafOutcome::NotARegularFile { kind: meta.kind() };
+        }
+        for access in self.required_access() {
+            match provider.access(path, access) {
+                Ok(true) => {}
+                Ok(false) => return LeafOutcome::PermissionDenied { access },
+                Err(e) => return LeafOutcome::from_io_error(e),
+            }
+        }
+        #[cfg(feature = "unix")]
+        if self.owner.is_some() || self.group.is_some() {
+            let Some((uid, gid)) = meta.owner() else {
+                return LeafOutcome::from_io_error(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "file ownership is not available",
+                ));
+            };
+            let owner_ok = self.owner.is_none_or(|owner| owner.uid() == uid);
+            let group_ok = self.group.is_none_or(|group| group == gid);
+            if !(owner_ok && group_ok) {
+                return LeafOutcome::WrongOwner { uid, gid };
+            }
+        }
+        if let Some(size) = self.size
+            && !size.contains(meta.len())
+        {
+            return LeafOutcome::SizeOutOfRange { actual: meta.len() };
+        }
+        if let Some(age) = self.age {
+            let Some(modified) = meta.modified() else {
+                return mtime_unavailable();
+            };
+            // Timestamps in the future count as age zero.
+            let elapsed = SystemTime::now()
+                .duration_since(modified)
+                .unwrap_or(Duration::ZERO);
+            if !age.contains(elapsed) {
+                return LeafOutcome::AgeOutOfRange { modified };
+            }
+        }
+        if let Some(dependency) = &self.newer_than {
+            let Some(modified) = meta.modified() else {
+                return mtime_unavailable();
+            };
+            let dependency_modified = match provider.metadata(dependency) {
+                Ok(dep_meta) => match dep_meta.modified() {
+                    Some(t) => t,
+                    None => return mtime_unavailable(),
+                },
+                Err(e) => {
+                    return LeafOutcome::from_io_error(io::Error::new(
+                        e.kind(),
+                        format!("dependency {}: {}", dependency.display(), e),
+                    ));
+                }
+            };
+            if modified < dependency_modified {
+                return LeafOutcome::Stale {
+                    modified,
+                    dependency_modified,
+                };
+            }
+        }
+        if let Some(interval) = self.stable_for {
+            std::thread::sleep(interval);
+            provider.invalidate(path);
+            match provider.metadata(path) {
+                Ok(later) if later.len() == meta.len() && later.modified() == meta.modified() => {}
+                Ok(_) => return LeafOutcome::StillChanging,
+                Err(e) => return LeafOutcome::from_io_error(e),
+            }
+        }
+        if let Some(magic) = &self.magic {
+            match provider.open(path).and_then(|reader| magic.sniff(reader)) {
+                Ok(None) => {}
+                Ok(Some(found)) => return LeafOutcome::MagicMismatch { found },
+                Err(e) => return LeafOutcome::from_io_error(e),
+            }
+        }
+        #[cfg(feature = "checksum")]
+        if let Some(digest) = &self.digest {
+            match provider
+                .open(path)
+                .and_then(|reader| digest.compute(reader))
+            {
+                Ok(actual) if actual == digest.hex() => {}
+                Ok(actual) => return LeafOutcome::DigestMismatch { actual },
+                Err(e) => return LeafOutcome::from_io_error(e),
+            }
+        }
+        #[cfg(feature = "gzip")]
+        if self.intact_gzip {
+            match provider.open(path).and_then(crate::gzip::verify) {
+                Ok(None) => {}
+                Ok(Some(problem)) => return LeafOutcome::CorruptGzip { problem },
+                Err(e) => return LeafOutcome::from_io_error(e),
+            }
+        }
+        LeafOutcome::Present
+    }
+
+    /// Apply one argument of the textual `file(path, ...)` form.
+    pub(crate) fn apply_arg(&mut self, arg: &str) -> Result<(), String> {
+        #[cfg(feature = "checksum")]
+        if let Some(digest) = crate::Digest::parse_arg(arg) {
+            self.digest = Some(digest.map_err(|e| e.to_string())?);
+            return Ok(());
+        }
+        #[cfg(feature = "unix")]
+        if let Some(owner) = crate::Owner::parse_arg(arg) {
+            self.owner = Some(owner);
+            return Ok(());
+        }
+        #[cfg(feature = "unix")]
+        if let Some(group) = crate::ownership::parse_group_arg(arg) {
+            self.group = Some(group);
+            return Ok(());
+        }
+        #[cfg(feature = "gzip")]
+        if arg == "intact_gzip" {
+            self.intact_gzip = true;
+            return Ok(());
+        }
+        match arg {
+            "regular" => self.regular_file = true,
+            "readable" => self.readable = true,
+            "writable" => self.writable = true,
+            "executable" => self.executable = true,
+            _ => {
+                if let Some(dependency) = arg.strip_prefix("newer_than=") {
+                    self.newer_than = Some(PathBuf::from(dependency));
+                } else if let Some(interval) = arg.strip_prefix("stable=") {
+                    self.stable_for = Some(
+                        parse_duration(interval)
+                            .ok_or_else(|| format!("invalid stability interval `{}`", interval))?,
+                    );
+                } else if let Some(size) = Size::parse_arg(arg) {
+                    self.size = Some(size);
+                } else if let Some(age) = Age::parse_arg(arg) {
+                    self.age = Some(age);
+                } else if let Some(magic) = Magic::parse_arg(arg) {
+                    self.magic = Some(magic);
+                } else {
+                    return Err(format!("unknown file constraint `{}`", arg));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Renders the constraint arguments of the textual `file(path, ...)` form,
+/// each preceded by `, `.
+impl fmt::Display for FileConstraints {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.regular_file {
+            f.write_str(", regular")?;
+        }
+        for access in self.required_access() {
+            write!(f, ", {}", access.adjective())?;
+        }
+        #[cfg(feature = "unix")]
+        if let Some(owner) = &self.owner {
+            f.write_str(", ")?;
+            owner.write_arg(f)?;
+        }
+        #[cfg(feature = "unix")]
+        if let Some(group) = self.group {
+            write!(f, ", group={}", group)?;
+        }
+        if let Some(size) = &self.size {
+            f.write_str(", ")?;
+            size.write_arg(f)?;
+        }
+        if let Some(age) = &self.age {
+            f.write_str(", ")?;
+            age.write_arg(f)?;
+        }
+        if let Some(dependency) = &self.newer_than {
+            f.write_str(", ")?;
+            let arg = format!("newer_than={}", dependency.display());
+            crate::parse::write_path(f, Path::new(&arg))?;
+        }
+        if let Some(magic) = &self.magic {
+            f.write_str(", ")?;
+            magic.write_arg(f)?;
+        }
+        if let Some(interval) = self.stable_for {
+            write!(f, ", stable={}", DisplayDuration(interval))?;
+        }
+        #[cfg(feature = "checksum")]
+        if let Some(digest) = &self.digest {
+            write!(f, ", {}", digest)?;
+        }
+        #[cfg(feature = "gzip")]
+        if self.intact_gzip {
+            f.write_str(", intact_gzip")?;
+        }
+        Ok(())
+    }
+}
+
+impl From<Size> for FileConstraints {
+    fn from(size: Size) -> Self {
+        FileConstraints::new().size(size)
+    }
+}
+
+impl From<Age> for FileConstraints {
+    fn from(age: Age) -> Self {
+        FileConstraints::new().age(age)
+    }
+}
@@ -0,0 +1,64 @@
+use std::path::PathBuf;
+
+use crate::{FileRequirement, FileRequirementBuildError};
+
+/// A value describing the files it needs, such as a CLI argument struct,
+/// converted to the [`FileRequirement`] on them.
+///
+/// With the `derive` feature, `#[derive(ToFileRequirement)]` implements it for
+/// structs whose path fields are annotated with `#[require]`,
+/// `#[require(dir)]`, `#[require(with = "ctab, ssi")]` or
+/// `#[require(any_with = "sshash, ssi")]`; `Option` fields are required only
+/// when set and `Vec` fields element by element.
+pub trait ToFileRequirement {
+    /// The requirement on the files this value names.
+    fn to_file_requirement(&self) -> Result<FileRequirement, FileRequirementBuildError>;
+}
+
+/// A single required file.
+impl From<PathBuf> for FileRequirement {
+    fn from(path: PathBuf) -> Self {
+        FileRequirement::File(path)
+    }
+}
+
+/// An `AND` group requiring every path, in order. As with the `&` operator,
+/// repeated paths are not rejected; use the builder for that.
+impl From<Vec<PathBuf>> for FileRequirement {
+    fn from(paths: Vec<PathBuf>) -> Self {
+        paths.into_iter().collect()
+    }
+}
+
+/// An `AND` group requiring every path, as [`From<Vec<PathBuf>>`] builds.
+impl FromIterator<PathBuf> for FileRequirement {
+    fn from_iter<I: IntoIterator<Item = PathBuf>>(paths: I) -> Self {
+        FileRequirement::All(paths.into_iter().map(FileRequirement::File).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FileRequirementBuilder;
+    use std::path::Path;
+
+    #[test]
+    fn path_lists_convert_to_conjunctions() {
+        let paths = vec![PathBuf::from("idx.ctab"), PathBuf::from("idx.ssi")];
+        let mut b = FileRequirementBuilder::new();
+        b.require_file("idx.ctab").unwrap();
+        b.require_file("idx.ssi").unwrap();
+        assert_eq!(FileRequirement::from(paths.clone()), b.build());
+
+        let req: FileRequirement = paths
+            .iter()
+            .map(|path| Path::new("ref").join(path))
+            .collect();
+        assert_eq!(req.to_string(), "(ref/idx.ctab AND ref/idx.ssi)");
+        assert_eq!(
+            FileRequirement::from(PathBuf::from("a.txt")),
+            FileRequirement::File(PathBuf::from("a.txt"))
+        );
+    }
+}
@@ -0,0 +1,245 @@
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::FileRequirement;
+
+impl FileRequirement {
+    /// Describe the expression as the `inputs` section of a Common Workflow
+    /// Language tool, so a CWL description can be generated from the
+    /// requirement tree instead of kept in sync by hand.
+    ///
+    /// File, archive and URL terms become `File` inputs, directory terms
+    /// `Directory` inputs, and glob and shard terms `File[]` inputs. Terms
+    /// under an `OR` / `XOR` group with several alternatives, and recommended
+    /// files, become optional (`File?`). Input ids come from term names, or
+    /// otherwise from file names; each input's `doc` is its hint, or otherwise
+    /// the term itself. Terms that are not inputs, such as environment
+    /// variables, executables or free space, and file constraints are left
+    /// out.
+    ///
+    /// ```text
+    /// inputs:
+    ///   idx_ctab:
+    ///     type: File
+    ///     doc: "idx.ctab"
+    ///   sshash_index:
+    ///     type: File?
+    ///     label: "sshash index"
+    ///     doc: "idx.sshash"
+    /// ```
+    pub fn to_cwl_inputs(&self) -> String {
+        let mut inputs = Vec::new();
+        collect(self, false, None, None, &mut inputs);
+        if inputs.is_empty() {
+            return "inputs: {}\n".to_owned();
+        }
+        let mut out = String::from("inputs:\n");
+        for input in inputs {
+            let _ = writeln!(out, "  {}:", input.id);
+            let _ = writeln!(
+                out,
+                "    type: {}{}{}",
+                input.class,
+                if input.array { "[]" } else { "" },
+                if input.optional { "?" } else { "" }
+            );
+            if let Some(label) = input.label {
+                let _ = writeln!(out, "    label: {}", yaml_string(&label));
+            }
+            let _ = writeln!(out, "    doc: {}", yaml_string(&input.doc));
+        }
+        out
+    }
+}
+
+struct CwlInput {
+    id: String,
+    class: &'static str,
+    array: bool,
+    optional: bool,
+    label: Option<String>,
+    doc: String,
+    /// The path, pattern or URL the input stands for, so archives with
+    /// several required members give a single input.
+    source: String,
+}
+
+fn collect(
+    node: &FileRequirement,
+    optional: bool,
+    name: Option<&str>,
+    hint: Option<&str>,
+    inputs: &mut Vec<CwlInput>,
+) {
+    let (class, array, optional, source, stem) = match node {
+        FileRequirement::Named { name, term } => {
+            // A group's name labels the group, not each of its terms.
+            let name = term.group().is_none().then_some(name.as_str());
+            return collect(term, optional, name, hint, inputs);
+        }
+        FileRequirement::Hinted { hint, term } => {
+            return collect(term, optional, name, Some(hint), inputs);
+        }
+        FileRequirement::All(children) => {
+            for child in children {
+                collect(child, optional, None, hint, inputs);
+            }
+            return;
+        }
+        FileRequirement::Any(children) | FileRequirement::ExactlyOne(children) => {
+            for child in children {
+                collect(child, optional || children.len() > 1, None, hint, inputs);
+            }
+            return;
+        }
+        FileRequirement::File(path)
+        | FileRequirement::FileWith { path, .. }
+        | FileRequirement::ArchiveMember { archive: path, .. } => {
+            ("File", false, optional, path_string(path), file_name(path))
+        }
+        FileRequirement::Recommended(path) => {
+            ("File", false, true, path_string(path), file_name(path))
+        }
+        FileRequirement::Dir { path, .. } | FileRequirement::DirEntries { path, .. } => (
+            "Directory",
+            false,
+            optional,
+            path_string(path),
+            file_name(path),
+        ),
+        FileRequirement::Glob { pattern, .. } | FileRequirement::GlobSize { pattern, .. } => (
+            "File",
+            true,
+            optional,
+            pattern.clone(),
+            file_name(Path::new(pattern)),
+        ),
+        FileRequirement::Shards { template, .. } => (
+            "File",
+            true,
+            optional,
+            template.clone(),
+            file_name(Path::new(template)),
+        ),
+        FileRequirement::Url(url) => (
+            "File",
+            false,
+            optional,
+            url.clone(),
+            url.rsplit('/').next().unwrap_or_default().to_owned(),
+        ),
+        _ => return,
+    };
+    if inputs.iter().any(|input| input.source == source) {
+        return;
+    }
+    let base = identifier(name.unwrap_or(&stem));
+    let mut id = base.clone();
+    let mut n = 1;
+    while inputs.iter().any(|input| input.id == id) {
+        n += 1;
+        id = format!("{}_{}", base, n);
+    }
+    inputs.push(CwlInput {
+        id,
+        class,
+        array,
+        optional,
+        label: name.map(str::to_owned),
+        doc: hint.map_or_else(|| node.to_string(), str::to_owned),
+        source,
+    });
+}
+
+fn path_string(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// `text` as a CWL input id: ASCII letters, digits and single underscores,
+/// not starting with a digit.
+fn identifier(text: &str) -> String {
+    let mut id = String::new();
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() {
+            id.push(c);
+        } else if !id.is_empty() && !id.ends_with('_') {
+            id.push('_');
+        }
+    }
+    let id = id.trim_end_matches('_');
+    match id.chars().next() {
+        None => "input".to_owned(),
+        Some(c) if c.is_ascii_digit() => format!("_{}", id),
+        Some(_) => id.to_owned(),
+    }
+}
+
+/// `text` as a double-quoted YAML scalar.
+fn yaml_string(text: &str) -> String {
+    let mut out = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FileRequirementBuilder;
+
+    #[test]
+    fn alternatives_become_optional_inputs() {
+        let mut b = FileRequirementBuilder::new();
+        b.require_file("idx/idx.ctab").unwrap();
+        b.require_any(|any| {
+            any.require_file_named("sshash index", "idx/idx.sshash")?;
+            any.require_all(|all| {
+                all.require_file("idx/idx.ssi")?;
+                all.require_file("idx/idx.ssi.mphf")?;
+                Ok(())
+            })?;
+            Ok(())
+        })
+        .unwrap();
+        b.require_glob("reads/*.fq.gz", 2).unwrap();
+        b.require_executable("samtools").unwrap();
+        assert_eq!(
+            b.build().to_cwl_inputs(),
+            concat!(
+                "inputs:\n",
+                "  idx_ctab:\n",
+                "    type: File\n",
+                "    doc: \"idx/idx.ctab\"\n",
+                "  sshash_index:\n",
+                "    type: File?\n",
+                "    label: \"sshash index\"\n",
+                "    doc: \"idx/idx.sshash\"\n",
+                "  idx_ssi:\n",
+                "    type: File?\n",
+                "    doc: \"idx/idx.ssi\"\n",
+                "  idx_ssi_mphf:\n",
+                "    type: File?\n",
+                "    doc: \"idx/idx.ssi.mphf\"\n",
+                "  fq_gz:\n",
+                "    type: File[]\n",
+                "    doc: \"glob(\\\"reads/*.fq.gz\\\", 2)\"\n",
+            )
+        );
+    }
+}
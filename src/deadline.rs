@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::sync::{Arc, mpsc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::metrics::{Metered, timed};
+use crate::{
+    CheckReport, FileRequirement, FileRequirementCheckError, LeafOutcome, NodeReport, StdFs,
+};
+
+/// Progress sent by the worker thread of a timed check.
+enum Progress {
+    /// A term's outcome, kept in case the deadline passes before the end.
+    Leaf(FileRequirement, LeafOutcome, Duration),
+    /// The finished evaluation.
+    Done(NodeReport),
+}
+
+impl FileRequirement {
+    /// Validate this requirement expression, giving up after `timeout`.
+    ///
+    /// Terms whose check had not finished by then, such as paths on a hung
+    /// network mount, are reported as [`LeafOutcome::Unresolved`].
+    pub fn check_with_timeout(&self, timeout: Duration) -> Result<(), FileRequirementCheckError> {
+        let report = self.check_report_with_timeout(timeout);
+        if report.is_satisfied() {
+            Ok(())
+        } else {
+            Err(FileRequirementCheckError::from_report(&report))
+        }
+    }
+
+    /// Like [`FileRequirement::check_report`], giving up after `timeout`.
+    ///
+    /// Terms are checked on a background thread. A blocked filesystem call
+    /// cannot be interrupted, so when the deadline passes that thread is left
+    /// to finish on its own, and every term it had not resolved is reported as
+    /// [`LeafOutcome::Unresolved`].
+    pub fn check_report_with_timeout(&self, timeout: Duration) -> CheckReport {
+        let deadline = Instant::now() + timeout;
+        let metered = Arc::new(Metered::new(StdFs));
+        let (tx, rx) = mpsc::channel();
+        let worker = {
+            let tree = self.clone();
+            let metered = Arc::clone(&metered);
+            move || {
+                let root = tree.evaluate(&mut |leaf| {
+                    let (outcome, latency) = timed(|| leaf.evaluate_leaf(&*metered));
+                    // The receiver is gone once the deadline has passed.
+                    let _ = tx.send(Progress::Leaf(leaf.clone(), outcome.clone(), latency));
+                    (outcome, latency)
+                });
+                let _ = tx.send(Progress::Done(root));
+            }
+        };
+        thread::spawn(worker);
+
+        let mut outcomes = HashMap::new();
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            match rx.recv_timeout(remaining) {
+                Ok(Progress::Leaf(leaf, outcome, latency)) => {
+                    outcomes.insert(leaf, (outcome, latency));
+                }
+                Ok(Progress::Done(root)) => return metered.report(root),
+                Err(_) => break,
+            }
+        }
+        // Out of time: fill in what the worker resolved so far.
+        let root = self.evaluate(&mut |leaf| {
+            outcomes
+                .remove(leaf)
+                .unwrap_or((LeafOutcome::Unresolved, Duration::ZERO))
+        });
+        metered.report(root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FileRequirementBuilder, LeafOutcome};
+    use std::fs;
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    #[test]
+    fn check_with_timeout_matches_the_plain_check_in_time() {
+        let td = tempdir().unwrap();
+        fs::write(td.path().join("a.txt"), "").unwrap();
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_file(td.path().join("a.txt")).unwrap();
+        b.require_file(td.path().join("b.txt")).unwrap();
+        let req = b.build();
+
+        let report = req.check_report_with_timeout(Duration::from_secs(60));
+        let outcomes: Vec<&LeafOutcome> = report.leaves().iter().map(|l| l.outcome()).collect();
+        assert_eq!(outcomes, [&LeafOutcome::Present, &LeafOutcome::Missing]);
+        assert_eq!(
+            req.check_with_timeout(Duration::from_secs(60))
+                .unwrap_err()
+                .to_string(),
+            req.check().unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn terms_unresolved_at_the_deadline_are_reported() {
+        let mut b = FileRequirementBuilder::new();
+        b.require_file("a.txt").unwrap();
+        let err = b
+            .build()
+            .check_with_timeout(Duration::ZERO)
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            err,
+            "Required input files were missing or incomplete (not resolved before the deadline: a.txt)"
+        );
+    }
+}
@@ -0,0 +1,81 @@
+use std::ops::Range;
+
+use miette::{Diagnostic, LabeledSpan, SourceCode};
+
+use crate::{CheckReport, FileRequirementCheckError};
+
+/// The rendered expression of a failed check and the spans of its failed terms.
+#[derive(Debug, Clone)]
+pub(crate) struct SourceSpans {
+    expression: String,
+    labels: Vec<(Range<usize>, String)>,
+}
+
+impl SourceSpans {
+    pub(crate) fn from_report(report: &CheckReport) -> Self {
+        let mut expression = String::new();
+        let mut spans = Vec::new();
+        report.root().render_with_spans(&mut expression, &mut spans);
+        let labels = spans
+            .into_iter()
+            .map(|(range, leaf)| (range, leaf.outcome().to_string()))
+            .collect();
+        Self { expression, labels }
+    }
+}
+
+/// With the `miette` feature, the rendered expression is the source code and
+/// every failed term is labeled with its outcome, e.g. `missing`.
+impl Diagnostic for FileRequirementCheckError {
+    fn code<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        Some(Box::new("file_requirements::check"))
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        Some(&self.spans.expression)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        Some(Box::new(self.spans.labels.iter().map(|(range, label)| {
+            LabeledSpan::new(Some(label.clone()), range.start, range.len())
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use miette::Diagnostic;
+
+    use crate::FileRequirementBuilder;
+
+    #[test]
+    fn diagnostics_label_failed_terms_in_the_expression() {
+        let mut b = FileRequirementBuilder::new();
+        b.require_file("idx.ctab").unwrap();
+        b.require_any(|any| {
+            any.require_file("idx.sshash")?;
+            any.require_file("idx.ssi")?;
+            Ok(())
+        })
+        .unwrap();
+        let err = b.build().check_with(|_| Ok(false)).unwrap_err();
+
+        assert_eq!(
+            err.spans.expression,
+            "(idx.ctab AND (idx.sshash OR idx.ssi))"
+        );
+        let labels: Vec<(usize, usize, Option<String>)> = err
+            .labels()
+            .unwrap()
+            .map(|span| (span.offset(), span.len(), span.label().map(str::to_owned)))
+            .collect();
+        assert_eq!(
+            labels,
+            [
+                (1, 8, Some("missing".to_owned())),
+                (15, 10, Some("missing".to_owned())),
+                (29, 7, Some("missing".to_owned())),
+            ]
+        );
+    }
+}
@@ -0,0 +1,893 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::scheme::scheme_of;
+use crate::shards;
+use crate::{
+    Access, FileProvider, FileRequirement, GroupKind, GroupReport, LeafOutcome, LeafReport,
+    NodeReport,
+};
+
+/// How groups are evaluated.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Strategy {
+    /// Stop evaluating an `AND` group at its first unsatisfied child.
+    pub(crate) fail_fast: bool,
+    /// Keep evaluating an `OR` group's alternatives after one is satisfied.
+    pub(crate) exhaustive_any: bool,
+}
+
+impl FileRequirement {
+    /// The term inside any [`FileRequirement::Named`] and
+    /// [`FileRequirement::Hinted`] wrappers.
+    pub(crate) fn unwrapped(&self) -> &FileRequirement {
+        match self {
+            FileRequirement::Named { term, .. } | FileRequirement::Hinted { term, .. } => {
+                term.unwrapped()
+            }
+            _ => self,
+        }
+    }
+
+    /// The outermost name given to this node, if any.
+    pub(crate) fn name(&self) -> Option<&str> {
+        match self {
+            FileRequirement::Named { name, .. } => Some(name),
+            FileRequirement::Hinted { term, .. } => term.name(),
+            _ => None,
+        }
+    }
+
+    /// The outermost remediation hint attached to this node, if any.
+    pub(crate) fn hint(&self) -> Option<&str> {
+        match self {
+            FileRequirement::Hinted { hint, .. } => Some(hint),
+            FileRequirement::Named { term, .. } => term.hint(),
+            _ => None,
+        }
+    }
+
+    /// The operator and children of a group node, or `None` for leaf terms.
+    pub(crate) fn group(&self) -> Option<(GroupKind, &[FileRequirement])> {
+        match self.unwrapped() {
+            FileRequirement::All(children) => Some((GroupKind::All, children)),
+            FileRequirement::Any(children) => Some((GroupKind::Any, children)),
+            FileRequirement::ExactlyOne(children) => Some((GroupKind::ExactlyOne, children)),
+            _ => None,
+        }
+    }
+
+    /// Evaluate the tree, resolving each leaf term with `probe`, which also
+    /// returns how long the term took.
+    pub(crate) fn evaluate<F>(&self, probe: &mut F) -> NodeReport
+    where
+        F: FnMut(&FileRequirement) -> (LeafOutcome, Duration),
+    {
+        self.evaluate_with(Strategy::default(), probe)
+    }
+
+    /// Like [`FileRequirement::evaluate`], evaluating groups as `strategy` says.
+    pub(crate) fn evaluate_with<F>(&self, strategy: Strategy, probe: &mut F) -> NodeReport
+    where
+        F: FnMut(&FileRequirement) -> (LeafOutcome, Duration),
+    {
+        let Some((kind, children)) = self.group() else {
+            let (outcome, latency) = probe(self);
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                path = %self.leaf_path().display(),
+                outcome = %outcome,
+                duration = ?latency,
+                "checked requirement term"
+            );
+            return NodeReport::Leaf(LeafReport::new(self.clone(), outcome).with_latency(latency));
+        };
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!(
+            "requirement_group",
+            operator = kind.operator(),
+            name = self.name()
+        )
+        .entered();
+        let reports: Vec<NodeReport> = match kind {
+            GroupKind::All if strategy.fail_fast => {
+                let mut failed = false;
+                let mut reports = Vec::with_capacity(children.len());
+                for child in children {
+                    if failed {
+                        reports.push(child.skipped());
+                    } else {
+                        let report = child.evaluate_with(strategy, probe);
+                        failed = !report.is_satisfied();
+                        reports.push(report);
+                    }
+                }
+                reports
+            }
+            GroupKind::Any => {
+                let mut satisfied = false;
+                let mut reports = Vec::with_capacity(children.len());
+                for child in children {
+                    if satisfied && !strategy.exhaustive_any {
+                        reports.push(child.skipped());
+                    } else {
+                        let report = child.evaluate_with(strategy, probe);
+                        satisfied |= report.is_satisfied();
+                        reports.push(report);
+                    }
+                }
+                reports
+            }
+            GroupKind::All | GroupKind::ExactlyOne => children
+                .iter()
+                .map(|child| child.evaluate_with(strategy, probe))
+                .collect(),
+        };
+        let satisfied = match kind {
+            GroupKind::All => reports.iter().all(NodeReport::is_satisfied),
+            GroupKind::Any => reports.iter().any(NodeReport::is_satisfied),
+            GroupKind::ExactlyOne => reports.iter().filter(|r| r.is_satisfied()).count() == 1,
+        };
+        NodeReport::Group(
+            GroupReport::new(kind, satisfied, reports)
+                .with_name(self.name())
+                .with_hint(self.hint()),
+        )
+    }
+
+    /// Evaluate a single leaf term against `provider`.
+    ///
+    /// Group nodes are never passed here.
+    pub(crate) fn evaluate_leaf<P: FileProvider + ?Sized>(&self, provider: &P) -> LeafOutcome {
+        match self {
+            FileRequirement::Named { term, .. } | FileRequirement::Hinted { term, .. } => {
+                term.evaluate_leaf(provider)
+            }
+            FileRequirement::File(path) => LeafOutcome::from_exists(provider.try_exists(path)),
+            FileRequirement::Glob {
+                pattern,
+                min_matches,
+            } => match provider.glob(pattern) {
+                Ok(matches) if matches.len() >= *min_matches => LeafOutcome::Present,
+                Ok(matches) => LeafOutcome::TooFewMatches {
+                    found: matches.len(),
+                },
+                Err(e) => LeafOutcome::from_io_error(e),
+            },
+            FileRequirement::GlobSize { pattern, min_bytes } => {
+                evaluate_glob_size(pattern, *min_bytes, provider)
+            }
+            FileRequirement::FileWith { path, constraints } => match provider.metadata(path) {
+                Ok(meta) => constraints.evaluate(path, &meta, provider),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => LeafOutcome::Missing,
+                Err(e) => LeafOutcome::from_io_error(e),
+            },
+            FileRequirement::Dir { path, non_empty } => match provider.metadata(path) {
+                Ok(meta) if !meta.is_dir() => LeafOutcome::NotADirectory,
+                Ok(_) if *non_empty => match provider.read_dir(path) {
+                    Ok(entries) if entries.is_empty() => LeafOutcome::EmptyDirectory,
+                    Ok(_) => LeafOutcome::Present,
+                    Err(e) => LeafOutcome::from_io_error(e),
+                },
+                Ok(_) => LeafOutcome::Present,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => LeafOutcome::Missing,
+                Err(e) => LeafOutcome::from_io_error(e),
+            },
+            FileRequirement::DirEntries {
+                path,
+                pattern,
+                min_entries,
+            } => evaluate_dir_entries(path, pattern.as_deref(), *min_entries, provider),
+            FileRequirement::Absent(path) => match provider.try_exists(path) {
+                Ok(false) => LeafOutcome::Present,
+                Ok(true) => LeafOutcome::AlreadyExists,
+                Err(e) => LeafOutcome::from_io_error(e),
+            },
+            FileRequirement::Recommended(path) => {
+                LeafOutcome::recommended(provider.try_exists(path))
+            }
+            FileRequirement::WritableDir(path) => evaluate_writable_dir(path, provider),
+            FileRequirement::Executable(name) => {
+                evaluate_executable(name, std::env::var_os("PATH"), provider)
+            }
+            FileRequirement::EnvVar {
+                name,
+                non_empty,
+                pattern,
+            } => evaluate_env_var(name, *non_empty, pattern.as_deref()),
+            FileRequirement::Url(url) => LeafOutcome::from_exists(provider.url_exists(url)),
+            FileRequirement::Shards {
+                template,
+                start,
+                end,
+                min,
+            } => shards::evaluate(template, *start..*end, *min, provider),
+            FileRequirement::ArchiveMember { archive, member } => {
+                match provider.archive_contains(archive, member) {
+                    Ok(true) => LeafOutcome::Present,
+                    Ok(false) => LeafOutcome::MemberMissing,
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => LeafOutcome::Missing,
+                    Err(e) => LeafOutcome::from_io_error(e),
+                }
+            }
+            FileRequirement::SameFilesystem { path, other } => {
+                match (device_of(path, provider), device_of(other, provider)) {
+                    (Ok(a), Ok(b)) if a == b => LeafOutcome::Present,
+                    (Ok(_), Ok(_)) => LeafOutcome::DifferentFilesystem,
+                    (Err(e), _) | (_, Err(e)) => LeafOutcome::from_io_error(e),
+                }
+            }
+            FileRequirement::FreeSpace { path, bytes } => {
+                let measured = nearest_existing(path, provider)
+                    .and_then(|existing| provider.available_space(existing));
+                match measured {
+                    Ok(available) if available >= *bytes => LeafOutcome::Present,
+                    Ok(available) => LeafOutcome::InsufficientSpace { available },
+                    Err(e) => LeafOutcome::from_io_error(e),
+                }
+            }
+            FileRequirement::All(_) | FileRequirement::Any(_) | FileRequirement::ExactlyOne(_) => {
+                unreachable!("group nodes are not leaf terms")
+            }
+        }
+    }
+
+    /// Report for a subtree that was not evaluated.
+    pub(crate) fn skipped(&self) -> NodeReport {
+        match self.group() {
+            None => NodeReport::Leaf(LeafReport::new(self.clone(), LeafOutcome::Skipped)),
+            Some((kind, children)) => NodeReport::Group(
+                GroupReport::new(kind, false, children.iter().map(Self::skipped).collect())
+                    .with_name(self.name())
+                    .with_hint(self.hint()),
+            ),
+        }
+    }
+
+    /// The path of a leaf term (the pattern, for glob terms).
+    ///
+    /// Group nodes yield an empty path.
+    pub(crate) fn leaf_path(&self) -> &Path {
+        match self {
+            FileRequirement::Named { term, .. } | FileRequirement::Hinted { term, .. } => {
+                term.leaf_path()
+            }
+            FileRequirement::File(path)
+            | FileRequirement::FileWith { path, .. }
+            | FileRequirement::Dir { path, .. }
+            | FileRequirement::DirEntries { path, .. }
+            | FileRequirement::Absent(path)
+            | FileRequirement::Recommended(path)
+            | FileRequirement::WritableDir(path)
+            | FileRequirement::FreeSpace { path, .. }
+            | FileRequirement::SameFilesystem { path, .. }
+            | FileRequirement::ArchiveMember { archive: path, .. }
+            | FileRequirement::Executable(path) => path,
+            FileRequirement::EnvVar { name, .. } | FileRequirement::Url(name) => Path::new(name),
+            FileRequirement::Glob { pattern, .. }
+            | FileRequirement::GlobSize { pattern, .. }
+            | FileRequirement::Shards {
+                template: pattern, ..
+            } => Path::new(pattern),
+            FileRequirement::All(_) | FileRequirement::Any(_) | FileRequirement::ExactlyOne(_) => {
+                Path::new("")
+            }
+        }
+    }
+
+    /// A copy of the tree with every file, directory and dependency path passed
+    /// through `f`. Glob patterns are left unchanged.
+    pub(crate) fn map_paths<F>(&self, f: &mut F) -> FileRequirement
+    where
+        F: FnMut(&Path) -> PathBuf,
+    {
+        match self {
+            FileRequirement::File(path) => FileRequirement::File(f(path)),
+            FileRequirement::FileWith { path, constraints } => {
+                let mut constraints = constraints.clone();
+                if let Some(dependency) = &constraints.newer_than {
+                    constraints.newer_than = Some(f(dependency));
+                }
+                FileRequirement::FileWith {
+                    path: f(path),
+                    constraints,
+                }
+            }
+            FileRequirement::Glob { .. }
+            | FileRequirement::GlobSize { .. }
+            | FileRequirement::Executable(_)
+            | FileRequirement::EnvVar { .. }
+            | FileRequirement::Url(_) => self.clone(),
+            FileRequirement::Shards {
+                template,
+                start,
+                end,
+                min,
+            } => FileRequirement::Shards {
+                template: shards::map_template(template, |template| {
+                    f(Path::new(template)).to_string_lossy().into_owned()
+                }),
+                start: *start,
+                end: *end,
+                min: *min,
+            },
+            FileRequirement::Absent(path) => FileRequirement::Absent(f(path)),
+            FileRequirement::Recommended(path) => FileRequirement::Recommended(f(path)),
+            FileRequirement::WritableDir(path) => FileRequirement::WritableDir(f(path)),
+            FileRequirement::ArchiveMember { archive, member } => FileRequirement::ArchiveMember {
+                archive: f(archive),
+                member: member.clone(),
+            },
+            FileRequirement::SameFilesystem { path, other } => FileRequirement::SameFilesystem {
+                path: f(path),
+                other: f(other),
+            },
+            FileRequirement::FreeSpace { path, bytes } => FileRequirement::FreeSpace {
+                path: f(path),
+                bytes: *bytes,
+            },
+            FileRequirement::Dir { path, non_empty } => FileRequirement::Dir {
+                path: f(path),
+                non_empty: *non_empty,
+            },
+            FileRequirement::DirEntries {
+                path,
+                pattern,
+                min_entries,
+            } => FileRequirement::DirEntries {
+                path: f(path),
+                pattern: pattern.clone(),
+                min_entries: *min_entries,
+            },
+            FileRequirement::Named { name, term } => FileRequirement::Named {
+                name: name.clone(),
+                term: Box::new(term.map_paths(f)),
+            },
+            FileRequirement::Hinted { hint, term } => FileRequirement::Hinted {
+                hint: hint.clone(),
+                term: Box::new(term.map_paths(f)),
+            },
+            FileRequirement::All(children) => {
+                FileRequirement::All(children.iter().map(|c| c.map_paths(f)).collect())
+            }
+            FileRequirement::Any(children) => {
+                FileRequirement::Any(children.iter().map(|c| c.map_paths(f)).collect())
+            }
+            FileRequirement::ExactlyOne(children) => {
+                FileRequirement::ExactlyOne(children.iter().map(|c| c.map_paths(f)).collect())
+            }
+        }
+    }
+
+    /// A copy of the tree with relative paths and glob patterns joined onto `base`.
+    pub(crate) fn rebased(&self, base: &Path) -> FileRequirement {
+        let rebased = self.map_paths(&mut |path| match path.to_str().and_then(scheme_of) {
+            Some(_) => path.to_path_buf(),
+            None => base.join(path),
+        });
+        rebased.map_globs(&mut |pattern| {
+            if Path::new(pattern).is_absolute() || scheme_of(pattern).is_some() {
+                pattern.to_owned()
+            } else {
+                let escaped = glob::Pattern::escape(&base.to_string_lossy());
+                Path::new(&escaped)
+                    .join(pattern)
+                    .to_string_lossy()
+                    .into_owned()
+            }
+        })
+    }
+
+    /// A copy of the tree with every UTF-8 path and every glob pattern passed
+    /// through `f` as text.
+    pub(crate) fn map_text<F>(&self, f: &mut F) -> FileRequirement
+    where
+        F: FnMut(&str) -> String,
+    {
+        self.map_paths(&mut |path| match path.to_str() {
+            Some(text) => PathBuf::from(f(text)),
+            None => path.to_path_buf(),
+        })
+        .map_globs(f)
+    }
+
+    /// The tree with every glob pattern passed through `f`.
+    pub(crate) fn map_globs<F>(self, f: &mut F) -> FileRequirement
+    where
+        F: FnMut(&str) -> String,
+    {
+        let map_children = |children: Vec<FileRequirement>, f: &mut F| {
+            children.into_iter().map(|c| c.map_globs(f)).collect()
+        };
+        match self {
+            FileRequirement::Glob {
+                pattern,
+                min_matches,
+            } => FileRequirement::Glob {
+                pattern: f(&pattern),
+                min_matches,
+            },
+            FileRequirement::GlobSize { pattern, min_bytes } => FileRequirement::GlobSize {
+                pattern: f(&pattern),
+                min_bytes,
+            },
+            FileRequirement::Named { name, term } => FileRequirement::Named {
+                name,
+                term: Box::new(term.map_globs(f)),
+            },
+            FileRequirement::Hinted { hint, term } => FileRequirement::Hinted {
+                hint,
+                term: Box::new(term.map_globs(f)),
+            },
+            FileRequirement::All(children) => FileRequirement::All(map_children(children, f)),
+            FileRequirement::Any(children) => FileRequirement::Any(map_children(children, f)),
+            FileRequirement::ExactlyOne(children) => {
+                FileRequirement::ExactlyOne(map_children(children, f))
+            }
+            leaf => leaf,
+        }
+    }
+
+    /// Distinct leaf terms, in depth-first order of first appearance.
+    #[cfg(feature = "rayon")]
+    pub(crate) fn distinct_leaves(&self) -> Vec<&FileRequirement> {
+        fn walk<'a>(
+            node: &'a FileRequirement,
+            seen: &mut std::collections::HashSet<&'a FileRequirement>,
+            out: &mut Vec<&'a FileRequirement>,
+        ) {
+            match node.group() {
+                None => {
+                    if seen.insert(node) {
+                        out.push(node);
+                    }
+                }
+                Some((_, children)) => {
+                    for child in children {
+                        walk(child, seen, out);
+                    }
+                }
+            }
+        }
+        let mut out = Vec::new();
+        walk(self, &mut std::collections::HashSet::new(), &mut out);
+        out
+    }
+
+    /// Aggregate precomputed per-leaf outcomes into a report tree.
+    #[cfg(any(feature = "tokio", feature = "rayon"))]
+    pub(crate) fn evaluate_outcomes(
+        &self,
+        outcomes: &std::collections::HashMap<&FileRequirement, (LeafOutcome, Duration)>,
+    ) -> NodeReport {
+        self.evaluate(&mut |leaf| {
+            outcomes
+                .get(leaf)
+                .cloned()
+                .unwrap_or((LeafOutcome::Skipped, Duration::ZERO))
+        })
+    }
+}
+
+/// Sum the sizes of the files matching `pattern`. Matches removed since the
+/// glob was expanded count as zero bytes.
+fn evaluate_glob_size<P: FileProvider + ?Sized>(
+    pattern: &str,
+    min_bytes: u64,
+    provider: &P,
+) -> LeafOutcome {
+    let matches = match provider.glob(pattern) {
+        Ok(matches) => matches,
+        Err(e) => return LeafOutcome::from_io_error(e),
+    };
+    let mut total = 0u64;
+    for path in &matches {
+        match provider.metadata(path) {
+            Ok(meta) => total = total.saturating_add(meta.len()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return LeafOutcome::from_io_error(e),
+        }
+    }
+    if total >= min_bytes {
+        LeafOutcome::Present
+    } else {
+        LeafOutcome::SizeOutOfRange { actual: total }
+    }
+}
+
+/// Count the entries of the directory `path` whose file names match `pattern`.
+fn evaluate_dir_entries<P: FileProvider + ?Sized>(
+    path: &Path,
+    pattern: Option<&str>,
+    min_entries: usize,
+    provider: &P,
+) -> LeafOutcome {
+    let pattern = match pattern.map(glob::Pattern::new).transpose() {
+        Ok(pattern) => pattern,
+        Err(e) => {
+            let error = io::Error::new(io::ErrorKind::InvalidInput, e.to_string());
+            return LeafOutcome::from_io_error(error);
+        }
+    };
+    match provider.metadata(path) {
+        Ok(meta) if !meta.is_dir() => return LeafOutcome::NotADirectory,
+        Ok(_) => {}
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return LeafOutcome::Missing,
+        Err(e) => return LeafOutcome::from_io_error(e),
+    }
+    let entries = match provider.read_dir(path) {
+        Ok(entries) => entries,
+        Err(e) => return LeafOutcome::from_io_error(e),
+    };
+    let found = entries
+        .iter()
+        .filter(|entry| match (&pattern, entry.file_name()) {
+            (None, _) => true,
+            (Some(pattern), Some(name)) => pattern.matches(&name.to_string_lossy()),
+            (Some(_), None) => false,
+        })
+        .count();
+    if found >= min_entries {
+        LeafOutcome::Present
+    } else {
+        LeafOutcome::TooFewMatches { found }
+    }
+}
+
+/// An existing directory must grant write access; a missing one must have a
+/// writable directory as its nearest existing ancestor.
+fn evaluate_writable_dir<P: FileProvider + ?Sized>(path: &Path, provider: &P) -> LeafOutcome {
+    let is_writable_dir = |dir: &Path| match provider.metadata(dir) {
+        Ok(meta) if !meta.is_dir() => Ok(Some(false)),
+        Ok(_) => provider.access(dir, Access::Write).map(Some),
+        // A file somewhere up the chain shows up as `NotADirectory`; the walk
+        // below then reports that file as the blocking ancestor.
+        Err(e)
+            if matches!(
+                e.kind(),
+                io::ErrorKind::NotFound | io::ErrorKind::NotADirectory
+            ) =>
+        {
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    };
+    match is_writable_dir(path) {
+        Ok(Some(true)) => return LeafOutcome::Present,
+        Ok(Some(false)) => {
+            return match provider.metadata(path) {
+                Ok(meta) if !meta.is_dir() => LeafOutcome::NotADirectory,
+                _ => LeafOutcome::PermissionDenied {
+                    access: Access::Write,
+                },
+            };
+        }
+        Ok(None) => {}
+        Err(e) => return LeafOutcome::from_io_error(e),
+    }
+    for ancestor in proper_ancestors(path) {
+        match is_writable_dir(ancestor) {
+            Ok(Some(true)) => return LeafOutcome::Present,
+            Ok(Some(false)) => {
+                return LeafOutcome::CannotCreate {
+                    ancestor: ancestor.to_path_buf(),
+                };
+            }
+            Ok(None) => {}
+            Err(e) => return LeafOutcome::from_io_error(e),
+        }
+    }
+    LeafOutcome::Missing
+}
+
+/// Ancestors of `path`, nearest first; a relative path's chain ends at `.`.
+fn proper_ancestors(path: &Path) -> impl Iterator<Item = &Path> {
+    path.ancestors().skip(1).map(|dir| {
+        if dir.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            dir
+        }
+    })
+}
+
+/// `path` itself if it exists, otherwise its nearest existing ancestor.
+fn nearest_existing<'a, P: FileProvider + ?Sized>(
+    path: &'a Path,
+    provider: &P,
+) -> io::Result<&'a Path> {
+    for candidate in std::iter::once(path).chain(proper_ancestors(path)) {
+        if provider.try_exists(candidate)? {
+            return Ok(candidate);
+        }
+    }
+    Err(io::Error::from(io::ErrorKind::NotFound))
+}
+
+/// The device id of `path`, or of its nearest existing ancestor.
+fn device_of<P: FileProvider + ?Sized>(path: &Path, provider: &P) -> io::Result<u64> {
+    let existing = nearest_existing(path, provider)?;
+    provider.metadata(existing)?.device().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            "device ids are not reported on this platform",
+        )
+    })
+}
+
+fn evaluate_env_var(name: &str, non_empty: bool, pattern: Option<&str>) -> LeafOutcome {
+    let Some(value) = std::env::var_os(name) else {
+        return LeafOutcome::UnsetVariable {
+            name: name.to_owned(),
+        };
+    };
+    if non_empty && value.is_empty() {
+        return LeafOutcome::EmptyVariable;
+    }
+    let Some(pattern) = pattern else {
+        return LeafOutcome::Present;
+    };
+    #[cfg(feature = "regex")]
+    {
+        let value = value.to_string_lossy();
+        match compile_anchored(pattern) {
+            Ok(re) if re.is_match(&value) => LeafOutcome::Present,
+            Ok(_) => LeafOutcome::VariableMismatch {
+                length: value.chars().count(),
+            },
+            Err(e) => LeafOutcome::from_io_error(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                e.to_string(),
+            )),
+        }
+    }
+    #[cfg(not(feature = "regex"))]
+    {
+        let _ = pattern;
+        LeafOutcome::from_io_error(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "environment variable patterns require the `regex` feature",
+        ))
+    }
+}
+
+/// Check that `name` is a portable environment variable name,
+/// `[A-Za-z_][A-Za-z0-9_]*`, as the shell export relies on.
+pub(crate) fn check_env_var_name(name: &str) -> Result<(), crate::FileRequirementBuildError> {
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(crate::FileRequirementBuildError::InvalidEnvVar {
+            name: name.to_owned(),
+        });
+    }
+    Ok(())
+}
+
+/// Deserialize an environment variable name, rejecting invalid ones.
+#[cfg(feature = "serde")]
+pub(crate) fn deserialize_env_var_name<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let name = <String as serde::Deserialize>::deserialize(deserializer)?;
+    check_env_var_name(&name).map_err(serde::de::Error::custom)?;
+    Ok(name)
+}
+
+/// Compile `pattern` so that it must match a whole value.
+#[cfg(feature = "regex")]
+pub(crate) fn compile_anchored(
+    pattern: &str,
+) -> Result<regex::Regex, crate::FileRequirementBuildError> {
+    regex::Regex::new(&format!("^(?:{})$", pattern)).map_err(|e| {
+        crate::FileRequirementBuildError::InvalidRegex {
+            pattern: pattern.to_owned(),
+            message: e.to_string(),
+        }
+    })
+}
+
+/// Search `path_var` for an executable file called `name`.
+fn evaluate_executable<P: FileProvider + ?Sized>(
+    name: &Path,
+    path_var: Option<std::ffi::OsString>,
+    provider: &P,
+) -> LeafOutcome {
+    let is_executable_file = |candidate: &Path| match provider.metadata(candidate) {
+        Ok(meta) if meta.is_file() => provider.access(candidate, Access::Execute),
+        Ok(_) => Ok(false),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    };
+    let candidates = |base: PathBuf| {
+        let mut candidates = vec![base.clone()];
+        if cfg!(windows) && base.extension().is_none() {
+            let pathext = std::env::var("PATHEXT").unwrap_or_else(|_| ".EXE;.BAT;.CMD;.COM".into());
+            for ext in pathext.split(';').filter(|ext| !ext.is_empty()) {
+                let mut with_ext = base.clone().into_os_string();
+                with_ext.push(ext);
+                candidates.push(PathBuf::from(with_ext));
+            }
+        }
+        candidates
+    };
+
+    if name.components().count() > 1 {
+        for candidate in candidates(name.to_path_buf()) {
+            match is_executable_file(&candidate) {
+                Ok(true) => return LeafOutcome::Present,
+                Ok(false) => {}
+                Err(e) => return LeafOutcome::from_io_error(e),
+            }
+        }
+        return LeafOutcome::Missing;
+    }
+
+    let searched: Vec<PathBuf> = path_var
+        .map(|var| {
+            std::env::split_paths(&var)
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+    for dir in &searched {
+        for candidate in candidates(dir.join(name)) {
+            // Unreadable PATH entries are skipped, as a shell would.
+            if is_executable_file(&candidate).unwrap_or(false) {
+                return LeafOutcome::Present;
+            }
+        }
+    }
+    LeafOutcome::NotOnPath { searched }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::evaluate_executable;
+    use crate::{LeafOutcome, StdFs};
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    #[cfg(unix)]
+    #[test]
+    fn executable_terms_search_path_directories() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let bin = tempdir().unwrap();
+        let empty = tempdir().unwrap();
+        let tool = bin.path().join("samtools");
+        std::fs::write(&tool, "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(&tool, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let path_var = std::env::join_paths([empty.path(), bin.path()]).unwrap();
+
+        let samtools = Path::new("samtools");
+        assert_eq!(
+            evaluate_executable(samtools, Some(path_var.clone()), &StdFs),
+            LeafOutcome::Present
+        );
+        assert_eq!(
+            evaluate_executable(Path::new("bcftools"), Some(path_var), &StdFs),
+            LeafOutcome::NotOnPath {
+                searched: vec![empty.path().to_path_buf(), bin.path().to_path_buf()]
+            }
+        );
+
+        std::fs::set_permissions(&tool, std::fs::Permissions::from_mode(0o644)).unwrap();
+        assert_eq!(
+            evaluate_executable(&tool, None, &StdFs),
+            LeafOutcome::Missing
+        );
+    }
+
+    #[test]
+    fn recommended_terms_warn_on_io_errors() {
+        struct Denied;
+
+        impl crate::FileProvider for Denied {
+            fn try_exists(&self, _: &Path) -> std::io::Result<bool> {
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::PermissionDenied,
+                    "access denied",
+                ))
+            }
+        }
+
+        let req = crate::FileRequirement::All(vec![crate::FileRequirement::Recommended(
+            "decoys.txt".into(),
+        )]);
+        assert!(req.check_with_provider(&Denied).is_ok());
+        let advisories = req.check_report_with_provider(&Denied).advisories();
+        assert_eq!(advisories.len(), 1);
+        assert_eq!(advisories[0].severity(), crate::Severity::Warning);
+        assert_eq!(
+            advisories[0].message(),
+            "recommended file could not be checked: decoys.txt (access denied)"
+        );
+    }
+
+    #[test]
+    fn env_var_terms_distinguish_unset_and_empty_variables() {
+        let req: crate::FileRequirement =
+            "(env(PATH, non_empty) AND env(FILE_REQUIREMENTS_UNSET_TEST_VAR))"
+                .parse()
+                .unwrap();
+        assert_eq!(
+            req.check().unwrap_err().to_string(),
+            "Required input files were missing or incomplete (unset environment variables: $FILE_REQUIREMENTS_UNSET_TEST_VAR)"
+        );
+        assert_eq!(
+            super::evaluate_env_var("PATH", false, None),
+            LeafOutcome::Present
+        );
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn env_var_patterns_must_match_the_whole_value() {
+        let path = std::env::var("PATH").unwrap();
+        let first = path.chars().next().unwrap().to_string();
+        assert_eq!(
+            super::evaluate_env_var("PATH", false, Some(&format!("{}.*", regex::escape(&first)))),
+            LeafOutcome::Present
+        );
+        let outcome = super::evaluate_env_var("PATH", false, Some(&regex::escape(&first)));
+        assert_eq!(
+            outcome,
+            LeafOutcome::VariableMismatch {
+                length: path.chars().count()
+            }
+        );
+        assert!(!outcome.to_string().contains(&path));
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn evaluation_emits_group_spans_and_leaf_events() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata, subscriber::Subscriber};
+
+        #[derive(Default)]
+        struct Counter {
+            spans: AtomicU64,
+            events: AtomicU64,
+        }
+
+        impl Subscriber for &'static Counter {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, _: &Attributes<'_>) -> Id {
+                Id::from_u64(self.spans.fetch_add(1, Ordering::SeqCst) + 1)
+            }
+            fn record(&self, _: &Id, _: &Record<'_>) {}
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+            fn event(&self, _: &Event<'_>) {
+                self.events.fetch_add(1, Ordering::SeqCst);
+            }
+            fn enter(&self, _: &Id) {}
+            fn exit(&self, _: &Id) {}
+        }
+
+        let mut b = crate::FileRequirementBuilder::new();
+        b.require_file("a.txt").unwrap();
+        b.require_any(|any| {
+            any.require_file("b.txt")?;
+            any.require_file("c.txt")?;
+            Ok(())
+        })
+        .unwrap();
+        let req = b.build();
+
+        let counter: &'static Counter = Box::leak(Box::default());
+        let listing: std::collections::HashSet<_> = [std::path::PathBuf::from("b.txt")].into();
+        tracing::subscriber::with_default(counter, || req.check_report_with_provider(&listing));
+        assert_eq!(counter.spans.load(Ordering::SeqCst), 2);
+        assert_eq!(counter.events.load(Ordering::SeqCst), 2);
+    }
+}
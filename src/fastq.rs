@@ -0,0 +1,187 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::{FileRequirement, FileRequirementBuildError, FileRequirementBuilder};
+
+/// Naming conventions that pair an R1 FASTQ file with its R2 mate.
+///
+/// Each convention is a pair of markers, such as `_R1_` / `_R2_`; the mate of
+/// an R1 file is named by replacing the last occurrence of the R1 marker in its
+/// file name. Conventions are tried in order, custom ones first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadPairing {
+    conventions: Vec<(String, String)>,
+}
+
+impl ReadPairing {
+    /// The Illumina `_R1_` / `_R2_` and `_R1.` / `_R2.` conventions, and the
+    /// SRA-style `_1.` / `_2.` one (`_1.fastq.gz`, `_1.fq.gz`).
+    pub fn new() -> Self {
+        Self {
+            conventions: [("_R1_", "_R2_"), ("_R1.", "_R2."), ("_1.", "_2.")]
+                .into_iter()
+                .map(|(r1, r2)| (r1.to_owned(), r2.to_owned()))
+                .collect(),
+        }
+    }
+
+    /// Try the `r1` / `r2` marker pair, such as `.read1.` / `.read2.`, before
+    /// the conventions already added.
+    pub fn convention<S: Into<String>, T: Into<String>>(mut self, r1: S, r2: T) -> Self {
+        self.conventions.insert(0, (r1.into(), r2.into()));
+        self
+    }
+
+    /// The path of the R2 mate of `r1`, if its file name follows a convention.
+    pub fn mate_of(&self, r1: &Path) -> Option<PathBuf> {
+        let name = r1.file_name()?.to_str()?;
+        self.conventions.iter().find_map(|(r1_marker, r2_marker)| {
+            let at = name.rfind(r1_marker.as_str())?;
+            let mate = format!(
+                "{}{}{}",
+                &name[..at],
+                r2_marker,
+                &name[at + r1_marker.len()..]
+            );
+            Some(r1.with_file_name(mate))
+        })
+    }
+}
+
+impl Default for ReadPairing {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Errors produced while building a paired FASTQ requirement.
+#[derive(Debug, Error)]
+pub enum PairedFastqError {
+    /// A listed R1 file follows none of the naming conventions.
+    #[error("`{path}` does not follow any R1 naming convention")]
+    NotReadOne { path: String },
+    /// A directory holds no FASTQ file following an R1 naming convention.
+    #[error("No R1 FASTQ files found in `{dir}`")]
+    NoReadOnes { dir: String },
+    /// The directory could not be listed.
+    #[error("Failed to list `{dir}`: {source}")]
+    Io {
+        dir: String,
+        #[source]
+        source: io::Error,
+    },
+    /// A file was listed more than once.
+    #[error(transparent)]
+    Build(#[from] FileRequirementBuildError),
+}
+
+impl FileRequirement {
+    /// Require every file of `r1_files` together with its R2 mate.
+    ///
+    /// Each mate is named `mate of <R1 file>`, so a failed check lists the
+    /// unpaired R1 files: `missing files: mate of s1_R1_001.fastq.gz
+    /// (s1_R2_001.fastq.gz)`.
+    pub fn paired_fastq<I, P>(
+        r1_files: I,
+        pairing: &ReadPairing,
+    ) -> Result<FileRequirement, PairedFastqError>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let mut builder = FileRequirementBuilder::new();
+        for r1 in r1_files {
+            let r1 = r1.as_ref();
+            let mate = pairing
+                .mate_of(r1)
+                .ok_or_else(|| PairedFastqError::NotReadOne {
+                    path: r1.display().to_string(),
+                })?;
+            let name = r1.file_name().unwrap_or(r1.as_os_str()).to_string_lossy();
+            builder.require_file(r1)?;
+            builder.require_file_named(format!("mate of {}", name), mate)?;
+        }
+        Ok(builder.build())
+    }
+
+    /// Require the R2 mate of every R1 FASTQ file (`.fastq` or `.fq`, possibly
+    /// compressed) in `dir`, as [`FileRequirement::paired_fastq`] does.
+    ///
+    /// R1 files are taken in file-name order. A directory with none is an
+    /// error, so a wrong path is not mistaken for a complete one.
+    pub fn paired_fastq_in<P: AsRef<Path>>(
+        dir: P,
+        pairing: &ReadPairing,
+    ) -> Result<FileRequirement, PairedFastqError> {
+        let dir = dir.as_ref();
+        let io_error = |source| PairedFastqError::Io {
+            dir: dir.display().to_string(),
+            source,
+        };
+        let mut r1_files = Vec::new();
+        for entry in fs::read_dir(dir).map_err(io_error)? {
+            let path = entry.map_err(io_error)?.path();
+            let is_fastq = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.contains(".fastq") || name.contains(".fq"));
+            if is_fastq && path.is_file() && pairing.mate_of(&path).is_some() {
+                r1_files.push(path);
+            }
+        }
+        if r1_files.is_empty() {
+            return Err(PairedFastqError::NoReadOnes {
+                dir: dir.display().to_string(),
+            });
+        }
+        r1_files.sort();
+        Self::paired_fastq(r1_files, pairing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn mates_follow_the_first_matching_convention() {
+        let pairing = ReadPairing::new().convention(".read1.", ".read2.");
+        let mate = |name: &str| pairing.mate_of(Path::new(name)).unwrap();
+        assert_eq!(
+            mate("run1/S1_L001_R1_001.fastq.gz"),
+            Path::new("run1/S1_L001_R2_001.fastq.gz")
+        );
+        assert_eq!(mate("SRR1_1.fastq.gz"), Path::new("SRR1_2.fastq.gz"));
+        assert_eq!(mate("s1.read1.fq"), Path::new("s1.read2.fq"));
+        assert!(pairing.mate_of(Path::new("s1.fq.gz")).is_none());
+    }
+
+    #[test]
+    fn unpaired_r1_files_are_listed() {
+        let td = tempdir().unwrap();
+        for name in [
+            "a_R1_001.fastq.gz",
+            "a_R2_001.fastq.gz",
+            "b_1.fq.gz",
+            "notes.txt",
+        ] {
+            fs::write(td.path().join(name), "").unwrap();
+        }
+
+        let req = FileRequirement::paired_fastq_in(td.path(), &ReadPairing::new()).unwrap();
+        assert_eq!(req.paths().len(), 4);
+        let err = req.check().unwrap_err();
+        assert_eq!(err.missing_files(), [td.path().join("b_2.fq.gz")]);
+        assert!(err.to_string().contains("mate of b_1.fq.gz"));
+
+        let empty = tempdir().unwrap();
+        assert!(matches!(
+            FileRequirement::paired_fastq_in(empty.path(), &ReadPairing::new()),
+            Err(PairedFastqError::NoReadOnes { .. })
+        ));
+    }
+}
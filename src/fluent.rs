@@ -0,0 +1,172 @@
+use std::path::Path;
+
+use crate::{FileConstraints, FileRequirementBuildError, FileRequirementBuilder, GroupBuilder};
+
+/// Owned, chainable counterparts of the `require_*` methods, so a tree can be
+/// built in a single expression:
+///
+/// ```
+/// use file_requirements::FileRequirementBuilder;
+///
+/// let req = FileRequirementBuilder::new()
+///     .file("idx.ctab")?
+///     .any(|g| {
+///         g.require_file("idx.sshash")?;
+///         g.require_file("idx.ssi")?;
+///         Ok(())
+///     })?
+///     .build();
+/// assert_eq!(req.to_string(), "(idx.ctab AND (idx.sshash OR idx.ssi))");
+/// # Ok::<(), file_requirements::FileRequirementBuildError>(())
+/// ```
+impl FileRequirementBuilder {
+    /// Add a required file to the root conjunction; see
+    /// [`require_file`](Self::require_file).
+    pub fn file<P: AsRef<Path>>(mut self, path: P) -> Result<Self, FileRequirementBuildError> {
+        self.require_file(path)?;
+        Ok(self)
+    }
+
+    /// Add a required file with extra constraints to the root conjunction; see
+    /// [`require_file_with`](Self::require_file_with).
+    pub fn file_with<P: AsRef<Path>, C: Into<FileConstraints>>(
+        mut self,
+        path: P,
+        constraints: C,
+    ) -> Result<Self, FileRequirementBuildError> {
+        self.require_file_with(path, constraints)?;
+        Ok(self)
+    }
+
+    /// Add a named required file to the root conjunction; see
+    /// [`require_file_named`](Self::require_file_named).
+    pub fn file_named<S: Into<String>, P: AsRef<Path>>(
+        mut self,
+        name: S,
+        path: P,
+    ) -> Result<Self, FileRequirementBuildError> {
+        self.require_file_named(name, path)?;
+        Ok(self)
+    }
+
+    /// Add a required directory to the root conjunction; see
+    /// [`require_dir`](Self::require_dir).
+    pub fn dir<P: AsRef<Path>>(mut self, path: P) -> Result<Self, FileRequirementBuildError> {
+        self.require_dir(path)?;
+        Ok(self)
+    }
+
+    /// Add a glob term to the root conjunction; see
+    /// [`require_glob`](Self::require_glob).
+    pub fn glob<S: Into<String>>(
+        mut self,
+        pattern: S,
+        min_matches: usize,
+    ) -> Result<Self, FileRequirementBuildError> {
+        self.require_glob(pattern, min_matches)?;
+        Ok(self)
+    }
+
+    /// Add an output path that must not exist yet to the root conjunction;
+    /// see [`require_absent`](Self::require_absent).
+    pub fn absent<P: AsRef<Path>>(mut self, path: P) -> Result<Self, FileRequirementBuildError> {
+        self.require_absent(path)?;
+        Ok(self)
+    }
+
+    /// Add a nested conjunction (`AND`); see
+    /// [`require_all`](Self::require_all).
+    pub fn all<F>(mut self, f: F) -> Result<Self, FileRequirementBuildError>
+    where
+        F: FnOnce(&mut GroupBuilder<'_>) -> Result<(), FileRequirementBuildError>,
+    {
+        self.require_all(f)?;
+        Ok(self)
+    }
+
+    /// Add a nested disjunction (`OR`); see
+    /// [`require_any`](Self::require_any).
+    pub fn any<F>(mut self, f: F) -> Result<Self, FileRequirementBuildError>
+    where
+        F: FnOnce(&mut GroupBuilder<'_>) -> Result<(), FileRequirementBuildError>,
+    {
+        self.require_any(f)?;
+        Ok(self)
+    }
+
+    /// Add a named disjunction (`OR`); see
+    /// [`require_any_named`](Self::require_any_named).
+    pub fn any_named<S, F>(mut self, name: S, f: F) -> Result<Self, FileRequirementBuildError>
+    where
+        S: Into<String>,
+        F: FnOnce(&mut GroupBuilder<'_>) -> Result<(), FileRequirementBuildError>,
+    {
+        self.require_any_named(name, f)?;
+        Ok(self)
+    }
+
+    /// Add a nested mutually exclusive group (`XOR`); see
+    /// [`require_exactly_one`](Self::require_exactly_one).
+    pub fn exactly_one<F>(mut self, f: F) -> Result<Self, FileRequirementBuildError>
+    where
+        F: FnOnce(&mut GroupBuilder<'_>) -> Result<(), FileRequirementBuildError>,
+    {
+        self.require_exactly_one(f)?;
+        Ok(self)
+    }
+
+    /// Attach a remediation hint to the most recently added term or group; see
+    /// [`with_hint`](Self::with_hint).
+    pub fn hint<S: Into<String>>(mut self, hint: S) -> Self {
+        self.with_hint(hint);
+        self
+    }
+
+    /// Resolve relative terms against `dir` when building; see
+    /// [`base_dir`](Self::base_dir).
+    pub fn in_dir<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.base_dir(dir);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chained_calls_match_the_mutable_builder() {
+        let chained = FileRequirementBuilder::new()
+            .file("idx.ctab")
+            .and_then(|b| b.dir("reads"))
+            .and_then(|b| {
+                b.exactly_one(|g| {
+                    g.require_file("idx.sshash")?;
+                    g.require_file("idx.ssi")?;
+                    Ok(())
+                })
+            })
+            .map(|b| b.hint("run piscem build").in_dir("/data"))
+            .unwrap()
+            .build();
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_file("idx.ctab").unwrap();
+        b.require_dir("reads").unwrap();
+        b.require_exactly_one(|g| {
+            g.require_file("idx.sshash")?;
+            g.require_file("idx.ssi")?;
+            Ok(())
+        })
+        .unwrap();
+        b.with_hint("run piscem build").base_dir("/data");
+        assert_eq!(chained, b.build());
+
+        assert!(matches!(
+            FileRequirementBuilder::new()
+                .file("a.txt")
+                .and_then(|b| b.file("a.txt")),
+            Err(FileRequirementBuildError::DuplicateFile { .. })
+        ));
+    }
+}
@@ -0,0 +1,120 @@
+use std::io::{self, Read};
+
+use flate2::read::MultiGzDecoder;
+
+/// The empty block that terminates every BGZF file.
+const BGZF_EOF: [u8; 28] = [
+    0x1f, 0x8b, 0x08, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0x06, 0x00, 0x42, 0x43, 0x02, 0x00,
+    0x1b, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+/// Decode the gzip stream in `reader` to its end, returning a description of
+/// the first problem found, or `None` if the stream is intact.
+///
+/// Every member is decompressed, so a truncated or corrupt member fails its
+/// CRC or length check; BGZF files, recognized by the `BC` extra subfield of
+/// their first header, must also end with the BGZF EOF block.
+pub(crate) fn verify<R: Read>(mut reader: R) -> io::Result<Option<String>> {
+    let mut header = Vec::with_capacity(16);
+    (&mut reader).take(16).read_to_end(&mut header)?;
+    if header.len() < 10 || header[..3] != [0x1f, 0x8b, 0x08] {
+        return Ok(Some("not a gzip file".to_owned()));
+    }
+    let bgzf = header[3] & 0x04 != 0 && header.get(12..14) == Some(b"BC");
+
+    let mut tail = Tail {
+        inner: io::Cursor::new(header).chain(reader),
+        last: Vec::with_capacity(2 * BGZF_EOF.len()),
+    };
+    let decoded = io::copy(&mut MultiGzDecoder::new(&mut tail), &mut io::sink());
+    match decoded {
+        Ok(_) => {}
+        Err(e)
+            if matches!(
+                e.kind(),
+                io::ErrorKind::UnexpectedEof
+                    | io::ErrorKind::InvalidData
+                    | io::ErrorKind::InvalidInput
+            ) =>
+        {
+            return Ok(Some(format!("truncated or corrupt: {}", e)));
+        }
+        Err(e) => return Err(e),
+    }
+    if bgzf && !tail.last.ends_with(&BGZF_EOF) {
+        return Ok(Some("missing BGZF EOF block".to_owned()));
+    }
+    Ok(None)
+}
+
+/// Passes reads through, keeping the last bytes read.
+struct Tail<R> {
+    inner: R,
+    last: Vec<u8>,
+}
+
+impl<R: Read> Read for Tail<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.last.extend_from_slice(&buf[..n]);
+        let excess = self.last.len().saturating_sub(BGZF_EOF.len());
+        self.last.drain(..excess);
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FileConstraints, FileRequirementBuilder, LeafOutcome};
+    use flate2::write::GzEncoder;
+    use std::fs;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    fn gzipped(data: &[u8]) -> Vec<u8> {
+        let mut gz = GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        gz.write_all(data).unwrap();
+        gz.finish().unwrap()
+    }
+
+    #[test]
+    fn truncated_and_unterminated_files_are_rejected() {
+        let td = tempdir().unwrap();
+        let whole = gzipped(b"@r1\nACGT\n+\nIIII\n");
+        fs::write(td.path().join("ok.fq.gz"), &whole).unwrap();
+        fs::write(td.path().join("cut.fq.gz"), &whole[..whole.len() - 4]).unwrap();
+        fs::write(td.path().join("eof.bam"), BGZF_EOF).unwrap();
+
+        assert_eq!(verify(&whole[..]).unwrap(), None);
+        assert_eq!(
+            verify(&b"ACGT"[..]).unwrap(),
+            Some("not a gzip file".to_owned())
+        );
+        // A BGZF block followed by a plain gzip member, without the EOF block.
+        assert_eq!(
+            verify(&[BGZF_EOF.to_vec(), whole.clone()].concat()[..]).unwrap(),
+            Some("missing BGZF EOF block".to_owned())
+        );
+
+        let mut b = FileRequirementBuilder::new();
+        for name in ["ok.fq.gz", "cut.fq.gz", "eof.bam"] {
+            b.require_file_with(td.path().join(name), FileConstraints::new().intact_gzip())
+                .unwrap();
+        }
+        let req = b.build();
+        assert_eq!(
+            req.to_string().parse::<crate::FileRequirement>().unwrap(),
+            req
+        );
+        let report = req.check_report();
+        assert_eq!(report.leaves()[0].outcome(), &LeafOutcome::Present);
+        assert!(matches!(
+            report.leaves()[1].outcome(),
+            LeafOutcome::CorruptGzip { problem } if problem.starts_with("truncated")
+        ));
+        assert_eq!(report.leaves()[2].outcome(), &LeafOutcome::Present);
+        let err = req.check().unwrap_err().to_string();
+        assert!(err.contains("corrupt gzip files: "));
+    }
+}
@@ -0,0 +1,322 @@
+use serde_json::{Map, Value, json};
+use thiserror::Error;
+
+use crate::{FileRequirement, FileRequirementBuildError, FileRequirementBuilder, GroupBuilder};
+
+/// Errors produced while converting between requirements and JSON Logic.
+#[derive(Debug, Error)]
+pub enum JsonLogicError {
+    /// An operation uses an operator with no requirement counterpart.
+    #[error(
+        "Unsupported JSON Logic operator `{operator}`; expected `and`, `or`, `!`, `in` or `var`"
+    )]
+    UnsupportedOperator { operator: String },
+    /// A value where an operation was expected is not an object with a
+    /// single operator.
+    #[error("Expected a JSON Logic operation, found `{value}`")]
+    NotAnOperation { value: String },
+    /// An operation has malformed arguments.
+    #[error("Malformed JSON Logic `{operator}`: {message}")]
+    Malformed { operator: String, message: String },
+    /// A term, such as a glob or a constrained file, has no JSON Logic
+    /// counterpart.
+    #[error("`{term}` has no JSON Logic counterpart")]
+    UnsupportedTerm { term: String },
+    /// The converted expression violates a builder invariant.
+    #[error(transparent)]
+    Build(#[from] FileRequirementBuildError),
+}
+
+/// The data variable listing the available files, as `to_json_logic` expects.
+const FILES_VAR: &str = "files";
+
+/// A JSON Logic tree checked for supported operators.
+enum Node {
+    All(Vec<Node>),
+    Any(Vec<Node>),
+    File(String),
+    Absent(String),
+}
+
+impl Node {
+    fn from_value(value: &Value) -> Result<Self, JsonLogicError> {
+        let operation = match value {
+            Value::Object(operation) if operation.len() == 1 => operation,
+            other => {
+                return Err(JsonLogicError::NotAnOperation {
+                    value: other.to_string(),
+                });
+            }
+        };
+        let (operator, args) = operation.iter().next().expect("one operator");
+        match operator.as_str() {
+            "and" | "or" => {
+                let Value::Array(args) = args else {
+                    return Err(malformed(operator, "expected an array of operations"));
+                };
+                let children = args
+                    .iter()
+                    .map(Node::from_value)
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(if operator == "and" {
+                    Node::All(children)
+                } else {
+                    Node::Any(children)
+                })
+            }
+            "var" => var_path(args).map(Node::File),
+            "in" => in_path(args).map(Node::File),
+            "!" => match single_arg(args)
+                .and_then(Value::as_object)
+                .and_then(file_operand)
+            {
+                Some(("var", args)) => var_path(args).map(Node::Absent),
+                Some((_, args)) => in_path(args).map(Node::Absent),
+                None => Err(malformed(
+                    operator,
+                    "only negated `in` and `var` operations are supported",
+                )),
+            },
+            _ => Err(JsonLogicError::UnsupportedOperator {
+                operator: operator.clone(),
+            }),
+        }
+    }
+
+    fn add_to(&self, group: &mut GroupBuilder<'_>) -> Result<(), FileRequirementBuildError> {
+        match self {
+            Node::All(children) => {
+                group.require_all(|g| children.iter().try_for_each(|child| child.add_to(g)))?
+            }
+            Node::Any(children) => {
+                group.require_any(|g| children.iter().try_for_each(|child| child.add_to(g)))?
+            }
+            Node::File(path) => group.require_file(path)?,
+            Node::Absent(path) => group.require_absent(path)?,
+        };
+        Ok(())
+    }
+}
+
+/// The argument of a unary operation, given bare or as a one-element array.
+fn single_arg(args: &Value) -> Option<&Value> {
+    match args {
+        Value::Array(args) if args.len() == 1 => args.first(),
+        Value::Array(_) => None,
+        arg => Some(arg),
+    }
+}
+
+/// The operator and arguments of `operation` if it is an `in` or `var`
+/// operation.
+fn file_operand(operation: &Map<String, Value>) -> Option<(&str, &Value)> {
+    match operation.iter().next() {
+        Some((operator, args))
+            if operation.len() == 1 && matches!(operator.as_str(), "in" | "var") =>
+        {
+            Some((operator, args))
+        }
+        _ => None,
+    }
+}
+
+/// The path of `{"in": [path, {"var": "files"}]}`.
+fn in_path(args: &Value) -> Result<String, JsonLogicError> {
+    let files = json!({"var": FILES_VAR});
+    match args {
+        Value::Array(args) if args.len() == 2 && args[1] == files => match &args[0] {
+            Value::String(path) if !path.is_empty() => Ok(path.clone()),
+            _ => Err(malformed("in", "expected a non-empty path string")),
+        },
+        _ => Err(malformed(
+            "in",
+            format!("expected a path and `{{\"var\": \"{FILES_VAR}\"}}`"),
+        )),
+    }
+}
+
+fn var_path(args: &Value) -> Result<String, JsonLogicError> {
+    match args {
+        Value::Array(args) if args.len() > 1 => Err(malformed(
+            "var",
+            "default values are not supported; a file must exist",
+        )),
+        _ => match single_arg(args) {
+            Some(Value::String(path)) if !path.is_empty() => Ok(path.clone()),
+            _ => Err(malformed("var", "expected a non-empty path string")),
+        },
+    }
+}
+
+fn malformed(operator: &str, message: impl Into<String>) -> JsonLogicError {
+    JsonLogicError::Malformed {
+        operator: operator.to_owned(),
+        message: message.into(),
+    }
+}
+
+impl FileRequirement {
+    /// Convert a JSON Logic expression of file preconditions: `and` / `or`
+    /// operations become `AND` / `OR` groups, `{"in": ["path", {"var":
+    /// "files"}]}` or `{"var": "path"}` a file term, and either negated with
+    /// `!` a path that must not exist.
+    ///
+    /// Other operators are rejected with
+    /// [`JsonLogicError::UnsupportedOperator`]. The result is an `AND` group,
+    /// as [`FileRequirementBuilder::build`] returns, and is validated like
+    /// one: repeated paths and empty groups are errors.
+    ///
+    /// ```
+    /// use file_requirements::FileRequirement;
+    /// use serde_json::json;
+    ///
+    /// let logic = json!({"and": [{"var": "idx.ctab"}, {"or": [{"var": "idx.sshash"}, {"var": "idx.ssi"}]}]});
+    /// let req = FileRequirement::from_json_logic(&logic).unwrap();
+    /// assert_eq!(req.to_string(), "(idx.ctab AND (idx.sshash OR idx.ssi))");
+    /// ```
+    pub fn from_json_logic(logic: &Value) -> Result<FileRequirement, JsonLogicError> {
+        let root = Node::from_value(logic)?;
+        let mut builder = FileRequirementBuilder::new();
+        builder.require_all(|g| match &root {
+            Node::All(children) => children.iter().try_for_each(|child| child.add_to(g)),
+            node => node.add_to(g),
+        })?;
+        Ok(match builder.build() {
+            FileRequirement::All(mut root) if root.len() == 1 => root.remove(0),
+            built => built,
+        })
+    }
+
+    /// Convert the expression to JSON Logic for front-ends that evaluate it
+    /// against their own file catalogs, given as data of the form
+    /// `{"files": ["idx.ctab", ...]}`: file terms become `{"in": ["path",
+    /// {"var": "files"}]}`, paths that must not exist the same negated with
+    /// `!`, and `AND` / `OR` groups `and` / `or` operations. An `XOR` group
+    /// becomes an `or` of one `and` per alternative, negating the others.
+    ///
+    /// Paths are not used as `var` names, since JSON Logic reads the dots of
+    /// `idx.ctab` as nested keys. Names and hints are dropped. Other terms
+    /// fail with [`JsonLogicError::UnsupportedTerm`].
+    pub fn to_json_logic(&self) -> Result<Value, JsonLogicError> {
+        Ok(match self {
+            FileRequirement::File(path) => json!({"in": [path, {"var": FILES_VAR}]}),
+            FileRequirement::Absent(path) => json!({"!": {"in": [path, {"var": FILES_VAR}]}}),
+            FileRequirement::Named { term, .. } | FileRequirement::Hinted { term, .. } => {
+                term.to_json_logic()?
+            }
+            FileRequirement::All(children) => json!({"and": json_logic_all(children)?}),
+            FileRequirement::Any(children) => json!({"or": json_logic_all(children)?}),
+            FileRequirement::ExactlyOne(children) => {
+                let children = json_logic_all(children)?;
+                let alternatives: Vec<Value> = (0..children.len())
+                    .map(|chosen| {
+                        let terms: Vec<Value> = children
+                            .iter()
+                            .enumerate()
+                            .map(|(i, child)| {
+                                if i == chosen {
+                                    child.clone()
+                                } else {
+                                    json!({"!": child})
+                                }
+                            })
+                            .collect();
+                        json!({"and": terms})
+                    })
+                    .collect();
+                json!({"or": alternatives})
+            }
+            term => {
+                return Err(JsonLogicError::UnsupportedTerm {
+                    term: term.to_string(),
+                });
+            }
+        })
+    }
+}
+
+fn json_logic_all(children: &[FileRequirement]) -> Result<Vec<Value>, JsonLogicError> {
+    children
+        .iter()
+        .map(FileRequirement::to_json_logic)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn operations_map_to_groups_and_terms() {
+        let logic = json!({"or": [
+            {"and": [{"var": ["idx.ssi"]}, {"var": "idx.ssi.mphf"}]},
+            {"var": "idx.sshash"},
+        ]});
+        let req = FileRequirement::from_json_logic(&logic).unwrap();
+        assert_eq!(
+            req.to_string(),
+            "(((idx.ssi AND idx.ssi.mphf) OR idx.sshash))"
+        );
+
+        let logic = json!({"and": [{"var": "in.bam"}, {"!": {"var": "out.bam"}}]});
+        let req = FileRequirement::from_json_logic(&logic).unwrap();
+        assert_eq!(req.to_string(), "(in.bam AND absent(out.bam))");
+    }
+
+    #[test]
+    fn requirements_export_to_json_logic() {
+        let req: FileRequirement = "(idx.ctab AND absent(out) AND (a.ssi XOR b.ssi))"
+            .parse()
+            .unwrap();
+        let file = |path: &str| json!({"in": [path, {"var": "files"}]});
+        assert_eq!(
+            req.to_json_logic().unwrap(),
+            json!({"and": [
+                file("idx.ctab"),
+                {"!": file("out")},
+                {"or": [
+                    {"and": [file("a.ssi"), {"!": file("b.ssi")}]},
+                    {"and": [{"!": file("a.ssi")}, file("b.ssi")]},
+                ]},
+            ]})
+        );
+
+        let req: FileRequirement = "(a AND (b OR absent(c)))".parse().unwrap();
+        let logic = req.to_json_logic().unwrap();
+        assert_eq!(FileRequirement::from_json_logic(&logic).unwrap(), req);
+
+        let req: FileRequirement = r#"(a AND glob("*.fq", 1))"#.parse().unwrap();
+        assert!(matches!(
+            req.to_json_logic(),
+            Err(JsonLogicError::UnsupportedTerm { term }) if term == r#"glob("*.fq", 1)"#
+        ));
+    }
+
+    #[test]
+    fn unsupported_and_malformed_operations_are_rejected() {
+        let err = FileRequirement::from_json_logic(&json!({"==": [1, 1]})).unwrap_err();
+        assert!(matches!(
+            &err,
+            JsonLogicError::UnsupportedOperator { operator } if operator == "=="
+        ));
+        assert!(
+            err.to_string()
+                .contains("expected `and`, `or`, `!`, `in` or `var`")
+        );
+        assert!(matches!(
+            FileRequirement::from_json_logic(&json!({"var": ["a.txt", "b.txt"]})),
+            Err(JsonLogicError::Malformed { .. })
+        ));
+        assert!(matches!(
+            FileRequirement::from_json_logic(&json!({"in": ["a", {"var": "dirs"}]})),
+            Err(JsonLogicError::Malformed { .. })
+        ));
+        assert!(matches!(
+            FileRequirement::from_json_logic(&json!({"and": [{"var": "a"}, {"var": "a"}]})),
+            Err(JsonLogicError::Build(
+                FileRequirementBuildError::DuplicateFile { .. }
+            ))
+        ));
+    }
+}
@@ -0,0 +1,156 @@
+use std::path::{Path, PathBuf};
+
+use crate::{FileConstraints, FileRequirement, FileRequirementBuildError, FileRequirementBuilder};
+
+/// An expected directory tree, compiled into a [`FileRequirement`] for a
+/// concrete root directory.
+///
+/// Entries are paths relative to the layout's directory; subdirectories are
+/// layouts of their own:
+///
+/// ```
+/// use file_requirements::DirLayout;
+///
+/// let run = DirLayout::new()
+///     .file("quant.json")
+///     .optional("cmd_info.json")
+///     .dir("alevin", DirLayout::new().file("quants_mat.mtx"))
+///     .glob("logs/*.log", 1);
+/// let req = run.compile("runs/pbmc_1k").unwrap();
+/// assert!(req.to_string().contains("runs/pbmc_1k/alevin/quants_mat.mtx"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DirLayout {
+    entries: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Entry {
+    File(PathBuf, FileConstraints),
+    Optional(PathBuf),
+    Glob(String, usize),
+    Dir(PathBuf, DirLayout),
+}
+
+impl DirLayout {
+    /// A layout with no entries, satisfied by any directory.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require the file at `path`.
+    pub fn file<P: AsRef<Path>>(self, path: P) -> Self {
+        self.file_with(path, FileConstraints::new())
+    }
+
+    /// Require the file at `path`, satisfying `constraints`.
+    pub fn file_with<P: AsRef<Path>>(mut self, path: P, constraints: FileConstraints) -> Self {
+        self.entries
+            .push(Entry::File(path.as_ref().to_path_buf(), constraints));
+        self
+    }
+
+    /// Recommend the file at `path`: its absence is a warning, not a failure.
+    pub fn optional<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.entries
+            .push(Entry::Optional(path.as_ref().to_path_buf()));
+        self
+    }
+
+    /// Require at least `min_matches` paths matching `pattern`.
+    pub fn glob<S: Into<String>>(mut self, pattern: S, min_matches: usize) -> Self {
+        self.entries.push(Entry::Glob(pattern.into(), min_matches));
+        self
+    }
+
+    /// Require the subdirectory `name`, laid out as `layout`.
+    pub fn dir<P: AsRef<Path>>(mut self, name: P, layout: DirLayout) -> Self {
+        self.entries
+            .push(Entry::Dir(name.as_ref().to_path_buf(), layout));
+        self
+    }
+
+    /// The requirement that `root` is a directory laid out as described.
+    ///
+    /// Fails like [`FileRequirementBuilder`] does: on invalid glob patterns and
+    /// on entries that name the same path twice.
+    pub fn compile<P: AsRef<Path>>(
+        &self,
+        root: P,
+    ) -> Result<FileRequirement, FileRequirementBuildError> {
+        let root = root.as_ref();
+        let mut builder = FileRequirementBuilder::new();
+        builder.require_dir(root)?;
+        self.add_to(&mut builder, root)?;
+        Ok(builder.build())
+    }
+
+    fn add_to(
+        &self,
+        builder: &mut FileRequirementBuilder,
+        dir: &Path,
+    ) -> Result<(), FileRequirementBuildError> {
+        for entry in &self.entries {
+            match entry {
+                Entry::File(path, constraints) if *constraints == FileConstraints::new() => {
+                    builder.require_file(dir.join(path))?;
+                }
+                Entry::File(path, constraints) => {
+                    builder.require_file_with(dir.join(path), constraints.clone())?;
+                }
+                Entry::Optional(path) => {
+                    builder.recommend_file(dir.join(path))?;
+                }
+                Entry::Glob(pattern, min_matches) => {
+                    let escaped = glob::Pattern::escape(&dir.to_string_lossy());
+                    let pattern = Path::new(&escaped).join(pattern);
+                    builder.require_glob(pattern.to_string_lossy(), *min_matches)?;
+                }
+                Entry::Dir(name, layout) => {
+                    let subdir = dir.join(name);
+                    builder.require_dir(&subdir)?;
+                    layout.add_to(builder, &subdir)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn layouts_compile_to_requirements_on_their_root() {
+        let td = tempdir().unwrap();
+        let layout = DirLayout::new()
+            .file_with("info.json", FileConstraints::new().non_empty())
+            .optional("notes.txt")
+            .dir("shards", DirLayout::new().glob("*.bin", 2));
+        let req = layout.compile(td.path()).unwrap();
+        assert!(req.check().is_err());
+
+        fs::write(td.path().join("info.json"), "{}").unwrap();
+        fs::create_dir(td.path().join("shards")).unwrap();
+        for name in ["0.bin", "1.bin"] {
+            fs::write(td.path().join("shards").join(name), "").unwrap();
+        }
+        let report = req.check_report();
+        assert!(report.is_satisfied());
+        assert_eq!(report.findings().len(), 1);
+    }
+
+    #[test]
+    fn repeated_entries_are_rejected() {
+        let layout = DirLayout::new()
+            .dir("alevin", DirLayout::new().file("quants_mat.mtx"))
+            .file("alevin/quants_mat.mtx");
+        assert!(matches!(
+            layout.compile("run"),
+            Err(FileRequirementBuildError::DuplicateFile { .. })
+        ));
+    }
+}
@@ -1,20 +1,201 @@
 use std::collections::{BTreeSet, HashSet};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use thiserror::Error;
 
+#[cfg(feature = "archive")]
+mod archive;
+#[cfg(feature = "tokio")]
+mod async_check;
+mod cache;
+mod cancel;
+#[cfg(feature = "checksum")]
+mod checksum;
+#[cfg(feature = "clap")]
+mod cli;
+mod constraints;
+mod convert;
+mod cwl;
+mod deadline;
+#[cfg(feature = "miette")]
+mod diagnostic;
+mod eval;
+mod fastq;
+mod fluent;
+#[cfg(feature = "gzip")]
+mod gzip;
+#[cfg(feature = "json-logic")]
+mod json_logic;
+mod layout;
+mod macros;
+mod manifest;
+mod merge;
+mod metrics;
+mod ops;
+mod options;
+#[cfg(feature = "unix")]
+mod ownership;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod parse;
+mod poll;
+mod preflight;
+#[cfg(feature = "presets")]
+mod presets;
+#[cfg(feature = "toml")]
+mod profiles;
+mod provider;
+mod recheck;
+#[cfg(feature = "object-store")]
+mod remote;
+mod render;
+mod report;
+mod retry;
+mod scheme;
+mod shards;
+mod shell;
+mod stage;
+mod suggest;
+mod template;
+#[cfg(feature = "watch")]
+mod watch;
+
+pub use cache::{CheckCache, CheckResult};
+pub use cancel::CancelToken;
+#[cfg(feature = "checksum")]
+pub use checksum::{ChecksumManifestError, Digest};
+#[cfg(feature = "clap")]
+pub use cli::{ArgMatchesError, RequirementParser};
+pub use constraints::{Age, FileConstraints, Magic, Size};
+pub use convert::ToFileRequirement;
+pub use fastq::{PairedFastqError, ReadPairing};
+#[cfg(feature = "derive")]
+pub use file_requirements_derive::ToFileRequirement;
+#[cfg(feature = "json-logic")]
+pub use json_logic::JsonLogicError;
+pub use layout::DirLayout;
+pub use manifest::Manifest;
+pub use merge::MergeError;
+pub use metrics::CheckMetrics;
+pub use options::{CheckOptions, SymlinkPolicy};
+#[cfg(feature = "unix")]
+pub use ownership::Owner;
+pub use parse::FileRequirementParseError;
+pub use preflight::Preflight;
+#[cfg(feature = "presets")]
+pub use presets::{PiscemIndex, SalmonIndex};
+#[cfg(feature = "toml")]
+pub use profiles::{ProfileError, Profiles};
+pub use provider::{Access, FileKind, FileMetadata, FileProvider, StdFs};
+pub use report::{
+    CheckReport, Finding, GroupKind, GroupReport, LeafOutcome, LeafReport, NodeReport, Severity,
+};
+pub use retry::RetryPolicy;
+pub use scheme::SchemeRegistry;
+pub use stage::{StageError, StageMode, Staged};
+pub use template::FileRequirementResolveError;
+#[cfg(feature = "watch")]
+pub use watch::WaitError;
+
+use metrics::{Metered, timed};
+use scheme::SeenTerms;
+
 /// A boolean file existence requirement expression.
 ///
 /// - [`FileRequirement::All`] is a conjunction (`AND`)
 /// - [`FileRequirement::Any`] is a disjunction (`OR`)
-#[derive(Debug, Clone)]
+/// - [`FileRequirement::ExactlyOne`] is a mutually exclusive choice (`XOR`)
+///
+/// With the `serde` feature enabled, expressions serialize as externally tagged
+/// maps keyed by variant name in `snake_case` (`file`, `glob`, `all`, ...), e.g.
+/// `{"all": [{"file": "a.txt"}, {"any": [{"file": "b.txt"}, {"file": "c.txt"}]}]}`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum FileRequirement {
     /// A single file term that must exist.
     File(PathBuf),
+    /// A file term that must exist and satisfy additional constraints.
+    FileWith {
+        path: PathBuf,
+        constraints: FileConstraints,
+    },
+    /// A glob pattern that must match at least `min_matches` existing paths.
+    Glob { pattern: String, min_matches: usize },
+    /// A glob pattern whose matching files must total at least `min_bytes`.
+    GlobSize { pattern: String, min_bytes: u64 },
+    /// At least `min` of the shards `start..end` of `template`, a path such as
+    /// `part_{}.rad` or `part_{:03}.rad` with a placeholder for the shard index.
+    Shards {
+        template: String,
+        start: usize,
+        end: usize,
+        min: usize,
+    },
+    /// A path that must be a directory (and, if `non_empty`, contain an entry).
+    Dir { path: PathBuf, non_empty: bool },
+    /// A directory that must contain at least `min_entries` entries, counting
+    /// only those whose file name matches the glob `pattern` if one is given.
+    DirEntries {
+        path: PathBuf,
+        pattern: Option<String>,
+        min_entries: usize,
+    },
+    /// A path that must not exist, such as an output that must not be overwritten.
+    Absent(PathBuf),
+    /// A file that should exist; when absent the check still passes, but the
+    /// report lists a warning.
+    Recommended(PathBuf),
+    /// A directory that must be writable, or creatable under a writable ancestor.
+    WritableDir(PathBuf),
+    /// `path` and `other` must be on the same filesystem, as hard links
+    /// between them require. Paths that do not exist yet are placed by their
+    /// nearest existing ancestor.
+    SameFilesystem { path: PathBuf, other: PathBuf },
+    /// The filesystem containing `path` must have at least `bytes` available.
+    FreeSpace { path: PathBuf, bytes: u64 },
+    /// An environment variable that must be set, and optionally non-empty or
+    /// fully matching the regular expression `pattern`.
+    EnvVar {
+        #[cfg_attr(
+            feature = "serde",
+            serde(deserialize_with = "eval::deserialize_env_var_name")
+        )]
+        name: String,
+        non_empty: bool,
+        pattern: Option<String>,
+    },
+    /// A program that must be found as an executable file on `PATH`.
+    ///
+    /// A name containing a path separator is checked directly instead.
+    Executable(PathBuf),
+    /// An `http://` or `https://` URL that must answer a `HEAD` request
+    /// successfully.
+    Url(String),
+    /// A path, such as `info.json`, that must be a member of the `.tar`,
+    /// `.tar.gz` / `.tgz` or `.zip` archive at `archive`.
+    ArchiveMember { archive: PathBuf, member: String },
+    /// A term or group with a human-readable name, used in error messages.
+    Named {
+        name: String,
+        term: Box<FileRequirement>,
+    },
+    /// A term or group with a remediation hint, shown when it is not satisfied.
+    Hinted {
+        hint: String,
+        term: Box<FileRequirement>,
+    },
     /// All children must be satisfied.
     All(Vec<FileRequirement>),
     /// At least one child must be satisfied.
     Any(Vec<FileRequirement>),
+    /// Exactly one child must be satisfied.
+    ExactlyOne(Vec<FileRequirement>),
 }
 
 /// Errors produced while building a requirement expression.
@@ -28,17 +209,121 @@ pub enum FileRequirementBuildError {
     /// A group was created but no children were added.
     #[error("Cannot create an empty `{group}` group.")]
     EmptyGroup { group: &'static str },
+    /// A glob term has an invalid pattern.
+    #[error("Invalid glob pattern `{pattern}`: {message}")]
+    InvalidGlob { pattern: String, message: String },
+    /// A shard template or range is invalid.
+    #[error("Invalid shards `{template}`: {message}")]
+    InvalidShards { template: String, message: String },
+    /// A path uses a URI scheme, such as `s3://`, with no registered backend.
+    #[error("Path `{path}` uses the URI scheme `{scheme}`, which has no registered backend.")]
+    UnknownScheme { scheme: String, path: String },
+    /// An environment variable name is not letters, digits and underscores
+    /// starting with a letter or underscore.
+    #[error(
+        "Invalid environment variable name `{name}`: expected letters, digits and underscores, not starting with a digit."
+    )]
+    InvalidEnvVar { name: String },
+    /// An environment variable pattern is not a valid regular expression.
+    #[cfg(feature = "regex")]
+    #[error("Invalid regular expression `{pattern}`: {message}")]
+    InvalidRegex { pattern: String, message: String },
+    /// An expected checksum is malformed.
+    #[cfg(feature = "checksum")]
+    #[error("Invalid digest `{digest}`: {message}")]
+    InvalidDigest { digest: String, message: String },
 }
 
 /// Errors produced when checking a built requirement expression.
-#[derive(Debug, Error)]
-#[error("{message}")]
+///
+/// When a term failed with an IO error, [`source`](std::error::Error::source)
+/// returns the first such error, so error-reporting stacks can show it as the
+/// cause; all of them are available from [`io_errors`](Self::io_errors).
+#[derive(Debug)]
 pub struct FileRequirementCheckError {
     message: String,
+    missing_files: Vec<PathBuf>,
+    io_errors: Vec<(PathBuf, std::io::Error)>,
+    unsatisfied_groups: Vec<String>,
+    #[cfg(feature = "miette")]
+    spans: Box<diagnostic::SourceSpans>,
+}
+
+impl std::fmt::Display for FileRequirementCheckError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for FileRequirementCheckError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.io_errors
+            .first()
+            .map(|(_, error)| error as &(dyn std::error::Error + 'static))
+    }
 }
 
 impl FileRequirementCheckError {
-    fn from_context(ctx: CheckContext) -> Self {
+    fn from_report(report: &CheckReport) -> Self {
+        Self::from_report_failing_on(report, Severity::Error)
+    }
+
+    /// The error for `report`, listing its failures and any lower-severity
+    /// findings at or above `fail_on`.
+    fn from_report_failing_on(report: &CheckReport, fail_on: Severity) -> Self {
+        let mut ctx = CheckContext::default();
+        ctx.collect(report.root());
+        let hints = std::mem::take(&mut ctx.hints);
+        let missing_files = std::mem::take(&mut ctx.missing_paths);
+        let io_errors = std::mem::take(&mut ctx.failed_io);
+        let unsatisfied_groups = std::mem::take(&mut ctx.unsatisfied_groups);
+        let mut sections = Self::sections(ctx);
+        sections.extend(
+            report
+                .advisories()
+                .into_iter()
+                .filter(|finding| finding.severity() >= fail_on)
+                .map(|finding| finding.message().to_owned()),
+        );
+        let mut message = format!(
+            "Required input files were missing or incomplete ({})",
+            sections.join("; ")
+        );
+        for hint in hints {
+            message.push_str("\nhint: ");
+            message.push_str(&hint);
+        }
+        Self {
+            message,
+            missing_files,
+            io_errors,
+            unsatisfied_groups,
+            #[cfg(feature = "miette")]
+            spans: Box::new(diagnostic::SourceSpans::from_report(report)),
+        }
+    }
+
+    /// Paths of failed terms that do not exist, in declaration order.
+    ///
+    /// Only failures that contributed to the error are listed; for instance, a
+    /// missing alternative of a satisfied `OR` is not.
+    pub fn missing_files(&self) -> &[PathBuf] {
+        &self.missing_files
+    }
+
+    /// Paths whose check failed with an IO error, with that error.
+    pub fn io_errors(&self) -> &[(PathBuf, std::io::Error)] {
+        &self.io_errors
+    }
+
+    /// Labels of the `OR` / `XOR` groups none of whose alternatives was
+    /// satisfied: the group's name, or its rendered expression.
+    pub fn unsatisfied_groups(&self) -> &[String] {
+        &self.unsatisfied_groups
+    }
+
+    /// One message section per kind of failure collected in `ctx`.
+    fn sections(ctx: CheckContext) -> Vec<String> {
         let mut sections: Vec<String> = Vec::new();
         if !ctx.missing_files.is_empty() {
             sections.push(format!(
@@ -46,12 +331,247 @@ impl FileRequirementCheckError {
                 ctx.missing_files.into_iter().collect::<Vec<_>>().join(", ")
             ));
         }
+        if !ctx.missing_urls.is_empty() {
+            sections.push(format!(
+                "URLs not found: {}",
+                ctx.missing_urls.into_iter().collect::<Vec<_>>().join(", ")
+            ));
+        }
+        if !ctx.missing_members.is_empty() {
+            sections.push(format!(
+                "missing archive members: {}",
+                ctx.missing_members
+                    .into_iter()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        if !ctx.existing_outputs.is_empty() {
+            sections.push(format!(
+                "outputs already exist: {}",
+                ctx.existing_outputs
+                    .into_iter()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        if !ctx.missing_executables.is_empty() {
+            sections.push(format!(
+                "executables not found on PATH: {}",
+                ctx.missing_executables
+                    .into_iter()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        if !ctx.broken_symlinks.is_empty() {
+            sections.push(format!(
+                "broken symlinks: {}",
+                ctx.broken_symlinks
+                    .into_iter()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        if !ctx.not_directories.is_empty() {
+            sections.push(format!(
+                "exists but is not a directory: {}",
+                ctx.not_directories
+                    .into_iter()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        if !ctx.not_regular_files.is_empty() {
+            sections.push(format!(
+                "exists but is not a regular file: {}",
+                ctx.not_regular_files
+                    .into_iter()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        for access in Access::ALL {
+            let denied: Vec<String> = ctx
+                .denied_access
+                .range((access, String::new())..)
+                .take_while(|(denied, _)| *denied == access)
+                .map(|(_, path)| path.clone())
+                .collect();
+            if !denied.is_empty() {
+                sections.push(format!(
+                    "exists but is not {}: {}",
+                    access.adjective(),
+                    denied.join(", ")
+                ));
+            }
+        }
+        if !ctx.uncreatable_dirs.is_empty() {
+            sections.push(format!(
+                "cannot be created: {}",
+                ctx.uncreatable_dirs
+                    .into_iter()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        if !ctx.low_space.is_empty() {
+            sections.push(format!(
+                "insufficient free space: {}",
+                ctx.low_space.into_iter().collect::<Vec<_>>().join(", ")
+            ));
+        }
+        if !ctx.split_filesystems.is_empty() {
+            sections.push(format!(
+                "not on the same filesystem: {}",
+                ctx.split_filesystems
+                    .into_iter()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        if !ctx.wrong_sizes.is_empty() {
+            sections.push(format!(
+                "size constraint violated: {}",
+                ctx.wrong_sizes.into_iter().collect::<Vec<_>>().join(", ")
+            ));
+        }
+        if !ctx.wrong_ages.is_empty() {
+            sections.push(format!(
+                "age constraint violated: {}",
+                ctx.wrong_ages.into_iter().collect::<Vec<_>>().join(", ")
+            ));
+        }
+        if !ctx.stale_files.is_empty() {
+            sections.push(format!(
+                "stale files: {}",
+                ctx.stale_files.into_iter().collect::<Vec<_>>().join(", ")
+            ));
+        }
+        if !ctx.wrong_formats.is_empty() {
+            sections.push(format!(
+                "unexpected file format: {}",
+                ctx.wrong_formats.into_iter().collect::<Vec<_>>().join(", ")
+            ));
+        }
+        if !ctx.wrong_owners.is_empty() {
+            sections.push(format!(
+                "unexpected ownership: {}",
+                ctx.wrong_owners.into_iter().collect::<Vec<_>>().join(", ")
+            ));
+        }
+        if !ctx.digest_mismatches.is_empty() {
+            sections.push(format!(
+                "checksum mismatch: {}",
+                ctx.digest_mismatches
+                    .into_iter()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        if !ctx.corrupt_gzips.is_empty() {
+            sections.push(format!(
+                "corrupt gzip files: {}",
+                ctx.corrupt_gzips.into_iter().collect::<Vec<_>>().join(", ")
+            ));
+        }
+        if !ctx.changing_files.is_empty() {
+            sections.push(format!(
+                "files still changing: {}",
+                ctx.changing_files
+                    .into_iter()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        if !ctx.empty_directories.is_empty() {
+            sections.push(format!(
+                "empty directories: {}",
+                ctx.empty_directories
+                    .into_iter()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        if !ctx.missing_shards.is_empty() {
+            sections.push(format!(
+                "too few shards: {}",
+                ctx.missing_shards
+                    .into_iter()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        if !ctx.sparse_directories.is_empty() {
+            sections.push(format!(
+                "too few directory entries: {}",
+                ctx.sparse_directories
+                    .into_iter()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        if !ctx.unmatched_globs.is_empty() {
+            sections.push(format!(
+                "unmatched globs: {}",
+                ctx.unmatched_globs
+                    .into_iter()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        if !ctx.small_globs.is_empty() {
+            sections.push(format!(
+                "globs below their total size: {}",
+                ctx.small_globs.into_iter().collect::<Vec<_>>().join(", ")
+            ));
+        }
+        if !ctx.unset_variables.is_empty() {
+            sections.push(format!(
+                "unset environment variables: {}",
+                ctx.unset_variables
+                    .into_iter()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        if !ctx.empty_variables.is_empty() {
+            sections.push(format!(
+                "empty environment variables: {}",
+                ctx.empty_variables
+                    .into_iter()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        if !ctx.mismatched_variables.is_empty() {
+            sections.push(format!(
+                "environment variables not matching: {}",
+                ctx.mismatched_variables
+                    .into_iter()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
         if !ctx.io_errors.is_empty() {
             sections.push(format!(
                 "path check errors: {}",
                 ctx.io_errors.into_iter().collect::<Vec<_>>().join(", ")
             ));
         }
+        if !ctx.cancelled.is_empty() {
+            sections.push(format!(
+                "not checked before cancellation: {}",
+                ctx.cancelled.into_iter().collect::<Vec<_>>().join(", ")
+            ));
+        }
+        if !ctx.unresolved.is_empty() {
+            sections.push(format!(
+                "not resolved before the deadline: {}",
+                ctx.unresolved.into_iter().collect::<Vec<_>>().join(", ")
+            ));
+        }
+        sections.extend(ctx.unsatisfied_named_groups);
         if !ctx.unsatisfied_disjunctions.is_empty() {
             sections.push(format!(
                 "unsatisfied disjunction(s): {}",
@@ -61,12 +581,16 @@ impl FileRequirementCheckError {
                     .join(", ")
             ));
         }
-        Self {
-            message: format!(
-                "Required input files were missing or incomplete ({})",
-                sections.join("; ")
-            ),
+        if !ctx.conflicting_groups.is_empty() {
+            sections.push(format!(
+                "conflicting exclusive group(s): {}",
+                ctx.conflicting_groups
+                    .into_iter()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
         }
+        sections
     }
 }
 
@@ -75,7 +599,8 @@ impl FileRequirementCheckError {
 /// The root group is an implicit `AND` group.
 pub struct FileRequirementBuilder {
     root_terms: Vec<FileRequirement>,
-    seen_terms: HashSet<PathBuf>,
+    seen_terms: SeenTerms,
+    base_dir: Option<PathBuf>,
 }
 
 impl FileRequirementBuilder {
@@ -83,7 +608,8 @@ impl FileRequirementBuilder {
     pub fn new() -> Self {
         Self {
             root_terms: Vec::new(),
-            seen_terms: HashSet::new(),
+            seen_terms: SeenTerms::default(),
+            base_dir: None,
         }
     }
 
@@ -96,258 +622,3054 @@ impl FileRequirementBuilder {
         Ok(self)
     }
 
-    /// Add a nested conjunction (`AND`) to the root conjunction.
-    pub fn require_all<F>(&mut self, f: F) -> Result<&mut Self, FileRequirementBuildError>
-    where
-        F: FnOnce(&mut GroupBuilder<'_>) -> Result<(), FileRequirementBuildError>,
-    {
-        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms).require_all(f)?;
+    /// Add a required file with extra constraints to the root conjunction.
+    ///
+    /// `constraints` may be a [`FileConstraints`] or a single constraint such as
+    /// [`Size::AtLeast(1)`](Size::AtLeast).
+    pub fn require_file_with<P: AsRef<Path>, C: Into<FileConstraints>>(
+        &mut self,
+        path: P,
+        constraints: C,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms)
+            .require_file_with(path, constraints)?;
         Ok(self)
     }
 
-    /// Add a nested disjunction (`OR`) to the root conjunction.
-    pub fn require_any<F>(&mut self, f: F) -> Result<&mut Self, FileRequirementBuildError>
-    where
-        F: FnOnce(&mut GroupBuilder<'_>) -> Result<(), FileRequirementBuildError>,
-    {
-        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms).require_any(f)?;
+    /// Add a required file modified within `max_age` to the root conjunction.
+    pub fn require_file_newer_than<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        max_age: Duration,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms)
+            .require_file_newer_than(path, max_age)?;
         Ok(self)
     }
 
-    /// Build the final requirement expression.
-    pub fn build(self) -> FileRequirement {
-        FileRequirement::All(self.root_terms)
+    /// Add a required file whose contents must start with `magic`.
+    pub fn require_file_with_magic<P: AsRef<Path>, M: Into<Magic>>(
+        &mut self,
+        path: P,
+        magic: M,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms)
+            .require_file_with_magic(path, magic)?;
+        Ok(self)
     }
-}
 
-impl Default for FileRequirementBuilder {
-    fn default() -> Self {
-        Self::new()
+    /// Add a required file whose contents must hash to the SHA-256 `hex` digest.
+    #[cfg(feature = "checksum")]
+    pub fn require_file_with_sha256<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        hex: &str,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms)
+            .require_file_with_sha256(path, hex)?;
+        Ok(self)
     }
-}
-
-/// Nested group builder used to create `AND` / `OR` sub-expressions.
-pub struct GroupBuilder<'a> {
-    target: &'a mut Vec<FileRequirement>,
-    seen_terms: &'a mut HashSet<PathBuf>,
-}
 
-impl<'a> GroupBuilder<'a> {
-    fn new(target: &'a mut Vec<FileRequirement>, seen_terms: &'a mut HashSet<PathBuf>) -> Self {
-        Self { target, seen_terms }
+    /// Add a required regular file (not a directory) to the root conjunction.
+    pub fn require_regular_file<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms).require_regular_file(path)?;
+        Ok(self)
     }
 
-    /// Add a required file term to this group.
-    pub fn require_file<P: AsRef<Path>>(
+    /// Add a required directory to the root conjunction.
+    pub fn require_dir<P: AsRef<Path>>(
         &mut self,
         path: P,
     ) -> Result<&mut Self, FileRequirementBuildError> {
-        let owned_path = path.as_ref().to_path_buf();
-        if !self.seen_terms.insert(owned_path.clone()) {
-            return Err(FileRequirementBuildError::DuplicateFile {
-                path: owned_path.display().to_string(),
-            });
-        }
-        self.target.push(FileRequirement::File(owned_path));
+        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms).require_dir(path)?;
         Ok(self)
     }
 
-    /// Add a nested conjunction (`AND`) group.
-    pub fn require_all<F>(&mut self, f: F) -> Result<&mut Self, FileRequirementBuildError>
-    where
-        F: FnOnce(&mut GroupBuilder<'_>) -> Result<(), FileRequirementBuildError>,
-    {
-        let mut child_terms = Vec::new();
-        f(&mut GroupBuilder::new(&mut child_terms, self.seen_terms))?;
-        if child_terms.is_empty() {
-            return Err(FileRequirementBuildError::EmptyGroup { group: "AND" });
-        }
-        self.target.push(FileRequirement::All(child_terms));
+    /// Add a required non-empty directory to the root conjunction.
+    pub fn require_non_empty_dir<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms)
+            .require_non_empty_dir(path)?;
         Ok(self)
     }
 
-    /// Add a nested disjunction (`OR`) group.
-    pub fn require_any<F>(&mut self, f: F) -> Result<&mut Self, FileRequirementBuildError>
-    where
-        F: FnOnce(&mut GroupBuilder<'_>) -> Result<(), FileRequirementBuildError>,
-    {
-        let mut child_terms = Vec::new();
-        f(&mut GroupBuilder::new(&mut child_terms, self.seen_terms))?;
-        if child_terms.is_empty() {
-            return Err(FileRequirementBuildError::EmptyGroup { group: "OR" });
-        }
-        self.target.push(FileRequirement::Any(child_terms));
+    /// Add a directory that must contain at least `min_entries` entries to the
+    /// root conjunction.
+    pub fn require_dir_entries<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        min_entries: usize,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms)
+            .require_dir_entries(path, min_entries)?;
         Ok(self)
     }
-}
 
-impl FileRequirement {
-    /// Validate this requirement expression against the local filesystem.
-    pub fn check(&self) -> Result<(), FileRequirementCheckError> {
-        let mut ctx = CheckContext::default();
-        if self.evaluate(&mut ctx) {
-            Ok(())
-        } else {
-            Err(FileRequirementCheckError::from_context(ctx))
-        }
+    /// Add a directory that must contain at least `min_entries` entries whose
+    /// names match `pattern` to the root conjunction.
+    pub fn require_dir_entries_matching<P: AsRef<Path>, S: Into<String>>(
+        &mut self,
+        path: P,
+        pattern: S,
+        min_entries: usize,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms)
+            .require_dir_entries_matching(path, pattern, min_entries)?;
+        Ok(self)
     }
 
-    fn evaluate(&self, ctx: &mut CheckContext) -> bool {
+    /// Add an output directory that must be writable (or creatable) to the root
+    /// conjunction.
+    pub fn require_writable_dir<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms).require_writable_dir(path)?;
+        Ok(self)
+    }
+
+    /// Require at least `bytes` of free space on the filesystem containing `path`
+    /// in the root conjunction.
+    pub fn require_free_space<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        bytes: u64,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms)
+            .require_free_space(path, bytes)?;
+        Ok(self)
+    }
+
+    /// Require `path` and `other` to be on the same filesystem, in the root
+    /// conjunction.
+    pub fn require_same_filesystem<P: AsRef<Path>, Q: AsRef<Path>>(
+        &mut self,
+        path: P,
+        other: Q,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms)
+            .require_same_filesystem(path, other)?;
+        Ok(self)
+    }
+
+    /// Add an environment variable that must be set to the root conjunction.
+    pub fn require_env_var<S: Into<String>>(
+        &mut self,
+        name: S,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms).require_env_var(name)?;
+        Ok(self)
+    }
+
+    /// Add an environment variable that must be set and non-empty to the root
+    /// conjunction.
+    pub fn require_non_empty_env_var<S: Into<String>>(
+        &mut self,
+        name: S,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms)
+            .require_non_empty_env_var(name)?;
+        Ok(self)
+    }
+
+    /// Add an environment variable whose value must match `pattern` to the root
+    /// conjunction.
+    #[cfg(feature = "regex")]
+    pub fn require_env_var_matching<S: Into<String>>(
+        &mut self,
+        name: S,
+        pattern: &str,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms)
+            .require_env_var_matching(name, pattern)?;
+        Ok(self)
+    }
+
+    /// Add a program that must be executable from `PATH` to the root conjunction.
+    pub fn require_executable<P: AsRef<Path>>(
+        &mut self,
+        name: P,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms).require_executable(name)?;
+        Ok(self)
+    }
+
+    /// Add every path of `paths` to the root conjunction.
+    ///
+    /// See [`GroupBuilder::require_files`].
+    pub fn require_files<I>(&mut self, paths: I) -> Result<&mut Self, FileRequirementBuildError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<Path>,
+    {
+        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms).require_files(paths)?;
+        Ok(self)
+    }
+
+    /// Add the files named by appending each extension to `prefix` to the root
+    /// conjunction.
+    ///
+    /// See [`GroupBuilder::require_with_extensions`].
+    pub fn require_with_extensions<P, I, S>(
+        &mut self,
+        prefix: P,
+        extensions: I,
+    ) -> Result<&mut Self, FileRequirementBuildError>
+    where
+        P: AsRef<Path>,
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms)
+            .require_with_extensions(prefix, extensions)?;
+        Ok(self)
+    }
+
+    /// Add a disjunction (`OR`) of the files named by appending each extension
+    /// to `prefix` to the root conjunction.
+    pub fn require_any_with_extensions<P, I, S>(
+        &mut self,
+        prefix: P,
+        extensions: I,
+    ) -> Result<&mut Self, FileRequirementBuildError>
+    where
+        P: AsRef<Path>,
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms)
+            .require_any_with_extensions(prefix, extensions)?;
+        Ok(self)
+    }
+
+    /// Add one required file per shard of `range` to the root conjunction.
+    ///
+    /// See [`GroupBuilder::require_shards`].
+    pub fn require_shards<S: AsRef<str>>(
+        &mut self,
+        template: S,
+        range: std::ops::Range<usize>,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms)
+            .require_shards(template, range)?;
+        Ok(self)
+    }
+
+    /// Add a term requiring at least `min` of the shards of `range` to the root
+    /// conjunction.
+    pub fn require_shards_at_least<S: Into<String>>(
+        &mut self,
+        template: S,
+        range: std::ops::Range<usize>,
+        min: usize,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms)
+            .require_shards_at_least(template, range, min)?;
+        Ok(self)
+    }
+
+    /// Add a URL that must answer a `HEAD` request to the root conjunction.
+    pub fn require_url<S: Into<String>>(
+        &mut self,
+        url: S,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms).require_url(url)?;
+        Ok(self)
+    }
+
+    /// Add a path that must be a member of `archive` to the root conjunction.
+    pub fn require_archive_member<P: AsRef<Path>, S: Into<String>>(
+        &mut self,
+        archive: P,
+        member: S,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms)
+            .require_archive_member(archive, member)?;
+        Ok(self)
+    }
+
+    /// Add a required file with a descriptive name, such as
+    /// `"transcriptome index"`, to the root conjunction.
+    pub fn require_file_named<S: Into<String>, P: AsRef<Path>>(
+        &mut self,
+        name: S,
+        path: P,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms)
+            .require_file_named(name, path)?;
+        Ok(self)
+    }
+
+    /// Add a recommended, warn-only file to the root conjunction.
+    pub fn recommend_file<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms).recommend_file(path)?;
+        Ok(self)
+    }
+
+    /// Add an output path that must not exist yet to the root conjunction.
+    pub fn require_absent<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms).require_absent(path)?;
+        Ok(self)
+    }
+
+    /// Add a glob term to the root conjunction, requiring at least `min_matches` matches.
+    pub fn require_glob<S: Into<String>>(
+        &mut self,
+        pattern: S,
+        min_matches: usize,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms)
+            .require_glob(pattern, min_matches)?;
+        Ok(self)
+    }
+
+    /// Add a glob term to the root conjunction, requiring the matching files to
+    /// total at least `min_bytes`.
+    pub fn require_glob_total_size<S: Into<String>>(
+        &mut self,
+        pattern: S,
+        min_bytes: u64,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms)
+            .require_glob_total_size(pattern, min_bytes)?;
+        Ok(self)
+    }
+
+    /// Add a nested conjunction (`AND`) to the root conjunction.
+    pub fn require_all<F>(&mut self, f: F) -> Result<&mut Self, FileRequirementBuildError>
+    where
+        F: FnOnce(&mut GroupBuilder<'_>) -> Result<(), FileRequirementBuildError>,
+    {
+        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms).require_all(f)?;
+        Ok(self)
+    }
+
+    /// Add a nested disjunction (`OR`) to the root conjunction.
+    pub fn require_any<F>(&mut self, f: F) -> Result<&mut Self, FileRequirementBuildError>
+    where
+        F: FnOnce(&mut GroupBuilder<'_>) -> Result<(), FileRequirementBuildError>,
+    {
+        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms).require_any(f)?;
+        Ok(self)
+    }
+
+    /// Add a named disjunction (`OR`) to the root conjunction.
+    ///
+    /// See [`GroupBuilder::require_any_named`].
+    pub fn require_any_named<S, F>(
+        &mut self,
+        name: S,
+        f: F,
+    ) -> Result<&mut Self, FileRequirementBuildError>
+    where
+        S: Into<String>,
+        F: FnOnce(&mut GroupBuilder<'_>) -> Result<(), FileRequirementBuildError>,
+    {
+        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms).require_any_named(name, f)?;
+        Ok(self)
+    }
+
+    /// Add a nested mutually exclusive group (`XOR`) to the root conjunction.
+    pub fn require_exactly_one<F>(&mut self, f: F) -> Result<&mut Self, FileRequirementBuildError>
+    where
+        F: FnOnce(&mut GroupBuilder<'_>) -> Result<(), FileRequirementBuildError>,
+    {
+        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms).require_exactly_one(f)?;
+        Ok(self)
+    }
+
+    /// Join every relative path and glob pattern onto `dir` when building.
+    ///
+    /// Terms added after this call are compared for duplicates as joined onto
+    /// `dir`, so `a` and `dir/a` conflict; set it before adding terms.
+    pub fn base_dir<P: AsRef<Path>>(&mut self, dir: P) -> &mut Self {
+        self.seen_terms.rebase_onto(dir.as_ref());
+        self.base_dir = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Accept terms such as `s3://bucket/key` whose URI scheme has a backend in
+    /// `registry`, for checking with the registry as the file provider.
+    ///
+    /// Without a registry, any path with a URI scheme is rejected with
+    /// [`FileRequirementBuildError::UnknownScheme`]. Only terms added after
+    /// this call may use the registry's schemes.
+    pub fn schemes(&mut self, registry: &SchemeRegistry) -> &mut Self {
+        self.seen_terms.allow_schemes(registry.schemes());
+        self
+    }
+
+    /// Let the same term appear in different branches of an `OR` or `XOR`
+    /// group, as in `((a AND b) OR (a AND c))`.
+    ///
+    /// A term is still rejected with [`FileRequirementBuildError::DuplicateFile`]
+    /// when it repeats a term outside the group, such as `(a AND (a OR b))`,
+    /// or when two branches of one group are identical. Only groups added
+    /// after this call are relaxed.
+    pub fn allow_shared_terms(&mut self) -> &mut Self {
+        self.seen_terms.share_terms();
+        self
+    }
+
+    /// Attach a remediation hint to the most recently added root term or group.
+    ///
+    /// Does nothing if no term has been added yet.
+    pub fn with_hint<S: Into<String>>(&mut self, hint: S) -> &mut Self {
+        GroupBuilder::new(&mut self.root_terms, &mut self.seen_terms).with_hint(hint);
+        self
+    }
+
+    /// Record build errors instead of returning them, so that
+    /// [`try_build`](Self::try_build) can report every problem of a spec at
+    /// once rather than only the first.
+    ///
+    /// A repeated term or unknown URI scheme is recorded and the term kept; an
+    /// invalid term or empty group is recorded and left out. The `require_*`
+    /// methods then only fail with errors returned by group closures.
+    pub fn collect_errors(&mut self) -> &mut Self {
+        self.seen_terms.collect_errors();
+        self
+    }
+
+    /// Build the expression, or return every error recorded since
+    /// [`collect_errors`](Self::collect_errors), in the order they occurred.
+    pub fn try_build(mut self) -> Result<FileRequirement, Vec<FileRequirementBuildError>> {
+        let errors = self.seen_terms.take_errors();
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        Ok(self.build())
+    }
+
+    /// Build the final requirement expression.
+    ///
+    /// Errors recorded by [`collect_errors`](Self::collect_errors) are
+    /// discarded; use [`try_build`](Self::try_build) to see them.
+    pub fn build(self) -> FileRequirement {
+        let built = FileRequirement::All(self.root_terms);
+        match &self.base_dir {
+            Some(base) => built.rebased(base),
+            None => built,
+        }
+    }
+}
+
+impl Default for FileRequirementBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Nested group builder used to create `AND` / `OR` sub-expressions.
+pub struct GroupBuilder<'a> {
+    target: &'a mut Vec<FileRequirement>,
+    seen_terms: &'a mut SeenTerms,
+}
+
+impl<'a> GroupBuilder<'a> {
+    fn new(target: &'a mut Vec<FileRequirement>, seen_terms: &'a mut SeenTerms) -> Self {
+        Self { target, seen_terms }
+    }
+
+    /// Add a required file term to this group.
+    pub fn require_file<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        let owned_path = self.claim_path(path.as_ref())?;
+        self.push(FileRequirement::File(owned_path));
+        Ok(self)
+    }
+
+    /// Add a required file term with extra constraints to this group.
+    pub fn require_file_with<P: AsRef<Path>, C: Into<FileConstraints>>(
+        &mut self,
+        path: P,
+        constraints: C,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        let path = self.claim_path(path.as_ref())?;
+        self.push(FileRequirement::FileWith {
+            path,
+            constraints: constraints.into(),
+        });
+        Ok(self)
+    }
+
+    /// Add a required file term that must be a regular file.
+    ///
+    /// Unlike [`GroupBuilder::require_file`], a directory at this path fails the check.
+    pub fn require_regular_file<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        self.require_file_with(path, FileConstraints::new().regular_file())
+    }
+
+    /// Add a required file term that must have been modified within `max_age`.
+    pub fn require_file_newer_than<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        max_age: Duration,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        self.require_file_with(path, Age::AtMost(max_age))
+    }
+
+    /// Add a required file term whose contents must start with `magic`, e.g.
+    /// [`Magic::Gzip`] or `&[0x1f, 0x8b]`.
+    pub fn require_file_with_magic<P: AsRef<Path>, M: Into<Magic>>(
+        &mut self,
+        path: P,
+        magic: M,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        self.require_file_with(path, FileConstraints::new().magic(magic))
+    }
+
+    /// Add a required file term whose contents must hash to the SHA-256 `hex` digest.
+    ///
+    /// The file is read in full during the check.
+    #[cfg(feature = "checksum")]
+    pub fn require_file_with_sha256<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        hex: &str,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        let digest = match Digest::sha256(hex) {
+            Ok(digest) => digest,
+            Err(e) => return self.skip_invalid(e),
+        };
+        self.require_file_with(path, FileConstraints::new().digest(digest))
+    }
+
+    /// Add a required directory term to this group.
+    ///
+    /// The path must exist and be a directory.
+    pub fn require_dir<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        let path = self.claim_path(path.as_ref())?;
+        self.push(FileRequirement::Dir {
+            path,
+            non_empty: false,
+        });
+        Ok(self)
+    }
+
+    /// Add a required directory term that must contain at least one entry.
+    pub fn require_non_empty_dir<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        let path = self.claim_path(path.as_ref())?;
+        self.push(FileRequirement::Dir {
+            path,
+            non_empty: true,
+        });
+        Ok(self)
+    }
+
+    /// Add a directory term that must contain at least `min_entries` entries.
+    pub fn require_dir_entries<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        min_entries: usize,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        let path = self.claim_path(path.as_ref())?;
+        self.push(FileRequirement::DirEntries {
+            path,
+            pattern: None,
+            min_entries,
+        });
+        Ok(self)
+    }
+
+    /// Add a directory term that must contain at least `min_entries` entries
+    /// whose file names match the glob `pattern`, such as `*.txt`.
+    ///
+    /// A failed check reports how many matching entries were found.
+    pub fn require_dir_entries_matching<P: AsRef<Path>, S: Into<String>>(
+        &mut self,
+        path: P,
+        pattern: S,
+        min_entries: usize,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        let pattern = pattern.into();
+        if let Err(e) = glob::Pattern::new(&pattern) {
+            return self.skip_invalid(FileRequirementBuildError::InvalidGlob {
+                pattern,
+                message: e.to_string(),
+            });
+        }
+        let path = self.claim_path(path.as_ref())?;
+        self.push(FileRequirement::DirEntries {
+            path,
+            pattern: Some(pattern),
+            min_entries,
+        });
+        Ok(self)
+    }
+
+    /// Add an output directory term to this group.
+    ///
+    /// The directory must exist and accept new files, or be creatable because
+    /// its nearest existing ancestor is a writable directory.
+    pub fn require_writable_dir<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        let path = self.claim_path(path.as_ref())?;
+        self.push(FileRequirement::WritableDir(path));
+        Ok(self)
+    }
+
+    /// Require at least `bytes` of free space on the filesystem containing `path`.
+    ///
+    /// A missing `path` is measured at its nearest existing ancestor. Space
+    /// terms do not claim `path`, so it may also appear in a file or directory
+    /// term. Checking against [`StdFs`] requires the `free-space` feature.
+    pub fn require_free_space<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        bytes: u64,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        self.push(FileRequirement::FreeSpace {
+            path: path.as_ref().to_path_buf(),
+            bytes,
+        });
+        Ok(self)
+    }
+
+    /// Require `path` and `other` to be on the same filesystem, such as an
+    /// input directory and the staging directory it is hard-linked into.
+    ///
+    /// Missing paths are placed by their nearest existing ancestor, and the
+    /// paths are not claimed, as with [`require_free_space`](Self::require_free_space).
+    /// Device ids are compared, which [`StdFs`] reports on Unix only.
+    pub fn require_same_filesystem<P: AsRef<Path>, Q: AsRef<Path>>(
+        &mut self,
+        path: P,
+        other: Q,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        self.push(FileRequirement::SameFilesystem {
+            path: path.as_ref().to_path_buf(),
+            other: other.as_ref().to_path_buf(),
+        });
+        Ok(self)
+    }
+
+    /// Add an environment variable, such as `REF_DIR`, that must be set.
+    pub fn require_env_var<S: Into<String>>(
+        &mut self,
+        name: S,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        let name = name.into();
+        if let Err(e) = eval::check_env_var_name(&name) {
+            return self.skip_invalid(e);
+        }
+        self.push(FileRequirement::EnvVar {
+            name,
+            non_empty: false,
+            pattern: None,
+        });
+        Ok(self)
+    }
+
+    /// Add an environment variable that must be set to a non-empty value.
+    pub fn require_non_empty_env_var<S: Into<String>>(
+        &mut self,
+        name: S,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        let name = name.into();
+        if let Err(e) = eval::check_env_var_name(&name) {
+            return self.skip_invalid(e);
+        }
+        self.push(FileRequirement::EnvVar {
+            name,
+            non_empty: true,
+            pattern: None,
+        });
+        Ok(self)
+    }
+
+    /// Add an environment variable whose whole value must match the regular
+    /// expression `pattern`.
+    #[cfg(feature = "regex")]
+    pub fn require_env_var_matching<S: Into<String>>(
+        &mut self,
+        name: S,
+        pattern: &str,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        let name = name.into();
+        if let Err(e) =
+            eval::check_env_var_name(&name).and_then(|()| eval::compile_anchored(pattern).map(drop))
+        {
+            return self.skip_invalid(e);
+        }
+        self.push(FileRequirement::EnvVar {
+            name,
+            non_empty: false,
+            pattern: Some(pattern.to_owned()),
+        });
+        Ok(self)
+    }
+
+    /// Add a program, such as `samtools`, that must be found on `PATH`.
+    ///
+    /// On Windows the `PATHEXT` extensions (`.exe`, `.bat`, ...) are also tried.
+    pub fn require_executable<P: AsRef<Path>>(
+        &mut self,
+        name: P,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        self.seen_terms.claim_executable(name.as_ref())?;
+        self.push(FileRequirement::Executable(name.as_ref().to_path_buf()));
+        Ok(self)
+    }
+
+    /// Add an `http://` or `https://` URL, such as a reference download, that
+    /// must answer a `HEAD` request with a success status.
+    ///
+    /// `404 Not Found` and `410 Gone` report the URL as missing; other failures
+    /// are IO errors. Checking against [`StdFs`] requires the `http` feature.
+    pub fn require_url<S: Into<String>>(
+        &mut self,
+        url: S,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        let url = url.into();
+        self.seen_terms.claim(Path::new(&url))?;
+        self.push(FileRequirement::Url(url));
+        Ok(self)
+    }
+
+    /// Add a path, such as `info.json`, that must be a member of the `.tar`,
+    /// `.tar.gz` / `.tgz` or `.zip` archive at `archive`.
+    ///
+    /// The format follows the archive's extension. Only the archive index is
+    /// read: the central directory of a zip, or the entry headers of a tar
+    /// until `member` is found. A leading `./` on member names is ignored.
+    /// Checking against [`StdFs`] requires the `archive` feature.
+    pub fn require_archive_member<P: AsRef<Path>, S: Into<String>>(
+        &mut self,
+        archive: P,
+        member: S,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        let (archive, member) = (archive.as_ref(), member.into());
+        self.seen_terms.claim_member(archive, &member)?;
+        self.push(FileRequirement::ArchiveMember {
+            archive: archive.to_path_buf(),
+            member,
+        });
+        Ok(self)
+    }
+
+    /// Add a required file term with a descriptive name.
+    ///
+    /// Error messages then read `missing files: transcriptome index (idx.sshash)`.
+    pub fn require_file_named<S: Into<String>, P: AsRef<Path>>(
+        &mut self,
+        name: S,
+        path: P,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        let path = self.claim_path(path.as_ref())?;
+        self.push(FileRequirement::Named {
+            name: name.into(),
+            term: Box::new(FileRequirement::File(path)),
+        });
+        Ok(self)
+    }
+
+    /// Add a path that must not exist to this group.
+    ///
+    /// Use this for outputs that must not be overwritten; see
+    /// [`CheckOptions::allow_overwrite`] to relax it to a warning.
+    pub fn require_absent<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        let path = self.claim_path(path.as_ref())?;
+        self.push(FileRequirement::Absent(path));
+        Ok(self)
+    }
+
+    /// Add a recommended file to this group.
+    ///
+    /// The term never fails the check; when the file is absent,
+    /// [`CheckReport::warnings`] lists it, together with its hint if one is
+    /// attached with [`with_hint`](Self::with_hint).
+    pub fn recommend_file<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        let path = self.claim_path(path.as_ref())?;
+        self.push(FileRequirement::Recommended(path));
+        Ok(self)
+    }
+
+    /// Fail with `error`, or record it and add nothing when the builder
+    /// collects errors.
+    fn skip_invalid(
+        &mut self,
+        error: FileRequirementBuildError,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        self.seen_terms.report(error)?;
+        Ok(self)
+    }
+
+    /// Add `term` to this group.
+    fn push(&mut self, term: FileRequirement) {
+        self.seen_terms.mark_added();
+        self.target.push(term);
+    }
+
+    /// Record `path` as used, rejecting duplicates anywhere in the tree.
+    fn claim_path(&mut self, path: &Path) -> Result<PathBuf, FileRequirementBuildError> {
+        self.seen_terms.claim_path(path)?;
+        Ok(path.to_path_buf())
+    }
+
+    /// Add a glob term to this group, requiring at least `min_matches` matches.
+    ///
+    /// Patterns use the syntax of the [`glob`](https://docs.rs/glob) crate and are
+    /// validated here; the pattern string takes part in duplicate-term detection.
+    pub fn require_glob<S: Into<String>>(
+        &mut self,
+        pattern: S,
+        min_matches: usize,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        let pattern = pattern.into();
+        if let Err(e) = glob::Pattern::new(&pattern) {
+            return self.skip_invalid(FileRequirementBuildError::InvalidGlob {
+                pattern,
+                message: e.to_string(),
+            });
+        }
+        self.claim_path(Path::new(&pattern))?;
+        self.push(FileRequirement::Glob {
+            pattern,
+            min_matches,
+        });
+        Ok(self)
+    }
+
+    /// Add a glob term to this group, requiring the matching files to total at
+    /// least `min_bytes`, such as `reads/*.fastq.gz` totalling 1 GB.
+    ///
+    /// This catches placeholder or truncated datasets whose files all exist.
+    /// No matches at all count as zero bytes.
+    pub fn require_glob_total_size<S: Into<String>>(
+        &mut self,
+        pattern: S,
+        min_bytes: u64,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        let pattern = pattern.into();
+        if let Err(e) = glob::Pattern::new(&pattern) {
+            return self.skip_invalid(FileRequirementBuildError::InvalidGlob {
+                pattern,
+                message: e.to_string(),
+            });
+        }
+        self.claim_path(Path::new(&pattern))?;
+        self.push(FileRequirement::GlobSize { pattern, min_bytes });
+        Ok(self)
+    }
+
+    /// Add a required file term to this group for every path of `paths`, in
+    /// order.
+    ///
+    /// A path repeated within `paths` or already in the tree fails with
+    /// [`FileRequirementBuildError::DuplicateFile`] naming it; the paths
+    /// before it stay added.
+    pub fn require_files<I>(&mut self, paths: I) -> Result<&mut Self, FileRequirementBuildError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<Path>,
+    {
+        for path in paths {
+            self.require_file(path)?;
+        }
+        Ok(self)
+    }
+
+    /// Add one required file per extension, named by appending `.ext` to
+    /// `prefix`: `require_with_extensions("idx", ["ctab", "ssi.mphf"])` requires
+    /// `idx.ctab` and `idx.ssi.mphf`.
+    ///
+    /// Unlike [`Path::with_extension`], an existing extension of `prefix` is
+    /// kept, so `gencode.v44` yields `gencode.v44.ctab`.
+    pub fn require_with_extensions<P, I, S>(
+        &mut self,
+        prefix: P,
+        extensions: I,
+    ) -> Result<&mut Self, FileRequirementBuildError>
+    where
+        P: AsRef<Path>,
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for extension in extensions {
+            self.require_file(with_suffix(prefix.as_ref(), extension.as_ref()))?;
+        }
+        Ok(self)
+    }
+
+    /// Add a disjunction (`OR`) of the files named by appending each extension
+    /// to `prefix`, as in [`require_with_extensions`](Self::require_with_extensions).
+    pub fn require_any_with_extensions<P, I, S>(
+        &mut self,
+        prefix: P,
+        extensions: I,
+    ) -> Result<&mut Self, FileRequirementBuildError>
+    where
+        P: AsRef<Path>,
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.require_any(|any| {
+            any.require_with_extensions(prefix, extensions)?;
+            Ok(())
+        })
+    }
+
+    /// Add one required file term per shard of `range`, naming shard `i` by
+    /// substituting `i` for the `{}` placeholder of `template`.
+    ///
+    /// `{:0N}` zero-pads the index to `N` digits, so `part_{:03}.rad` over
+    /// `0..2` requires `part_000.rad` and `part_001.rad`.
+    pub fn require_shards<S: AsRef<str>>(
+        &mut self,
+        template: S,
+        range: std::ops::Range<usize>,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        let template = template.as_ref();
+        if let Err(e) = shards::validate(template, &range, 0) {
+            return self.skip_invalid(e);
+        }
+        for index in range {
+            self.require_file(shards::expand(template, index))?;
+        }
+        Ok(self)
+    }
+
+    /// Add a single term requiring at least `min` of the shards of `range`,
+    /// named as in [`require_shards`](Self::require_shards).
+    ///
+    /// A failed check reports how many shards were found, e.g. `too few
+    /// shards: part_{}.rad (found 11 of 0..16, need at least 12)`.
+    pub fn require_shards_at_least<S: Into<String>>(
+        &mut self,
+        template: S,
+        range: std::ops::Range<usize>,
+        min: usize,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        let template = template.into();
+        if let Err(e) = shards::validate(&template, &range, min) {
+            return self.skip_invalid(e);
+        }
+        self.claim_path(Path::new(&template))?;
+        self.push(FileRequirement::Shards {
+            template,
+            start: range.start,
+            end: range.end,
+            min,
+        });
+        Ok(self)
+    }
+
+    /// Add a nested conjunction (`AND`) group.
+    pub fn require_all<F>(&mut self, f: F) -> Result<&mut Self, FileRequirementBuildError>
+    where
+        F: FnOnce(&mut GroupBuilder<'_>) -> Result<(), FileRequirementBuildError>,
+    {
+        let mut child_terms = Vec::new();
+        f(&mut GroupBuilder::new(&mut child_terms, self.seen_terms))?;
+        if child_terms.is_empty() {
+            self.seen_terms
+                .report(FileRequirementBuildError::EmptyGroup { group: "AND" })?;
+            return Ok(self);
+        }
+        self.push(FileRequirement::All(child_terms));
+        Ok(self)
+    }
+
+    /// Add a nested disjunction (`OR`) group.
+    pub fn require_any<F>(&mut self, f: F) -> Result<&mut Self, FileRequirementBuildError>
+    where
+        F: FnOnce(&mut GroupBuilder<'_>) -> Result<(), FileRequirementBuildError>,
+    {
+        let child_terms = self.disjunction_terms(f)?;
+        if child_terms.is_empty() {
+            self.seen_terms
+                .report(FileRequirementBuildError::EmptyGroup { group: "OR" })?;
+            return Ok(self);
+        }
+        self.push_disjunction(FileRequirement::Any(child_terms))
+    }
+
+    /// Add a nested disjunction (`OR`) group with a descriptive name.
+    ///
+    /// When no alternative is satisfied, the error reads
+    /// `no valid index format found: tried 'idx.sshash', 'idx.ssi+idx.ssi.mphf'`
+    /// instead of listing the whole group expression.
+    pub fn require_any_named<S, F>(
+        &mut self,
+        name: S,
+        f: F,
+    ) -> Result<&mut Self, FileRequirementBuildError>
+    where
+        S: Into<String>,
+        F: FnOnce(&mut GroupBuilder<'_>) -> Result<(), FileRequirementBuildError>,
+    {
+        let before = self.target.len();
+        self.require_any(f)?;
+        if self.target.len() > before
+            && let Some(group) = self.target.pop()
+        {
+            self.target.push(FileRequirement::Named {
+                name: name.into(),
+                term: Box::new(group),
+            });
+        }
+        Ok(self)
+    }
+
+    /// Add a nested mutually exclusive (`XOR`) group.
+    ///
+    /// The group is satisfied only when exactly one of its children is.
+    pub fn require_exactly_one<F>(&mut self, f: F) -> Result<&mut Self, FileRequirementBuildError>
+    where
+        F: FnOnce(&mut GroupBuilder<'_>) -> Result<(), FileRequirementBuildError>,
+    {
+        let child_terms = self.disjunction_terms(f)?;
+        if child_terms.is_empty() {
+            self.seen_terms
+                .report(FileRequirementBuildError::EmptyGroup { group: "XOR" })?;
+            return Ok(self);
+        }
+        self.push_disjunction(FileRequirement::ExactlyOne(child_terms))
+    }
+
+    /// The branches `f` adds to an `OR` / `XOR` group. With shared terms,
+    /// their duplicate checks wait for [`push_disjunction`](Self::push_disjunction).
+    fn disjunction_terms<F>(
+        &mut self,
+        f: F,
+    ) -> Result<Vec<FileRequirement>, FileRequirementBuildError>
+    where
+        F: FnOnce(&mut GroupBuilder<'_>) -> Result<(), FileRequirementBuildError>,
+    {
+        let mut child_terms = Vec::new();
+        if self.seen_terms.shares_terms() {
+            let mut branch_terms = self.seen_terms.deferred();
+            let added = f(&mut GroupBuilder::new(&mut child_terms, &mut branch_terms));
+            self.seen_terms.absorb_errors(branch_terms);
+            added?;
+        } else {
+            f(&mut GroupBuilder::new(&mut child_terms, self.seen_terms))?;
+        }
+        Ok(child_terms)
+    }
+
+    fn push_disjunction(
+        &mut self,
+        group: FileRequirement,
+    ) -> Result<&mut Self, FileRequirementBuildError> {
+        if self.seen_terms.shares_terms() {
+            self.seen_terms.claim_tree(&group)?;
+        }
+        self.push(group);
+        Ok(self)
+    }
+
+    /// Attach a remediation hint to the most recently added term or group.
+    ///
+    /// When that part fails, the check error lists the hint, e.g.
+    /// `hint: run `salmon index` to generate this file`. Does nothing if the
+    /// group is still empty.
+    pub fn with_hint<S: Into<String>>(&mut self, hint: S) -> &mut Self {
+        if self.seen_terms.last_skipped() {
+            return self;
+        }
+        if let Some(last) = self.target.pop() {
+            self.target.push(last.with_hint(hint));
+        }
+        self
+    }
+}
+
+/// `prefix` with `.extension` appended.
+pub(crate) fn with_suffix(prefix: &Path, extension: &str) -> PathBuf {
+    let mut path = prefix.as_os_str().to_owned();
+    path.push(".");
+    path.push(extension.trim_start_matches('.'));
+    PathBuf::from(path)
+}
+
+impl FileRequirement {
+    /// Wrap this term or group with a remediation hint shown when it fails.
+    pub fn with_hint<S: Into<String>>(self, hint: S) -> Self {
+        FileRequirement::Hinted {
+            hint: hint.into(),
+            term: Box::new(self),
+        }
+    }
+
+    /// Validate this requirement expression against the local filesystem.
+    pub fn check(&self) -> Result<(), FileRequirementCheckError> {
+        self.check_with_provider(&StdFs)
+    }
+
+    /// Validate this requirement expression against a custom [`FileProvider`].
+    pub fn check_with_provider<P: FileProvider>(
+        &self,
+        provider: &P,
+    ) -> Result<(), FileRequirementCheckError> {
+        let report = self.check_report_with_provider(provider);
+        if report.is_satisfied() {
+            Ok(())
+        } else {
+            Err(FileRequirementCheckError::from_report(&report))
+        }
+    }
+
+    /// Validate this requirement expression against the local filesystem under
+    /// `options`.
+    pub fn check_with_options(
+        &self,
+        options: &CheckOptions,
+    ) -> Result<(), FileRequirementCheckError> {
+        let report = self.check_report_with_options(options);
+        if report.passes(options.failure_threshold()) {
+            Ok(())
+        } else {
+            Err(FileRequirementCheckError::from_report_failing_on(
+                &report,
+                options.failure_threshold(),
+            ))
+        }
+    }
+
+    /// Validate this requirement expression with relative paths and glob patterns
+    /// resolved against `base`. The stored tree is not modified.
+    pub fn check_in<P: AsRef<Path>>(&self, base: P) -> Result<(), FileRequirementCheckError> {
+        self.check_with_options(&CheckOptions::new().base_dir(base))
+    }
+
+    /// Validate this requirement expression with a caller-supplied existence predicate.
+    ///
+    /// `pred` is invoked once per evaluated file term and follows the contract of
+    /// [`Path::try_exists`].
+    ///
+    /// Terms other than plain files report an `Unsupported` IO error.
+    pub fn check_with<F>(&self, pred: F) -> Result<(), FileRequirementCheckError>
+    where
+        F: FnMut(&Path) -> std::io::Result<bool>,
+    {
+        self.check_with_provider(&provider::PredicateProvider::new(pred))
+    }
+
+    /// Validate this requirement expression against a listing of existing paths,
+    /// such as an object-store prefix listing or an archive manifest.
+    ///
+    /// Paths are compared verbatim; no normalization is applied.
+    pub fn check_against<I>(&self, paths: I) -> Result<(), FileRequirementCheckError>
+    where
+        I: IntoIterator<Item = PathBuf>,
+    {
+        let listing: HashSet<PathBuf> = paths.into_iter().collect();
+        self.check_with_provider(&listing)
+    }
+
+    /// Evaluate this requirement expression and return a structured per-node report.
+    ///
+    /// Branches of an `OR` group after the first satisfied one are reported as
+    /// [`LeafOutcome::Skipped`].
+    pub fn check_report(&self) -> CheckReport {
+        self.check_report_with_provider(&StdFs)
+    }
+
+    /// Like [`FileRequirement::check_report`], resolving file terms with `provider`.
+    pub fn check_report_with_provider<P: FileProvider>(&self, provider: &P) -> CheckReport {
+        let metered = Metered::new(provider);
+        let root = self.evaluate(&mut |leaf| timed(|| leaf.evaluate_leaf(&metered)));
+        metered.report(root)
+    }
+
+    /// Like [`FileRequirement::check_report`], applying `options`.
+    pub fn check_report_with_options(&self, options: &CheckOptions) -> CheckReport {
+        let metered = Metered::new(StdFs);
+        let prepared = options.prepare(self);
+        let root = prepared.evaluate_with(options.strategy(), &mut |leaf| {
+            timed(|| options.evaluate_leaf(&prepared, leaf, &metered))
+        });
+        let mut report = metered.report(root);
+        if options.suggests_similar() {
+            report.attach_suggestions(&StdFs);
+        }
+        report
+    }
+
+    /// All file term paths, in depth-first declaration order.
+    ///
+    /// Glob patterns are not included.
+    pub fn paths(&self) -> Vec<&Path> {
+        fn walk<'a>(node: &'a FileRequirement, out: &mut Vec<&'a Path>) {
+            match node {
+                FileRequirement::File(path) => out.push(path),
+                FileRequirement::FileWith { path, .. }
+                | FileRequirement::Dir { path, .. }
+                | FileRequirement::DirEntries { path, .. }
+                | FileRequirement::Absent(path)
+                | FileRequirement::Recommended(path)
+                | FileRequirement::WritableDir(path)
+                | FileRequirement::ArchiveMember { archive: path, .. } => out.push(path),
+                FileRequirement::Glob { .. }
+                | FileRequirement::GlobSize { .. }
+                | FileRequirement::Shards { .. }
+                | FileRequirement::FreeSpace { .. }
+                | FileRequirement::SameFilesystem { .. }
+                | FileRequirement::EnvVar { .. }
+                | FileRequirement::Executable(_)
+                | FileRequirement::Url(_) => {}
+                FileRequirement::Named { term, .. } | FileRequirement::Hinted { term, .. } => {
+                    walk(term, out)
+                }
+                FileRequirement::All(children)
+                | FileRequirement::Any(children)
+                | FileRequirement::ExactlyOne(children) => {
+                    for child in children {
+                        walk(child, out);
+                    }
+                }
+            }
+        }
+        let mut out = Vec::new();
+        walk(self, &mut out);
+        out
+    }
+}
+
+/// The compact one-line form, e.g. `(a.txt AND (b.txt OR c.txt))`.
+///
+/// The alternate form (`{:#}`) puts every term on its own line, indenting
+/// nested groups by four spaces. Both forms parse back with [`str::parse`].
+impl std::fmt::Display for FileRequirement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if f.alternate() {
+            return self.fmt_pretty(f, 0);
+        }
         match self {
-            FileRequirement::File(path) => match path.try_exists() {
-                Ok(true) => true,
-                Ok(false) => {
-                    ctx.missing_files.insert(path.display().to_string());
-                    false
+            FileRequirement::File(path) => parse::write_path(f, path),
+            FileRequirement::Glob {
+                pattern,
+                min_matches,
+            } => {
+                f.write_str("glob(")?;
+                parse::write_quoted(f, pattern)?;
+                write!(f, ", {})", min_matches)
+            }
+            FileRequirement::GlobSize { pattern, min_bytes } => {
+                f.write_str("glob_size(")?;
+                parse::write_quoted(f, pattern)?;
+                write!(f, ", {})", min_bytes)
+            }
+            FileRequirement::Shards {
+                template,
+                start,
+                end,
+                min,
+            } => {
+                f.write_str("shards(")?;
+                parse::write_quoted(f, template)?;
+                write!(f, ", {}..{}, {})", start, end, min)
+            }
+            FileRequirement::FileWith { path, constraints } => {
+                f.write_str("file(")?;
+                parse::write_path(f, path)?;
+                write!(f, "{})", constraints)
+            }
+            FileRequirement::Dir { path, non_empty } => {
+                f.write_str(if *non_empty { "non_empty_dir(" } else { "dir(" })?;
+                parse::write_path(f, path)?;
+                f.write_str(")")
+            }
+            FileRequirement::DirEntries {
+                path,
+                pattern,
+                min_entries,
+            } => {
+                f.write_str("dir_entries(")?;
+                parse::write_path(f, path)?;
+                write!(f, ", {}", min_entries)?;
+                if let Some(pattern) = pattern {
+                    f.write_str(", ")?;
+                    parse::write_quoted(f, pattern)?;
+                }
+                f.write_str(")")
+            }
+            FileRequirement::Absent(path) => {
+                f.write_str("absent(")?;
+                parse::write_path(f, path)?;
+                f.write_str(")")
+            }
+            FileRequirement::Recommended(path) => {
+                f.write_str("recommend(")?;
+                parse::write_path(f, path)?;
+                f.write_str(")")
+            }
+            FileRequirement::WritableDir(path) => {
+                f.write_str("writable_dir(")?;
+                parse::write_path(f, path)?;
+                f.write_str(")")
+            }
+            FileRequirement::EnvVar {
+                name,
+                non_empty,
+                pattern,
+            } => {
+                f.write_str("env(")?;
+                parse::write_path(f, Path::new(name))?;
+                if *non_empty {
+                    f.write_str(", non_empty")?;
+                }
+                if let Some(pattern) = pattern {
+                    f.write_str(", ")?;
+                    parse::write_path(f, Path::new(&format!("matches={}", pattern)))?;
+                }
+                f.write_str(")")
+            }
+            FileRequirement::Executable(name) => {
+                f.write_str("executable(")?;
+                parse::write_path(f, name)?;
+                f.write_str(")")
+            }
+            FileRequirement::ArchiveMember { archive, member } => {
+                f.write_str("archive_member(")?;
+                parse::write_path(f, archive)?;
+                f.write_str(", ")?;
+                parse::write_path(f, Path::new(member))?;
+                f.write_str(")")
+            }
+            FileRequirement::Url(url) => {
+                f.write_str("url(")?;
+                parse::write_quoted(f, url)?;
+                f.write_str(")")
+            }
+            FileRequirement::FreeSpace { path, bytes } => {
+                f.write_str("free_space(")?;
+                parse::write_path(f, path)?;
+                write!(f, ", {})", bytes)
+            }
+            FileRequirement::SameFilesystem { path, other } => {
+                f.write_str("same_filesystem(")?;
+                parse::write_path(f, path)?;
+                f.write_str(", ")?;
+                parse::write_path(f, other)?;
+                f.write_str(")")
+            }
+            FileRequirement::Named { name, term } => {
+                f.write_str("named(")?;
+                parse::write_quoted(f, name)?;
+                write!(f, ", {})", term)
+            }
+            FileRequirement::Hinted { hint, term } => {
+                f.write_str("hint(")?;
+                parse::write_quoted(f, hint)?;
+                write!(f, ", {})", term)
+            }
+            FileRequirement::All(children) => {
+                let joined = children
+                    .iter()
+                    .map(std::string::ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(" AND ");
+                write!(f, "({})", joined)
+            }
+            FileRequirement::Any(children) => {
+                let joined = children
+                    .iter()
+                    .map(std::string::ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(" OR ");
+                write!(f, "({})", joined)
+            }
+            FileRequirement::ExactlyOne(children) => {
+                let joined = children
+                    .iter()
+                    .map(std::string::ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(" XOR ");
+                write!(f, "({})", joined)
+            }
+        }
+    }
+}
+
+impl FileRequirement {
+    /// Multi-line rendering for `{:#}`, with groups starting at `depth`.
+    fn fmt_pretty(&self, f: &mut std::fmt::Formatter<'_>, depth: usize) -> std::fmt::Result {
+        let (operator, children) = match self {
+            FileRequirement::Named { name, term } => {
+                f.write_str("named(")?;
+                parse::write_quoted(f, name)?;
+                f.write_str(", ")?;
+                term.fmt_pretty(f, depth)?;
+                return f.write_str(")");
+            }
+            FileRequirement::Hinted { hint, term } => {
+                f.write_str("hint(")?;
+                parse::write_quoted(f, hint)?;
+                f.write_str(", ")?;
+                term.fmt_pretty(f, depth)?;
+                return f.write_str(")");
+            }
+            FileRequirement::All(children) => ("AND", children),
+            FileRequirement::Any(children) => ("OR", children),
+            FileRequirement::ExactlyOne(children) => ("XOR", children),
+            leaf => return write!(f, "{}", leaf),
+        };
+        let indent = "    ";
+        f.write_str("(\n")?;
+        for (i, child) in children.iter().enumerate() {
+            f.write_str(&indent.repeat(depth + 1))?;
+            if i > 0 {
+                write!(f, "{} ", operator)?;
+            }
+            child.fmt_pretty(f, depth + 1)?;
+            f.write_str("\n")?;
+        }
+        f.write_str(&indent.repeat(depth))?;
+        f.write_str(")")
+    }
+}
+
+#[derive(Default)]
+struct CheckContext {
+    missing_files: BTreeSet<String>,
+    broken_symlinks: BTreeSet<String>,
+    existing_outputs: BTreeSet<String>,
+    missing_executables: BTreeSet<String>,
+    missing_urls: BTreeSet<String>,
+    missing_members: BTreeSet<String>,
+    io_errors: BTreeSet<String>,
+    unset_variables: BTreeSet<String>,
+    empty_variables: BTreeSet<String>,
+    mismatched_variables: BTreeSet<String>,
+    unsatisfied_disjunctions: BTreeSet<String>,
+    unsatisfied_named_groups: BTreeSet<String>,
+    conflicting_groups: BTreeSet<String>,
+    unmatched_globs: BTreeSet<String>,
+    small_globs: BTreeSet<String>,
+    missing_shards: BTreeSet<String>,
+    sparse_directories: BTreeSet<String>,
+    not_directories: BTreeSet<String>,
+    not_regular_files: BTreeSet<String>,
+    denied_access: BTreeSet<(Access, String)>,
+    uncreatable_dirs: BTreeSet<String>,
+    low_space: BTreeSet<String>,
+    split_filesystems: BTreeSet<String>,
+    wrong_sizes: BTreeSet<String>,
+    wrong_ages: BTreeSet<String>,
+    stale_files: BTreeSet<String>,
+    wrong_formats: BTreeSet<String>,
+    wrong_owners: BTreeSet<String>,
+    digest_mismatches: BTreeSet<String>,
+    corrupt_gzips: BTreeSet<String>,
+    empty_directories: BTreeSet<String>,
+    changing_files: BTreeSet<String>,
+    hints: Vec<String>,
+    missing_paths: Vec<PathBuf>,
+    failed_io: Vec<(PathBuf, std::io::Error)>,
+    unsatisfied_groups: Vec<String>,
+    unresolved: BTreeSet<String>,
+    cancelled: BTreeSet<String>,
+}
+
+impl CheckContext {
+    /// Record an `OR` / `XOR` group none of whose alternatives was satisfied.
+    ///
+    /// Named groups list their alternatives by label; anonymous ones fall back
+    /// to the rendered expression.
+    fn unsatisfied_disjunction(&mut self, node: &NodeReport, group: &GroupReport) {
+        self.unsatisfied_groups.push(node.label());
+        match group.name() {
+            Some(name) => {
+                let tried: Vec<String> = group
+                    .children()
+                    .iter()
+                    .map(|child| format!("'{}'", child.label()))
+                    .collect();
+                self.unsatisfied_named_groups.insert(format!(
+                    "no valid {} found: tried {}",
+                    name,
+                    tried.join(", ")
+                ));
+            }
+            None => {
+                self.unsatisfied_disjunctions.insert(node.to_string());
+            }
+        }
+    }
+
+    /// Collect diagnostics for the unsatisfied parts of a report.
+    fn collect(&mut self, node: &NodeReport) {
+        if node.is_satisfied() || node.is_skipped() {
+            return;
+        }
+        let hint = match node {
+            NodeReport::Leaf(leaf) if *leaf.outcome() == LeafOutcome::Skipped => None,
+            NodeReport::Leaf(leaf) => leaf.hint(),
+            NodeReport::Group(group) => group.hint(),
+        };
+        if let Some(hint) = hint
+            && !self.hints.iter().any(|h| h == hint)
+        {
+            self.hints.push(hint.to_owned());
+        }
+        if let NodeReport::Leaf(leaf) = node {
+            match leaf.outcome() {
+                LeafOutcome::Missing if matches!(leaf.term(), FileRequirement::Url(_)) => {}
+                LeafOutcome::Missing => self.missing_paths.push(leaf.path().to_path_buf()),
+                outcome @ LeafOutcome::IoError { .. } => {
+                    if let Some(error) = outcome.io_error() {
+                        self.failed_io.push((leaf.path().to_path_buf(), error));
+                    }
+                }
+                _ => {}
+            }
+        }
+        match node {
+            NodeReport::Leaf(leaf) => match leaf.outcome() {
+                LeafOutcome::Missing if matches!(leaf.term(), FileRequirement::Url(_)) => {
+                    self.missing_urls.insert(leaf.subject());
+                }
+                LeafOutcome::Missing if leaf.suggestions().is_empty() => {
+                    self.missing_files.insert(leaf.subject());
+                }
+                LeafOutcome::Missing => {
+                    let names: Vec<String> = leaf
+                        .suggestions()
+                        .iter()
+                        .map(|path| {
+                            let name = path.file_name().unwrap_or(path.as_os_str());
+                            format!("`{}`", name.to_string_lossy())
+                        })
+                        .collect();
+                    self.missing_files.insert(format!(
+                        "{} (did you mean {}?)",
+                        leaf.subject(),
+                        names.join(" or ")
+                    ));
+                }
+                LeafOutcome::NotOnPath { searched } => {
+                    let searched = if searched.is_empty() {
+                        "PATH is empty".to_owned()
+                    } else {
+                        let dirs: Vec<String> =
+                            searched.iter().map(|d| d.display().to_string()).collect();
+                        format!("searched {}", dirs.join(", "))
+                    };
+                    self.missing_executables
+                        .insert(format!("{} ({})", leaf.subject(), searched));
+                }
+                LeafOutcome::AlreadyExists => {
+                    self.existing_outputs.insert(leaf.subject());
+                }
+                LeafOutcome::BrokenSymlink => {
+                    self.broken_symlinks.insert(leaf.subject());
+                }
+                LeafOutcome::UnsetVariable { name } => {
+                    self.unset_variables.insert(match leaf.term() {
+                        FileRequirement::EnvVar { .. } => format!("${}", name),
+                        _ => format!("${} (in {})", name, leaf.path().display()),
+                    });
+                }
+                LeafOutcome::EmptyVariable => {
+                    self.empty_variables
+                        .insert(format!("${}", leaf.path().display()));
+                }
+                LeafOutcome::VariableMismatch { length } => {
+                    if let FileRequirement::EnvVar {
+                        pattern: Some(pattern),
+                        ..
+                    } = leaf.term()
+                    {
+                        self.mismatched_variables.insert(format!(
+                            "${} ({}-character value, expected /{}/)",
+                            leaf.path().display(),
+                            length,
+                            pattern
+                        ));
+                    }
+                }
+                LeafOutcome::IoError { message, .. } => {
+                    self.io_errors
+                        .insert(format!("{} ({})", leaf.subject(), message));
+                }
+                LeafOutcome::TooFewMatches { found } => {
+                    if let FileRequirement::Shards {
+                        template,
+                        start,
+                        end,
+                        min,
+                    } = leaf.term()
+                    {
+                        self.missing_shards.insert(format!(
+                            "{} (found {} of {}..{}, need at least {})",
+                            template, found, start, end, min
+                        ));
+                    }
+                    if let FileRequirement::DirEntries {
+                        path,
+                        pattern,
+                        min_entries,
+                    } = leaf.term()
+                    {
+                        let entries = match pattern {
+                            Some(pattern) => format!("`{}` entries", pattern),
+                            None => "entries".to_owned(),
+                        };
+                        self.sparse_directories.insert(format!(
+                            "{} (found {} {}, need at least {})",
+                            path.display(),
+                            found,
+                            entries,
+                            min_entries
+                        ));
+                    }
+                    if let FileRequirement::Glob {
+                        pattern,
+                        min_matches,
+                    } = leaf.term()
+                    {
+                        self.unmatched_globs.insert(format!(
+                            "{} (found {}, need at least {})",
+                            pattern, found, min_matches
+                        ));
+                    }
+                }
+                LeafOutcome::NotADirectory => {
+                    self.not_directories.insert(leaf.subject());
+                }
+                LeafOutcome::NotARegularFile { kind } => {
+                    let kind = match kind {
+                        FileKind::Dir => "directory",
+                        FileKind::Symlink => "symlink",
+                        FileKind::File | FileKind::Other => "special file",
+                    };
+                    self.not_regular_files
+                        .insert(format!("{} (is a {})", leaf.subject(), kind));
+                }
+                LeafOutcome::PermissionDenied { access } => {
+                    self.denied_access.insert((*access, leaf.subject()));
+                }
+                LeafOutcome::CannotCreate { ancestor } => {
+                    self.uncreatable_dirs.insert(format!(
+                        "{} ({} is not a writable directory)",
+                        leaf.subject(),
+                        ancestor.display()
+                    ));
+                }
+                LeafOutcome::InsufficientSpace { available } => {
+                    if let FileRequirement::FreeSpace { bytes, .. } = leaf.term() {
+                        self.low_space.insert(format!(
+                            "{} ({} byte(s) available, need {})",
+                            leaf.subject(),
+                            available,
+                            bytes
+                        ));
+                    }
+                }
+                LeafOutcome::DifferentFilesystem => {
+                    if let FileRequirement::SameFilesystem { path, other } = leaf.term() {
+                        self.split_filesystems.insert(format!(
+                            "{} and {}",
+                            path.display(),
+                            other.display()
+                        ));
+                    }
+                }
+                LeafOutcome::SizeOutOfRange { actual } => {
+                    if let FileRequirement::GlobSize { pattern, min_bytes } = leaf.term() {
+                        self.small_globs.insert(format!(
+                            "{} ({} byte(s) in total, need at least {})",
+                            pattern, actual, min_bytes
+                        ));
+                    }
+                    if let FileRequirement::FileWith { constraints, .. } = leaf.term()
+                        && let Some(size) = constraints.size_bounds()
+                    {
+                        self.wrong_sizes.insert(format!(
+                            "{} ({} byte(s), expected {})",
+                            leaf.subject(),
+                            actual,
+                            size
+                        ));
+                    }
+                }
+                LeafOutcome::AgeOutOfRange { modified } => {
+                    if let FileRequirement::FileWith { constraints, .. } = leaf.term()
+                        && let Some(age) = constraints.age_bounds()
+                    {
+                        self.wrong_ages.insert(format!(
+                            "{} (modified {}, expected {})",
+                            leaf.subject(),
+                            constraints::DisplayTime(*modified),
+                            age
+                        ));
+                    }
+                }
+                LeafOutcome::Stale {
+                    modified,
+                    dependency_modified,
+                } => {
+                    if let FileRequirement::FileWith { constraints, .. } = leaf.term()
+                        && let Some(dependency) = constraints.dependency()
+                    {
+                        self.stale_files.insert(format!(
+                            "{} (modified {}, but {} was modified {})",
+                            leaf.subject(),
+                            constraints::DisplayTime(*modified),
+                            dependency.display(),
+                            constraints::DisplayTime(*dependency_modified)
+                        ));
+                    }
+                }
+                LeafOutcome::MagicMismatch { found } => {
+                    if let FileRequirement::FileWith { constraints, .. } = leaf.term()
+                        && let Some(magic) = constraints.expected_magic()
+                    {
+                        self.wrong_formats.insert(format!(
+                            "{} (expected {}, found bytes {})",
+                            leaf.subject(),
+                            magic,
+                            constraints::DisplayBytes(found)
+                        ));
+                    }
+                }
+                LeafOutcome::WrongOwner { uid, gid } => {
+                    #[cfg(feature = "unix")]
+                    if let FileRequirement::FileWith { constraints, .. } = leaf.term() {
+                        let mut expected = Vec::new();
+                        if let Some(owner) = constraints.required_owner() {
+                            expected.push(format!("owner {}", owner));
+                        }
+                        if let Some(group) = constraints.required_group() {
+                            expected.push(format!("group gid {}", group));
+                        }
+                        self.wrong_owners.insert(format!(
+                            "{} (owned by uid {}, gid {}; expected {})",
+                            leaf.subject(),
+                            uid,
+                            gid,
+                            expected.join(", ")
+                        ));
+                    }
+                    #[cfg(not(feature = "unix"))]
+                    let _ = (uid, gid);
+                }
+                LeafOutcome::DigestMismatch { actual } => {
+                    #[cfg(feature = "checksum")]
+                    if let FileRequirement::FileWith { constraints, .. } = leaf.term()
+                        && let Some(digest) = constraints.expected_digest()
+                    {
+                        self.digest_mismatches.insert(format!(
+                            "{} (expected {} {}, got {})",
+                            leaf.subject(),
+                            digest.algorithm(),
+                            digest.hex(),
+                            actual
+                        ));
+                    }
+                    #[cfg(not(feature = "checksum"))]
+                    let _ = actual;
+                }
+                LeafOutcome::EmptyDirectory => {
+                    self.empty_directories.insert(leaf.subject());
                 }
-                Err(e) => {
-                    ctx.io_errors.insert(format!("{} ({})", path.display(), e));
-                    false
+                LeafOutcome::StillChanging => {
+                    self.changing_files.insert(leaf.subject());
                 }
+                LeafOutcome::CorruptGzip { problem } => {
+                    self.corrupt_gzips
+                        .insert(format!("{} ({})", leaf.subject(), problem));
+                }
+                LeafOutcome::MemberMissing => {
+                    if let FileRequirement::ArchiveMember { member, .. } = leaf.term() {
+                        self.missing_members
+                            .insert(format!("{} (in {})", member, leaf.subject()));
+                    }
+                }
+                LeafOutcome::Cancelled => {
+                    self.cancelled.insert(leaf.subject());
+                }
+                LeafOutcome::Unresolved => {
+                    self.unresolved.insert(leaf.subject());
+                }
+                LeafOutcome::Present
+                | LeafOutcome::OverwriteAllowed
+                | LeafOutcome::RecommendedMissing
+                | LeafOutcome::RecommendedUnreadable { .. }
+                | LeafOutcome::Skipped => {}
             },
-            FileRequirement::All(children) => {
-                let mut all_ok = true;
-                for child in children {
-                    if !child.evaluate(ctx) {
-                        all_ok = false;
+            NodeReport::Group(group) => match group.kind() {
+                GroupKind::All => {
+                    for child in group.children() {
+                        self.collect(child);
                     }
                 }
-                all_ok
-            }
-            FileRequirement::Any(children) => {
-                let mut branch_contexts = Vec::with_capacity(children.len());
-                for child in children {
-                    let mut branch_ctx = CheckContext::default();
-                    if child.evaluate(&mut branch_ctx) {
-                        return true;
+                GroupKind::Any => {
+                    for child in group.children() {
+                        self.collect(child);
                     }
-                    branch_contexts.push(branch_ctx);
+                    self.unsatisfied_disjunction(node, group);
                 }
-                for branch_ctx in branch_contexts {
-                    ctx.merge(branch_ctx);
+                GroupKind::ExactlyOne => {
+                    let satisfied: Vec<String> = group
+                        .children()
+                        .iter()
+                        .filter(|child| child.is_satisfied())
+                        .map(std::string::ToString::to_string)
+                        .collect();
+                    if satisfied.is_empty() {
+                        for child in group.children() {
+                            self.collect(child);
+                        }
+                        self.unsatisfied_disjunction(node, group);
+                    } else {
+                        self.conflicting_groups.insert(format!(
+                            "{} (satisfied: {})",
+                            node,
+                            satisfied.join(", ")
+                        ));
+                    }
                 }
-                ctx.unsatisfied_disjunctions.insert(self.to_string());
-                false
-            }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Age, FileConstraints, FileRequirementBuildError, FileRequirementBuilder, GroupKind,
+        LeafOutcome, Magic, NodeReport, Size,
+    };
+    use std::fs;
+    use std::path::PathBuf;
+    use std::time::{Duration, SystemTime};
+    use tempfile::tempdir;
+
+    #[test]
+    fn builder_rejects_duplicate_term_across_nested_groups() {
+        let mut b = FileRequirementBuilder::new();
+        let err = match b.require_any(|any| {
+            any.require_file("a.txt")?;
+            any.require_all(|all| {
+                all.require_file("b.txt")?;
+                all.require_file("a.txt")?;
+                Ok(())
+            })?;
+            Ok(())
+        }) {
+            Ok(_) => panic!("expected duplicate file insertion to fail"),
+            Err(err) => err,
+        };
+        assert!(matches!(
+            err,
+            FileRequirementBuildError::DuplicateFile { .. }
+        ));
+    }
+
+    #[test]
+    fn checker_succeeds_when_or_clause_satisfied_by_compound_branch() {
+        let td = tempdir().unwrap();
+        let base = td.path().join("idx");
+        fs::write(base.with_extension("ctab"), "").unwrap();
+        fs::write(base.with_extension("ssi"), "").unwrap();
+        fs::write(base.with_extension("ssi.mphf"), "").unwrap();
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_file(base.with_extension("ctab")).unwrap();
+        b.require_any(|any| {
+            any.require_file(base.with_extension("sshash"))?;
+            any.require_all(|all| {
+                all.require_file(base.with_extension("ssi"))?;
+                all.require_file(base.with_extension("ssi.mphf"))?;
+                Ok(())
+            })?;
+            Ok(())
+        })
+        .unwrap();
+        let req = b.build();
+        assert!(req.check().is_ok());
+    }
+
+    #[test]
+    fn checker_fails_when_no_or_branch_is_satisfied() {
+        let td = tempdir().unwrap();
+        let base = td.path().join("idx");
+        fs::write(base.with_extension("ctab"), "").unwrap();
+        fs::write(base.with_extension("ssi"), "").unwrap();
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_file(base.with_extension("ctab")).unwrap();
+        b.require_any(|any| {
+            any.require_file(base.with_extension("sshash"))?;
+            any.require_all(|all| {
+                all.require_file(base.with_extension("ssi"))?;
+                all.require_file(base.with_extension("ssi.mphf"))?;
+                Ok(())
+            })?;
+            Ok(())
+        })
+        .unwrap();
+        let req = b.build();
+        let err = req.check().expect_err("expected OR clause to fail");
+        let rendered = format!("{:#}", err);
+        assert!(rendered.contains("unsatisfied disjunction"));
+        assert!(rendered.contains("sshash"));
+        assert!(rendered.contains("ssi.mphf"));
+    }
+
+    #[test]
+    fn exactly_one_fails_when_multiple_branches_are_satisfied() {
+        let td = tempdir().unwrap();
+        let base = td.path().join("idx");
+        fs::write(base.with_extension("sshash"), "").unwrap();
+        fs::write(base.with_extension("sshash.gz"), "").unwrap();
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_exactly_one(|one| {
+            one.require_file(base.with_extension("sshash"))?;
+            one.require_file(base.with_extension("sshash.gz"))?;
+            Ok(())
+        })
+        .unwrap();
+        let req = b.build();
+        let err = req.check().expect_err("expected XOR clause to fail");
+        let rendered = err.to_string();
+        assert!(rendered.contains("conflicting exclusive group"));
+        assert!(rendered.contains("satisfied: "));
+        assert!(rendered.contains("sshash.gz"));
+
+        fs::remove_file(base.with_extension("sshash.gz")).unwrap();
+        assert!(req.check().is_ok());
+
+        fs::remove_file(base.with_extension("sshash")).unwrap();
+        let err = req.check().expect_err("expected empty XOR clause to fail");
+        assert!(err.to_string().contains("unsatisfied disjunction"));
+    }
+
+    #[test]
+    fn check_report_exposes_leaf_outcomes_and_group_satisfaction() {
+        let td = tempdir().unwrap();
+        let base = td.path().join("idx");
+        fs::write(base.with_extension("ctab"), "").unwrap();
+        fs::write(base.with_extension("sshash"), "").unwrap();
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_file(base.with_extension("ctab")).unwrap();
+        b.require_file(base.with_extension("refinfo")).unwrap();
+        b.require_any(|any| {
+            any.require_file(base.with_extension("sshash"))?;
+            any.require_file(base.with_extension("ssi"))?;
+            Ok(())
+        })
+        .unwrap();
+        let report = b.build().check_report();
+
+        assert!(!report.is_satisfied());
+        let outcomes: Vec<_> = report
+            .leaves()
+            .iter()
+            .map(|l| l.outcome().clone())
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                LeafOutcome::Present,
+                LeafOutcome::Missing,
+                LeafOutcome::Present,
+                LeafOutcome::Skipped,
+            ]
+        );
+        let NodeReport::Group(root) = report.root() else {
+            panic!("expected root group");
+        };
+        let NodeReport::Group(any) = &root.children()[2] else {
+            panic!("expected nested group");
+        };
+        assert_eq!(any.kind(), GroupKind::Any);
+        assert!(any.is_satisfied());
+    }
+
+    #[test]
+    fn shared_terms_are_allowed_only_across_branches() {
+        let branches = |g: &mut super::GroupBuilder<'_>| {
+            g.require_all(|g| {
+                g.require_files(["genome.fa", "idx.sshash"])?;
+                Ok(())
+            })?;
+            g.require_all(|g| {
+                g.require_files(["genome.fa", "idx.ssi"])?;
+                Ok(())
+            })?;
+            Ok(())
+        };
+        let mut b = FileRequirementBuilder::new();
+        assert!(b.require_any(branches).is_err());
+
+        let mut b = FileRequirementBuilder::new();
+        b.allow_shared_terms()
+            .require_file("reads.fq")
+            .unwrap()
+            .require_exactly_one(branches)
+            .unwrap();
+        assert_eq!(
+            b.build().to_string(),
+            "(reads.fq AND ((genome.fa AND idx.sshash) XOR (genome.fa AND idx.ssi)))"
+        );
+
+        let mut b = FileRequirementBuilder::new();
+        b.allow_shared_terms();
+        b.require_file("genome.fa").unwrap();
+        assert!(b.require_any(branches).is_err());
+
+        let mut b = FileRequirementBuilder::new();
+        b.allow_shared_terms();
+        b.require_any(branches).unwrap();
+        assert!(b.require_file("idx.ssi").is_err());
+        let err = b
+            .require_any(|g| {
+                g.require_file("a.txt")?;
+                g.require_file("a.txt")?;
+                Ok(())
+            })
+            .map(|_| ())
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            FileRequirementBuildError::DuplicateFile { path } if path == "a.txt"
+        ));
+    }
+
+    #[test]
+    fn collected_errors_are_reported_together() {
+        let mut b = FileRequirementBuilder::new();
+        b.collect_errors()
+            .require_files(["idx.ctab", "idx.ssi", "idx.ctab"])
+            .unwrap()
+            .require_glob("reads/[*.fq", 1)
+            .unwrap()
+            .require_any(|any| {
+                any.require_file("idx.ssi")?;
+                any.require_all(|_| Ok(()))?;
+                any.require_file("idx.sshash")?;
+                Ok(())
+            })
+            .unwrap();
+        let errors = b.try_build().unwrap_err();
+        assert!(matches!(
+            &errors[..],
+            [
+                FileRequirementBuildError::DuplicateFile { path: first },
+                FileRequirementBuildError::InvalidGlob { .. },
+                FileRequirementBuildError::DuplicateFile { path: second },
+                FileRequirementBuildError::EmptyGroup { group: "AND" },
+            ] if first == "idx.ctab" && second == "idx.ssi"
+        ));
+
+        let mut b = FileRequirementBuilder::new();
+        b.collect_errors().require_file("idx.ctab").unwrap();
+        assert_eq!(b.try_build().unwrap().to_string(), "(idx.ctab)");
+
+        let mut b = FileRequirementBuilder::new();
+        b.collect_errors()
+            .require_file("idx.ctab")
+            .unwrap()
+            .require_any_named("index format", |_| Ok(()))
+            .unwrap()
+            .require_glob("[", 1)
+            .unwrap()
+            .with_hint("rebuild the index");
+        assert_eq!(b.build().to_string(), "(idx.ctab)");
+    }
+
+    #[test]
+    fn bulk_file_terms_report_the_repeated_path() {
+        let mut b = FileRequirementBuilder::new();
+        b.require_files(["idx.ctab", "idx.ssi"]).unwrap();
+        b.require_any(|any| {
+            any.require_files(vec![PathBuf::from("r1.fq"), PathBuf::from("r2.fq")])?;
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(
+            b.build().to_string(),
+            "(idx.ctab AND idx.ssi AND (r1.fq OR r2.fq))"
+        );
+
+        let mut b = FileRequirementBuilder::new();
+        let err = b
+            .require_files(["a.txt", "b.txt", "a.txt"])
+            .map(|_| ())
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            FileRequirementBuildError::DuplicateFile { path } if path == "a.txt"
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trips_nested_expression() {
+        let mut b = FileRequirementBuilder::new();
+        b.require_file("idx.ctab").unwrap();
+        b.require_any(|any| {
+            any.require_file("idx.sshash")?;
+            any.require_exactly_one(|one| {
+                one.require_file("idx.ssi")?;
+                one.require_file("idx.ssi.gz")?;
+                Ok(())
+            })?;
+            Ok(())
+        })
+        .unwrap();
+        let req = b.build();
+
+        let json = serde_json::to_string(&req).unwrap();
+        assert_eq!(
+            json,
+            r#"{"all":[{"file":"idx.ctab"},{"any":[{"file":"idx.sshash"},{"exactly_one":[{"file":"idx.ssi"},{"file":"idx.ssi.gz"}]}]}]}"#
+        );
+        let parsed: super::FileRequirement = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, req);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn constraint_specs_reject_unknown_and_disabled_keys() {
+        let parse = |constraints: &str| {
+            serde_json::from_str::<super::FileRequirement>(&format!(
+                r#"{{"file_with": {{"path": "reads.fq", "constraints": {}}}}}"#,
+                constraints
+            ))
+        };
+        assert!(parse(r#"{"regular_file": true}"#).is_ok());
+        let err = parse(r#"{"regular_fiel": true}"#).unwrap_err();
+        assert!(err.to_string().contains("unknown field `regular_fiel`"));
+
+        let gzip = parse(r#"{"intact_gzip": true}"#);
+        if cfg!(feature = "gzip") {
+            assert!(gzip.is_ok());
+        } else {
+            let err = gzip.unwrap_err().to_string();
+            assert!(err.contains("the `intact_gzip` constraint requires the `gzip` feature"));
+        }
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn json_schema_lists_each_term_kind_by_its_serialized_key() {
+        let schema = serde_json::to_value(schemars::schema_for!(super::FileRequirement)).unwrap();
+        let keys: Vec<&str> = schema["oneOf"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|variant| variant["required"][0].as_str())
+            .collect();
+        for key in ["file", "file_with", "glob", "all", "any", "exactly_one"] {
+            assert!(keys.contains(&key), "{} missing from {:?}", key, keys);
         }
+        let constraints = &schema["$defs"]["FileConstraints"];
+        assert!(constraints["properties"]["size"].is_object());
+        // Config linters must flag misspelled constraint keys.
+        assert_eq!(constraints["additionalProperties"], false);
+        #[cfg(not(feature = "gzip"))]
+        assert!(constraints["properties"]["intact_gzip"].is_null());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn check_reports_serialize_failures_for_machines() {
+        let mut b = FileRequirementBuilder::new();
+        b.require_file("idx.ctab").unwrap();
+        b.require_any_named("index format", |any| {
+            any.require_file("idx.sshash")?;
+            any.require_file("idx.ssi")?;
+            Ok(())
+        })
+        .unwrap();
+        let report =
+            b.build()
+                .check_report_with_provider(&crate::provider::PredicateProvider::new(
+                    |path: &std::path::Path| {
+                        if path.ends_with("idx.ssi") {
+                            Err(std::io::Error::from(std::io::ErrorKind::PermissionDenied))
+                        } else {
+                            Ok(false)
+                        }
+                    },
+                ));
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["satisfied"], false);
+        assert_eq!(
+            json["missing_files"],
+            serde_json::json!(["idx.ctab", "idx.sshash"])
+        );
+        assert_eq!(json["io_errors"][0]["path"], "idx.ssi");
+        assert_eq!(json["io_errors"][0]["kind"], "PermissionDenied");
+        assert_eq!(
+            json["unsatisfied_groups"],
+            serde_json::json!(["index format"])
+        );
+        assert_eq!(json["findings"][0]["severity"], "error");
+        assert_eq!(json["root"]["group"]["kind"], "all");
+        assert_eq!(
+            json["root"]["group"]["children"][0]["leaf"]["outcome"],
+            "missing"
+        );
+    }
+
+    #[test]
+    fn check_with_reuses_aggregation_for_custom_predicates() {
+        let mut b = FileRequirementBuilder::new();
+        b.require_file("catalog/idx.ctab").unwrap();
+        b.require_any(|any| {
+            any.require_file("catalog/idx.sshash")?;
+            any.require_file("catalog/idx.ssi")?;
+            Ok(())
+        })
+        .unwrap();
+        let req = b.build();
+
+        let mut queried = Vec::new();
+        let err = req
+            .check_with(|path| {
+                queried.push(path.to_path_buf());
+                if path.ends_with("idx.ssi") {
+                    Err(std::io::Error::other("catalog unavailable"))
+                } else {
+                    Ok(path.ends_with("idx.ctab"))
+                }
+            })
+            .unwrap_err();
+        assert_eq!(queried.len(), 3);
+        let rendered = err.to_string();
+        assert!(rendered.contains("missing files: catalog/idx.sshash"));
+        assert!(rendered.contains("catalog/idx.ssi (catalog unavailable)"));
+    }
+
+    #[test]
+    fn glob_term_reports_match_count_when_below_minimum() {
+        let td = tempdir().unwrap();
+        let reads = td.path().join("reads");
+        fs::create_dir(&reads).unwrap();
+        fs::write(reads.join("s1_R1.fastq.gz"), "").unwrap();
+        let pattern = format!("{}/*.fastq.gz", reads.display());
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_glob(pattern.clone(), 1).unwrap();
+        assert!(b.build().check().is_ok());
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_glob(pattern.clone(), 2).unwrap();
+        let err = b.build().check().unwrap_err();
+        assert!(err.to_string().contains(&format!(
+            "unmatched globs: {} (found 1, need at least 2)",
+            pattern
+        )));
+
+        let mut b = FileRequirementBuilder::new();
+        assert!(matches!(
+            b.require_glob("reads/[.fastq", 1),
+            Err(FileRequirementBuildError::InvalidGlob { .. })
+        ));
+    }
+
+    #[test]
+    fn glob_size_terms_sum_the_matching_files() {
+        let td = tempdir().unwrap();
+        fs::write(td.path().join("a_R1.fastq"), [0u8; 40]).unwrap();
+        fs::write(td.path().join("b_R1.fastq"), [0u8; 20]).unwrap();
+        fs::write(td.path().join("notes.txt"), [0u8; 100]).unwrap();
+        let pattern = format!(
+            "{}/*.fastq",
+            glob::Pattern::escape(&td.path().to_string_lossy())
+        );
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_glob_total_size(pattern.clone(), 60).unwrap();
+        let req = b.build();
+        assert!(req.check().is_ok());
+        assert_eq!(
+            req.to_string().parse::<super::FileRequirement>().unwrap(),
+            req
+        );
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_glob_total_size(pattern.clone(), 1 << 30).unwrap();
+        let err = b.build().check().unwrap_err();
+        assert!(err.to_string().contains(&format!(
+            "globs below their total size: {} (60 byte(s) in total, need at least 1073741824)",
+            pattern
+        )));
+    }
+
+    #[test]
+    fn dir_terms_distinguish_regular_files_and_empty_directories() {
+        let td = tempdir().unwrap();
+        let out = td.path().join("out");
+        let staging = td.path().join("staging");
+        let not_a_dir = td.path().join("logs");
+        fs::create_dir(&out).unwrap();
+        fs::create_dir(&staging).unwrap();
+        fs::write(&not_a_dir, "").unwrap();
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_dir(&out).unwrap();
+        b.require_non_empty_dir(&staging).unwrap();
+        b.require_dir(&not_a_dir).unwrap();
+        let req = b.build();
+
+        let rendered = req.check().unwrap_err().to_string();
+        assert!(rendered.contains(&format!(
+            "exists but is not a directory: {}",
+            not_a_dir.display()
+        )));
+        assert!(rendered.contains(&format!("empty directories: {}", staging.display())));
+        assert!(!rendered.contains(&out.display().to_string()));
+
+        fs::write(staging.join("chunk_0"), "").unwrap();
+        fs::remove_file(&not_a_dir).unwrap();
+        fs::create_dir(&not_a_dir).unwrap();
+        assert!(req.check().is_ok());
+    }
+
+    #[test]
+    fn dir_entry_terms_report_the_matching_count() {
+        let td = tempdir().unwrap();
+        let barcodes = td.path().join("barcodes");
+        fs::create_dir(&barcodes).unwrap();
+        fs::write(barcodes.join("whitelist.txt"), "").unwrap();
+        fs::write(barcodes.join("README.md"), "").unwrap();
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_dir_entries_matching(&barcodes, "*.txt", 2)
+            .unwrap();
+        let req = b.build();
+        assert_eq!(
+            req.to_string().parse::<super::FileRequirement>().unwrap(),
+            req
+        );
+        let rendered = req.check().unwrap_err().to_string();
+        assert!(rendered.contains(&format!(
+            "too few directory entries: {} (found 1 `*.txt` entries, need at least 2)",
+            barcodes.display()
+        )));
+
+        fs::write(barcodes.join("extra.txt"), "").unwrap();
+        assert!(req.check().is_ok());
+        let mut b = FileRequirementBuilder::new();
+        b.require_dir_entries(&barcodes, 4).unwrap();
+        assert!(b.build().check().is_err());
+    }
+
+    #[test]
+    fn regular_file_terms_reject_directories() {
+        let td = tempdir().unwrap();
+        let index = td.path().join("idx.ctab");
+        fs::create_dir(&index).unwrap();
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_file(&index).unwrap();
+        assert!(b.build().check().is_ok());
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_regular_file(&index).unwrap();
+        let req = b.build();
+        let rendered = req.check().unwrap_err().to_string();
+        assert!(rendered.contains(&format!(
+            "exists but is not a regular file: {} (is a directory)",
+            index.display()
+        )));
+
+        fs::remove_dir(&index).unwrap();
+        assert!(
+            req.check()
+                .unwrap_err()
+                .to_string()
+                .contains("missing files")
+        );
+        fs::write(&index, "").unwrap();
+        assert!(req.check().is_ok());
     }
-}
 
-impl std::fmt::Display for FileRequirement {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            FileRequirement::File(path) => write!(f, "{}", path.display()),
-            FileRequirement::All(children) => {
-                let joined = children
-                    .iter()
-                    .map(std::string::ToString::to_string)
-                    .collect::<Vec<_>>()
-                    .join(" AND ");
-                write!(f, "({})", joined)
-            }
-            FileRequirement::Any(children) => {
-                let joined = children
-                    .iter()
-                    .map(std::string::ToString::to_string)
-                    .collect::<Vec<_>>()
-                    .join(" OR ");
-                write!(f, "({})", joined)
-            }
-        }
+    #[test]
+    fn size_constraints_report_actual_size() {
+        let td = tempdir().unwrap();
+        let base = td.path().join("idx");
+        fs::write(base.with_extension("ctab"), "").unwrap();
+        fs::write(base.with_extension("refinfo"), "0123456789").unwrap();
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_file_with(base.with_extension("ctab"), Size::AtLeast(1))
+            .unwrap();
+        b.require_file_with(
+            base.with_extension("refinfo"),
+            FileConstraints::new()
+                .regular_file()
+                .size(Size::Between(1, 8)),
+        )
+        .unwrap();
+        let rendered = b.build().check().unwrap_err().to_string();
+        assert!(rendered.contains(&format!(
+            "{} (0 byte(s), expected at least 1 byte(s))",
+            base.with_extension("ctab").display()
+        )));
+        assert!(rendered.contains(&format!(
+            "{} (10 byte(s), expected between 1 and 8 byte(s))",
+            base.with_extension("refinfo").display()
+        )));
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_file_with(base.with_extension("refinfo"), Size::AtMost(10))
+            .unwrap();
+        assert!(b.build().check().is_ok());
     }
-}
 
-#[derive(Default)]
-struct CheckContext {
-    missing_files: BTreeSet<String>,
-    io_errors: BTreeSet<String>,
-    unsatisfied_disjunctions: BTreeSet<String>,
-}
+    #[test]
+    fn age_constraints_report_actual_mtime() {
+        let td = tempdir().unwrap();
+        let cache = td.path().join("cache.bin");
+        fs::write(&cache, "").unwrap();
+        let two_hours_ago = SystemTime::now() - Duration::from_secs(7200);
+        fs::File::options()
+            .write(true)
+            .open(&cache)
+            .unwrap()
+            .set_modified(two_hours_ago)
+            .unwrap();
 
-impl CheckContext {
-    fn merge(&mut self, other: CheckContext) {
-        self.missing_files.extend(other.missing_files);
-        self.io_errors.extend(other.io_errors);
-        self.unsatisfied_disjunctions
-            .extend(other.unsatisfied_disjunctions);
+        let mut b = FileRequirementBuilder::new();
+        b.require_file_newer_than(&cache, Duration::from_secs(3600))
+            .unwrap();
+        let rendered = b.build().check().unwrap_err().to_string();
+        assert!(rendered.contains(&format!(
+            "age constraint violated: {} (modified {}, expected modified within 3600s)",
+            cache.display(),
+            super::constraints::DisplayTime(fs::metadata(&cache).unwrap().modified().unwrap())
+        )));
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_file_with(&cache, Age::AtLeast(Duration::from_secs(60)))
+            .unwrap();
+        assert!(b.build().check().is_ok());
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::{FileRequirementBuildError, FileRequirementBuilder};
-    use std::fs;
-    use tempfile::tempdir;
+    #[test]
+    fn display_time_renders_rfc3339_utc() {
+        let t = std::time::UNIX_EPOCH + Duration::from_secs(1_714_564_800);
+        assert_eq!(
+            super::constraints::DisplayTime(t).to_string(),
+            "2024-05-01T12:00:00Z"
+        );
+    }
 
     #[test]
-    fn builder_rejects_duplicate_term_across_nested_groups() {
-        let mut b = FileRequirementBuilder::new();
-        let err = match b.require_any(|any| {
-            any.require_file("a.txt")?;
-            any.require_all(|all| {
-                all.require_file("b.txt")?;
-                all.require_file("a.txt")?;
-                Ok(())
-            })?;
-            Ok(())
-        }) {
-            Ok(_) => panic!("expected duplicate file insertion to fail"),
-            Err(err) => err,
+    fn newer_than_constraint_reports_stale_outputs() {
+        let td = tempdir().unwrap();
+        let fasta = td.path().join("ref.fa");
+        let index = td.path().join("ref.idx");
+        fs::write(&index, "").unwrap();
+        fs::write(&fasta, "").unwrap();
+        let set_mtime = |path: &std::path::Path, secs_ago: u64| {
+            fs::File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(SystemTime::now() - Duration::from_secs(secs_ago))
+                .unwrap();
         };
+        set_mtime(&index, 600);
+        set_mtime(&fasta, 60);
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_file_with(&index, FileConstraints::new().newer_than(&fasta))
+            .unwrap();
+        let req = b.build();
+        let rendered = req.check().unwrap_err().to_string();
+        assert!(rendered.contains(&format!("stale files: {} (modified ", index.display())));
+        assert!(rendered.contains(&format!("but {} was modified", fasta.display())));
+
+        set_mtime(&index, 0);
+        assert!(req.check().is_ok());
+
+        fs::remove_file(&fasta).unwrap();
+        assert!(req.check().unwrap_err().to_string().contains("dependency"));
+    }
+
+    #[test]
+    fn magic_constraints_sniff_leading_bytes() {
+        let td = tempdir().unwrap();
+        let bam = td.path().join("reads.bam");
+        let cram = td.path().join("reads.cram");
+        fs::write(&bam, [0x1f, 0x8b, 0x08, 0x00, 0x00]).unwrap();
+        fs::write(&cram, b"CRAM\x03\x00").unwrap();
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_file_with_magic(&bam, Magic::Bam).unwrap();
+        b.require_file_with_magic(&cram, &[0x43, 0x52, 0x41, 0x4d])
+            .unwrap();
+        let req = b.build();
+        assert_eq!(
+            req.check().unwrap_err().to_string(),
+            format!(
+                "Required input files were missing or incomplete (unexpected file format: {} (expected BAM, found bytes 1f8b0800))",
+                bam.display()
+            )
+        );
+
+        let parsed: super::FileRequirement = req.to_string().parse().unwrap();
+        assert_eq!(parsed, req);
+
+        fs::write(&bam, [0x1f, 0x8b, 0x08, 0x04, 0x00]).unwrap();
+        assert!(req.check().is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn permission_constraints_report_missing_access() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let td = tempdir().unwrap();
+        let tool = td.path().join("salmon");
+        fs::write(&tool, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&tool, fs::Permissions::from_mode(0o644)).unwrap();
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_file_with(&tool, FileConstraints::new().readable().executable())
+            .unwrap();
+        let req = b.build();
+        assert_eq!(
+            req.check().unwrap_err().to_string(),
+            format!(
+                "Required input files were missing or incomplete (exists but is not executable: {})",
+                tool.display()
+            )
+        );
+        assert_eq!(
+            req.to_string(),
+            format!("(file({}, readable, executable))", tool.display())
+        );
+
+        fs::set_permissions(&tool, fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(req.check().is_ok());
+    }
+
+    #[test]
+    fn builder_base_dir_joins_relative_terms() {
+        let mut b = FileRequirementBuilder::new();
+        b.base_dir("/data/idx");
+        b.require_file("idx.ctab").unwrap();
+        b.require_file("/opt/ref.fa").unwrap();
+        b.require_glob("reads/*.fq", 1).unwrap();
+        assert_eq!(
+            b.build().to_string(),
+            r#"(/data/idx/idx.ctab AND /opt/ref.fa AND glob("/data/idx/reads/*.fq", 1))"#
+        );
+    }
+
+    #[test]
+    fn builder_base_dir_detects_duplicates_after_rebasing() {
+        let mut b = FileRequirementBuilder::new();
+        b.base_dir("/data/idx");
+        b.require_file("idx.ctab").unwrap();
+        b.require_executable("samtools").unwrap();
         assert!(matches!(
-            err,
-            FileRequirementBuildError::DuplicateFile { .. }
+            b.require_file("/data/idx/idx.ctab"),
+            Err(FileRequirementBuildError::DuplicateFile { path }) if path == "/data/idx/idx.ctab"
         ));
+        b.require_file("/data/idx/samtools").unwrap();
+        assert_eq!(
+            b.build().to_string(),
+            "(/data/idx/idx.ctab AND executable(samtools) AND /data/idx/samtools)"
+        );
     }
 
     #[test]
-    fn checker_succeeds_when_or_clause_satisfied_by_compound_branch() {
+    fn named_terms_appear_in_messages_and_round_trip() {
+        let mut b = FileRequirementBuilder::new();
+        b.require_file_named("transcriptome index", "idx.sshash")
+            .unwrap();
+        b.require_file("idx.ctab").unwrap();
+        let req = b.build();
+        assert_eq!(
+            req.to_string(),
+            r#"(named("transcriptome index", idx.sshash) AND idx.ctab)"#
+        );
+        assert_eq!(
+            req.to_string().parse::<super::FileRequirement>().unwrap(),
+            req
+        );
+
         let td = tempdir().unwrap();
-        let base = td.path().join("idx");
-        fs::write(base.with_extension("ctab"), "").unwrap();
-        fs::write(base.with_extension("ssi"), "").unwrap();
-        fs::write(base.with_extension("ssi.mphf"), "").unwrap();
+        assert_eq!(
+            req.check_in(td.path()).unwrap_err().to_string(),
+            format!(
+                "Required input files were missing or incomplete (missing files: {}, transcriptome index ({}))",
+                td.path().join("idx.ctab").display(),
+                td.path().join("idx.sshash").display()
+            )
+        );
+    }
+
+    #[test]
+    fn hints_of_failing_parts_are_listed_after_the_message() {
+        let td = tempdir().unwrap();
+        fs::write(td.path().join("idx.ssi"), "").unwrap();
 
         let mut b = FileRequirementBuilder::new();
-        b.require_file(base.with_extension("ctab")).unwrap();
+        b.require_file(td.path().join("idx.ctab"))
+            .unwrap()
+            .with_hint("run `salmon index` to generate this file");
+        b.require_file(td.path().join("idx.ssi"))
+            .unwrap()
+            .with_hint("never shown");
         b.require_any(|any| {
-            any.require_file(base.with_extension("sshash"))?;
+            any.require_file(td.path().join("idx.sshash"))?
+                .with_hint("rebuild the index with --sshash");
+            any.require_file(td.path().join("idx.mphf"))?;
+            Ok(())
+        })
+        .unwrap()
+        .with_hint("upgrade salmon to read this index");
+        let req = b.build();
+        assert_eq!(
+            req.to_string().parse::<super::FileRequirement>().unwrap(),
+            req
+        );
+
+        let message = req.check().unwrap_err().to_string();
+        let hints: Vec<&str> = message.lines().skip(1).collect();
+        assert_eq!(
+            hints,
+            [
+                "hint: run `salmon index` to generate this file",
+                "hint: upgrade salmon to read this index",
+                "hint: rebuild the index with --sshash",
+            ]
+        );
+    }
+
+    #[test]
+    fn named_disjunctions_list_their_alternatives() {
+        let mut b = FileRequirementBuilder::new();
+        b.require_any_named("index format", |any| {
+            any.require_file_named("sshash", "idx.sshash")?;
             any.require_all(|all| {
-                all.require_file(base.with_extension("ssi"))?;
-                all.require_file(base.with_extension("ssi.mphf"))?;
+                all.require_file("idx.ssi")?;
+                all.require_file("idx.ssi.mphf")?;
                 Ok(())
             })?;
             Ok(())
         })
         .unwrap();
         let req = b.build();
-        assert!(req.check().is_ok());
+        assert_eq!(
+            req.to_string().parse::<super::FileRequirement>().unwrap(),
+            req
+        );
+
+        let td = tempdir().unwrap();
+        let message = req.check_in(td.path()).unwrap_err().to_string();
+        assert!(
+            message.ends_with(&format!(
+                "; no valid index format found: tried 'sshash', '{}+{}')",
+                td.path().join("idx.ssi").display(),
+                td.path().join("idx.ssi.mphf").display()
+            )),
+            "{}",
+            message
+        );
+        assert!(!message.contains("unsatisfied disjunction"));
     }
 
     #[test]
-    fn checker_fails_when_no_or_branch_is_satisfied() {
+    fn recommended_files_only_produce_warnings() {
         let td = tempdir().unwrap();
-        let base = td.path().join("idx");
-        fs::write(base.with_extension("ctab"), "").unwrap();
-        fs::write(base.with_extension("ssi"), "").unwrap();
+        fs::write(td.path().join("idx.ctab"), "").unwrap();
 
         let mut b = FileRequirementBuilder::new();
-        b.require_file(base.with_extension("ctab")).unwrap();
+        b.require_file("idx.ctab").unwrap();
+        b.recommend_file("decoys.txt")
+            .unwrap()
+            .with_hint("results may be less accurate");
+        let req = b.build();
+        assert_eq!(
+            req.to_string(),
+            r#"(idx.ctab AND hint("results may be less accurate", recommend(decoys.txt)))"#
+        );
+        assert_eq!(
+            req.to_string().parse::<super::FileRequirement>().unwrap(),
+            req
+        );
+
+        assert!(req.check_in(td.path()).is_ok());
+        let report = req.rebased(td.path()).check_report();
+        assert_eq!(
+            report.warnings(),
+            [format!(
+                "recommended file not found: {}; results may be less accurate",
+                td.path().join("decoys.txt").display()
+            )]
+        );
+    }
+
+    #[test]
+    fn reports_record_the_satisfied_alternative() {
+        let td = tempdir().unwrap();
+        fs::write(td.path().join("idx.ssi"), "").unwrap();
+        fs::write(td.path().join("idx.ssi.mphf"), "").unwrap();
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_any_named("index format", |any| {
+            any.require_file_named("sshash", "idx.sshash")?;
+            any.require_all(|all| {
+                all.require_file_named("ssi", "idx.ssi")?;
+                all.require_file_named("mphf", "idx.ssi.mphf")?;
+                Ok(())
+            })?;
+            Ok(())
+        })
+        .unwrap();
+        let report = b.build().rebased(td.path()).check_report();
+
+        let format = report.group("index format").unwrap();
+        assert_eq!(format.satisfied_branch(), Some(1));
+        assert_eq!(format.satisfied_branch_label().as_deref(), Some("ssi+mphf"));
+        assert_eq!(report.groups()[0].satisfied_branch(), None);
+        assert!(report.group("aligner").is_none());
+    }
+
+    #[test]
+    fn minimal_missing_set_keeps_the_cheapest_alternative() {
+        let td = tempdir().unwrap();
+        fs::write(td.path().join("idx.ssi"), "").unwrap();
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_file("idx.ctab").unwrap();
         b.require_any(|any| {
-            any.require_file(base.with_extension("sshash"))?;
             any.require_all(|all| {
-                all.require_file(base.with_extension("ssi"))?;
-                all.require_file(base.with_extension("ssi.mphf"))?;
+                all.require_file("idx.sshash")?;
+                all.require_file("idx.sshash.meta")?;
+                Ok(())
+            })?;
+            any.require_all(|all| {
+                all.require_file("idx.ssi")?;
+                all.require_file("idx.ssi.mphf")?;
                 Ok(())
             })?;
             Ok(())
         })
         .unwrap();
+        let report = b.build().rebased(td.path()).check_report();
+        let fix: Vec<_> = report
+            .minimal_missing_set()
+            .unwrap()
+            .into_iter()
+            .map(|leaf| leaf.path().strip_prefix(td.path()).unwrap().to_path_buf())
+            .collect();
+        assert_eq!(
+            fix,
+            [
+                std::path::Path::new("idx.ctab"),
+                std::path::Path::new("idx.ssi.mphf")
+            ]
+        );
+
+        fs::write(td.path().join("b.txt"), "").unwrap();
+        fs::write(td.path().join("c.txt"), "").unwrap();
+        let mut b = FileRequirementBuilder::new();
+        b.require_exactly_one(|xor| {
+            xor.require_file(td.path().join("b.txt"))?;
+            xor.require_file(td.path().join("c.txt"))?;
+            Ok(())
+        })
+        .unwrap();
+        assert!(b.build().check_report().minimal_missing_set().is_none());
+
+        // Terms skipped by a fail-fast check count as failed.
+        let mut b = FileRequirementBuilder::new();
+        b.require_file(td.path().join("a.txt")).unwrap();
+        b.require_file(td.path().join("b.txt")).unwrap();
+        let report = b
+            .build()
+            .check_report_with_options(&crate::CheckOptions::new().fail_fast(true));
+        let fix = report.minimal_missing_set().unwrap();
+        assert_eq!(fix.len(), 2);
+        assert_eq!(*fix[1].outcome(), LeafOutcome::Skipped);
+    }
+
+    #[test]
+    fn check_errors_expose_structured_failures() {
+        let mut b = FileRequirementBuilder::new();
+        b.require_file("idx.ctab").unwrap();
+        b.require_any(|any| {
+            any.require_file("present.txt")?;
+            any.require_file("absent.txt")?;
+            Ok(())
+        })
+        .unwrap();
+        b.require_any_named("index format", |any| {
+            any.require_file("idx.sshash")?;
+            any.require_file("idx.ssi")?;
+            Ok(())
+        })
+        .unwrap();
+        let err = b
+            .build()
+            .check_with(|path| match path.to_str() {
+                Some("present.txt") => Ok(true),
+                Some("idx.ssi") => Err(std::io::Error::from(std::io::ErrorKind::PermissionDenied)),
+                _ => Ok(false),
+            })
+            .unwrap_err();
+
+        assert_eq!(
+            err.missing_files(),
+            [PathBuf::from("idx.ctab"), PathBuf::from("idx.sshash")]
+        );
+        assert_eq!(err.io_errors().len(), 1);
+        assert_eq!(err.io_errors()[0].0, PathBuf::from("idx.ssi"));
+        assert_eq!(
+            err.io_errors()[0].1.kind(),
+            std::io::ErrorKind::PermissionDenied
+        );
+        assert_eq!(err.unsatisfied_groups(), ["index format"]);
+        let source = std::error::Error::source(&err).unwrap();
+        let io = source.downcast_ref::<std::io::Error>().unwrap();
+        assert_eq!(io.kind(), std::io::ErrorKind::PermissionDenied);
+    }
+
+    #[test]
+    fn stability_constraint_rejects_files_still_growing() {
+        struct Growing(std::cell::Cell<u64>);
+
+        impl crate::FileProvider for Growing {
+            fn try_exists(&self, _: &std::path::Path) -> std::io::Result<bool> {
+                Ok(true)
+            }
+
+            fn metadata(&self, _: &std::path::Path) -> std::io::Result<crate::FileMetadata> {
+                self.0.set(self.0.get() + 1);
+                Ok(crate::FileMetadata::new(crate::FileKind::File).with_len(self.0.get()))
+            }
+        }
+
+        let stable = FileConstraints::new().stable_for(Duration::from_millis(1));
+        let mut b = FileRequirementBuilder::new();
+        b.require_file_with("reads.fq.gz", stable.clone()).unwrap();
         let req = b.build();
-        let err = req.check().expect_err("expected OR clause to fail");
-        let rendered = format!("{:#}", err);
-        assert!(rendered.contains("unsatisfied disjunction"));
-        assert!(rendered.contains("sshash"));
-        assert!(rendered.contains("ssi.mphf"));
+        assert_eq!(req.to_string(), "(file(reads.fq.gz, stable=0.001000000s))");
+        assert_eq!(
+            req.to_string().parse::<super::FileRequirement>().unwrap(),
+            req
+        );
+
+        let report = req.check_report_with_provider(&Growing(std::cell::Cell::new(0)));
+        assert_eq!(report.leaves()[0].outcome(), &LeafOutcome::StillChanging);
+
+        let td = tempdir().unwrap();
+        let done = td.path().join("done.fq.gz");
+        fs::write(&done, "reads").unwrap();
+        let mut b = FileRequirementBuilder::new();
+        b.require_file_with(&done, stable).unwrap();
+        assert!(b.build().check().is_ok());
+    }
+
+    #[test]
+    fn reports_keep_os_error_codes_per_failing_path() {
+        let mut b = FileRequirementBuilder::new();
+        b.require_file("locked.txt").unwrap();
+        b.require_file("flaky.txt").unwrap();
+        let report =
+            b.build()
+                .check_report_with_provider(&crate::provider::PredicateProvider::new(
+                    |path: &std::path::Path| match path.to_str() {
+                        Some("locked.txt") => Err(std::io::Error::from_raw_os_error(13)),
+                        _ => Err(std::io::Error::from(std::io::ErrorKind::Interrupted)),
+                    },
+                ));
+
+        let errors = report.io_errors();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].0, std::path::Path::new("locked.txt"));
+        assert_eq!(errors[0].1.raw_os_error(), Some(13));
+        assert_eq!(errors[1].1.kind(), std::io::ErrorKind::Interrupted);
+        assert_eq!(errors[1].1.raw_os_error(), None);
+    }
+
+    #[test]
+    fn writable_dir_terms_accept_creatable_directories() {
+        let td = tempdir().unwrap();
+        let blocker = td.path().join("blocker");
+        fs::write(&blocker, "").unwrap();
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_writable_dir(td.path()).unwrap();
+        b.require_writable_dir(td.path().join("out/quant")).unwrap();
+        assert!(b.build().check().is_ok());
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_writable_dir(blocker.join("out")).unwrap();
+        b.require_writable_dir(&blocker).unwrap();
+        assert_eq!(
+            b.build().check().unwrap_err().to_string(),
+            format!(
+                "Required input files were missing or incomplete (exists but is not a directory: {}; cannot be created: {} ({} is not a writable directory))",
+                blocker.display(),
+                blocker.join("out").display(),
+                blocker.display()
+            )
+        );
+    }
+
+    #[test]
+    fn extensions_are_appended_to_the_prefix() {
+        let mut b = FileRequirementBuilder::new();
+        b.require_with_extensions("gencode.v44", ["ctab", ".refinfo"])
+            .unwrap();
+        b.require_any_with_extensions("gencode.v44", ["sshash", "ssi"])
+            .unwrap();
+        assert_eq!(
+            b.build().to_string(),
+            "(gencode.v44.ctab AND gencode.v44.refinfo AND \
+             (gencode.v44.sshash OR gencode.v44.ssi))"
+        );
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_with_extensions("idx", ["ctab"]).unwrap();
+        assert!(
+            b.require_any_with_extensions("idx", ["sshash", "ctab"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn url_terms_are_checked_through_the_provider() {
+        struct Mirror;
+
+        impl crate::FileProvider for Mirror {
+            fn try_exists(&self, _: &std::path::Path) -> std::io::Result<bool> {
+                Ok(true)
+            }
+
+            fn url_exists(&self, url: &str) -> std::io::Result<bool> {
+                Ok(url.ends_with("genome.fa.gz"))
+            }
+        }
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_url("https://example.org/ref/genome.fa.gz")
+            .unwrap();
+        b.require_url("https://example.org/ref/genes.gtf").unwrap();
+        let req = b.build();
+        assert_eq!(
+            req.to_string(),
+            r#"(url("https://example.org/ref/genome.fa.gz") AND url("https://example.org/ref/genes.gtf"))"#
+        );
+        assert_eq!(
+            req.to_string().parse::<super::FileRequirement>().unwrap(),
+            req
+        );
+
+        let err = req.check_with_provider(&Mirror).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("URLs not found: https://example.org/ref/genes.gtf")
+        );
+        assert!(err.missing_files().is_empty());
+        assert!(req.paths().is_empty());
+    }
+
+    #[cfg(all(feature = "free-space", unix))]
+    #[test]
+    fn free_space_terms_measure_the_nearest_existing_ancestor() {
+        let td = tempdir().unwrap();
+        let out = td.path().join("not/yet/created");
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_writable_dir(&out).unwrap();
+        b.require_free_space(&out, 1).unwrap();
+        assert!(b.build().check().is_ok());
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_free_space(&out, u64::MAX).unwrap();
+        let err = b.build().check().unwrap_err().to_string();
+        assert!(err.contains(&format!("insufficient free space: {} (", out.display())));
+        assert!(err.contains(&format!("need {})", u64::MAX)));
+    }
+
+    #[test]
+    fn same_filesystem_terms_compare_device_ids() {
+        use super::{FileKind, FileMetadata, FileProvider};
+        use std::io;
+        use std::path::Path;
+
+        /// `/scratch` is a separate mount; `/data/run1/work` does not exist yet.
+        struct Mounts;
+
+        impl FileProvider for Mounts {
+            fn try_exists(&self, path: &Path) -> io::Result<bool> {
+                Ok(["/", "/data", "/data/run1", "/scratch"]
+                    .iter()
+                    .any(|existing| path == Path::new(existing)))
+            }
+
+            fn metadata(&self, path: &Path) -> io::Result<FileMetadata> {
+                let device = if path.starts_with("/scratch") { 2 } else { 1 };
+                Ok(FileMetadata::new(FileKind::Dir).with_device(device))
+            }
+        }
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_same_filesystem("/data/run1", "/data/run1/work")
+            .unwrap();
+        let req = b.build();
+        assert!(req.check_with_provider(&Mounts).is_ok());
+        assert_eq!(
+            req.to_string().parse::<super::FileRequirement>().unwrap(),
+            req
+        );
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_same_filesystem("/data/run1", "/scratch/work")
+            .unwrap();
+        let err = b.build().check_with_provider(&Mounts).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("not on the same filesystem: /data/run1 and /scratch/work")
+        );
+
+        #[cfg(unix)]
+        {
+            let td = tempdir().unwrap();
+            let mut b = FileRequirementBuilder::new();
+            b.require_same_filesystem(td.path(), td.path().join("stage"))
+                .unwrap();
+            assert!(b.build().check().is_ok());
+        }
     }
 }
@@ -0,0 +1,107 @@
+/// Build a requirement from a nested declaration instead of builder closures.
+///
+/// Items are separated by commas: an expression is a file term (anything
+/// [`FileRequirementBuilder::require_file`](crate::FileRequirementBuilder::require_file)
+/// accepts), and `all { .. }`, `any { .. }`, `any("name") { .. }` and
+/// `exactly_one { .. }` are nested groups. The items are added to the root
+/// conjunction in order, with the builder's checks, so the macro evaluates to
+/// a `Result<FileRequirement, FileRequirementBuildError>` that reports
+/// repeated paths and empty groups:
+///
+/// ```
+/// use file_requirements::{file_requirements, FileRequirementBuildError};
+///
+/// let req = file_requirements! {
+///     "idx.ctab",
+///     any("index format") {
+///         "idx.sshash",
+///         all { "idx.ssi", "idx.ssi.mphf" },
+///     },
+/// }
+/// .unwrap();
+/// assert_eq!(
+///     req.to_string(),
+///     r#"(idx.ctab AND named("index format", (idx.sshash OR (idx.ssi AND idx.ssi.mphf))))"#
+/// );
+///
+/// let err = file_requirements! { "idx.ctab", any { "idx.ctab" } }.unwrap_err();
+/// assert!(matches!(err, FileRequirementBuildError::DuplicateFile { .. }));
+/// ```
+///
+/// Malformed declarations fail to compile at the offending token.
+#[macro_export]
+macro_rules! file_requirements {
+    ($($items:tt)*) => {{
+        let mut builder = $crate::FileRequirementBuilder::new();
+        let added: ::core::result::Result<(), $crate::FileRequirementBuildError> = (|| {
+            $crate::__file_requirements_items!(builder; $($items)*);
+            ::core::result::Result::Ok(())
+        })();
+        added.map(|()| builder.build())
+    }};
+}
+
+/// Adds the items of a [`file_requirements!`] declaration to `$builder`, a
+/// `FileRequirementBuilder` or `GroupBuilder`, returning early on errors.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __file_requirements_items {
+    ($builder:ident;) => {
+        let _ = &$builder;
+    };
+    ($builder:ident; all { $($group:tt)* } $(, $($rest:tt)*)?) => {
+        $builder.require_all(|group| {
+            $crate::__file_requirements_items!(group; $($group)*);
+            ::core::result::Result::Ok(())
+        })?;
+        $crate::__file_requirements_items!($builder; $($($rest)*)?);
+    };
+    ($builder:ident; any ($name:expr) { $($group:tt)* } $(, $($rest:tt)*)?) => {
+        $builder.require_any_named($name, |group| {
+            $crate::__file_requirements_items!(group; $($group)*);
+            ::core::result::Result::Ok(())
+        })?;
+        $crate::__file_requirements_items!($builder; $($($rest)*)?);
+    };
+    ($builder:ident; any { $($group:tt)* } $(, $($rest:tt)*)?) => {
+        $builder.require_any(|group| {
+            $crate::__file_requirements_items!(group; $($group)*);
+            ::core::result::Result::Ok(())
+        })?;
+        $crate::__file_requirements_items!($builder; $($($rest)*)?);
+    };
+    ($builder:ident; exactly_one { $($group:tt)* } $(, $($rest:tt)*)?) => {
+        $builder.require_exactly_one(|group| {
+            $crate::__file_requirements_items!(group; $($group)*);
+            ::core::result::Result::Ok(())
+        })?;
+        $crate::__file_requirements_items!($builder; $($($rest)*)?);
+    };
+    ($builder:ident; $path:expr $(, $($rest:tt)*)?) => {
+        $builder.require_file($path)?;
+        $crate::__file_requirements_items!($builder; $($($rest)*)?);
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FileRequirementBuildError;
+
+    #[test]
+    fn declarations_match_builder_calls() {
+        let prefix = std::path::Path::new("idx");
+        let req = file_requirements! {
+            prefix.with_extension("ctab"),
+            exactly_one { "reads.fq", all { "r1.fq", "r2.fq" } }
+        }
+        .unwrap();
+        assert_eq!(
+            req.to_string(),
+            "(idx.ctab AND (reads.fq XOR (r1.fq AND r2.fq)))"
+        );
+        assert!(matches!(
+            file_requirements! { "a.txt", any {} },
+            Err(FileRequirementBuildError::EmptyGroup { group: "OR" })
+        ));
+    }
+}
@@ -0,0 +1,219 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::shards;
+use crate::{
+    CheckReport, FileProvider, FileRequirement, FileRequirementCheckError, GroupKind, LeafOutcome,
+    NodeReport, StdFs,
+};
+
+/// The concrete files and directories that satisfied a requirement, for
+/// provenance records or container mount specs.
+///
+/// Only the alternative that satisfied each `OR` / `XOR` group is included;
+/// glob and shard terms contribute the paths they matched. Paths that must
+/// not exist, output directories, free-space, environment variable,
+/// executable and URL terms contribute nothing. Paths are listed once, in
+/// declaration order.
+///
+/// With the `serde` feature enabled, a manifest serializes as
+/// `{"files": [...], "dirs": [...]}`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Manifest {
+    files: Vec<PathBuf>,
+    dirs: Vec<PathBuf>,
+}
+
+impl Manifest {
+    /// The files used, including matched glob and shard paths and archives
+    /// whose members were required.
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// The directories used.
+    pub fn dirs(&self) -> &[PathBuf] {
+        &self.dirs
+    }
+
+    fn add_file(&mut self, path: &Path) {
+        if !self.files.iter().any(|file| file == path) {
+            self.files.push(path.to_path_buf());
+        }
+    }
+
+    fn add_dir(&mut self, path: &Path) {
+        if !self.dirs.iter().any(|dir| dir == path) {
+            self.dirs.push(path.to_path_buf());
+        }
+    }
+
+    /// Add the paths used by `node`, or return the leaf whose expansion failed.
+    fn collect<'r, P: FileProvider + ?Sized>(
+        &mut self,
+        node: &'r NodeReport,
+        provider: &P,
+    ) -> Result<(), (&'r FileRequirement, io::Error)> {
+        match node {
+            NodeReport::Group(group) => {
+                let used: Vec<&NodeReport> = match group.kind() {
+                    GroupKind::All => group.children().iter().collect(),
+                    GroupKind::Any | GroupKind::ExactlyOne => group
+                        .satisfied_branch()
+                        .map(|index| &group.children()[index])
+                        .into_iter()
+                        .collect(),
+                };
+                for child in used {
+                    self.collect(child, provider)?;
+                }
+            }
+            NodeReport::Leaf(leaf) if *leaf.outcome() == LeafOutcome::Present => self
+                .add_leaf(leaf.term().unwrapped(), provider)
+                .map_err(|e| (leaf.declared_term(), e))?,
+            NodeReport::Leaf(_) => {}
+        }
+        Ok(())
+    }
+
+    fn add_leaf<P: FileProvider + ?Sized>(
+        &mut self,
+        term: &FileRequirement,
+        provider: &P,
+    ) -> io::Result<()> {
+        match term {
+            FileRequirement::File(path)
+            | FileRequirement::FileWith { path, .. }
+            | FileRequirement::Recommended(path)
+            | FileRequirement::ArchiveMember { archive: path, .. } => self.add_file(path),
+            FileRequirement::Dir { path, .. } | FileRequirement::DirEntries { path, .. } => {
+                self.add_dir(path)
+            }
+            FileRequirement::Glob { pattern, .. } | FileRequirement::GlobSize { pattern, .. } => {
+                for path in provider.glob(pattern)? {
+                    match provider.metadata(&path) {
+                        Ok(meta) if meta.is_dir() => self.add_dir(&path),
+                        _ => self.add_file(&path),
+                    }
+                }
+            }
+            FileRequirement::Shards {
+                template,
+                start,
+                end,
+                ..
+            } => {
+                for index in *start..*end {
+                    let path = PathBuf::from(shards::expand(template, index));
+                    if provider.try_exists(&path)? {
+                        self.add_file(&path);
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+impl CheckReport {
+    /// The files and directories that satisfied the checked expression, found
+    /// with `provider` where globs and shards need expanding.
+    ///
+    /// Returns `None` for an unsatisfied report.
+    pub fn manifest_with_provider<P: FileProvider + ?Sized>(
+        &self,
+        provider: &P,
+    ) -> Option<io::Result<Manifest>> {
+        if !self.is_satisfied() {
+            return None;
+        }
+        let mut manifest = Manifest::default();
+        Some(match manifest.collect(self.root(), provider) {
+            Ok(()) => Ok(manifest),
+            Err((_, e)) => Err(e),
+        })
+    }
+}
+
+impl FileRequirement {
+    /// Check the expression against the local filesystem and, if it is
+    /// satisfied, return the [`Manifest`] of files it used.
+    ///
+    /// An IO error while expanding a glob or shard term fails the check with
+    /// that error.
+    pub fn manifest(&self) -> Result<Manifest, FileRequirementCheckError> {
+        self.manifest_with_provider(&StdFs)
+    }
+
+    /// Check the expression with a custom file provider and, if it is
+    /// satisfied, return the [`Manifest`] of files it used.
+    pub fn manifest_with_provider<P: FileProvider>(
+        &self,
+        provider: &P,
+    ) -> Result<Manifest, FileRequirementCheckError> {
+        let mut report = self.check_report_with_provider(provider);
+        if !report.is_satisfied() {
+            return Err(FileRequirementCheckError::from_report(&report));
+        }
+        let mut manifest = Manifest::default();
+        let (failed, e) = match manifest.collect(report.root(), provider) {
+            Ok(()) => return Ok(manifest),
+            Err((failed, e)) => (failed.clone(), e),
+        };
+        for leaf in report.leaves_mut() {
+            if *leaf.declared_term() == failed {
+                leaf.set_outcome(LeafOutcome::from_io_error(e));
+                break;
+            }
+        }
+        Err(FileRequirementCheckError::from_report(&report))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FileRequirementBuilder;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn manifests_list_the_satisfied_alternative_and_glob_matches() {
+        let td = tempdir().unwrap();
+        let p = |name: &str| td.path().join(name);
+        fs::write(p("idx.sshash"), "").unwrap();
+        fs::create_dir(p("reads")).unwrap();
+        fs::write(p("reads/a.fq"), "").unwrap();
+        fs::write(p("reads/b.fq"), "").unwrap();
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_any(|g| {
+            g.require_file(p("idx.ssi"))?;
+            g.require_file(p("idx.sshash"))?;
+            Ok(())
+        })
+        .unwrap();
+        b.require_dir(p("reads")).unwrap();
+        b.require_glob("reads/*.fq", 2).unwrap();
+        b.recommend_file(p("README")).unwrap();
+        b.base_dir(td.path());
+        let req = b.build();
+
+        let manifest = req.manifest().unwrap();
+        assert_eq!(
+            manifest.files(),
+            [p("idx.sshash"), p("reads/a.fq"), p("reads/b.fq")]
+        );
+        assert_eq!(manifest.dirs(), [p("reads")]);
+
+        fs::remove_file(p("idx.sshash")).unwrap();
+        assert!(req.manifest().is_err());
+        assert!(
+            req.check_report()
+                .manifest_with_provider(&crate::StdFs)
+                .is_none()
+        );
+    }
+}
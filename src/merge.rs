@@ -0,0 +1,124 @@
+use thiserror::Error;
+
+use crate::FileRequirement;
+use crate::FileRequirementBuildError;
+use crate::scheme::SeenTerms;
+
+/// Errors produced while merging two requirement trees.
+#[derive(Debug, Error)]
+pub enum MergeError {
+    /// Both trees claim the same path, glob pattern or URL.
+    #[error("`{path}` is required by both trees; each file can appear in at most one clause.")]
+    Conflict { path: String },
+}
+
+impl FileRequirement {
+    /// Combine two trees under a root `AND`, so components of an application
+    /// can each contribute their own requirements.
+    ///
+    /// Unnamed, unhinted `AND` roots, as [`FileRequirementBuilder::build`]
+    /// returns, are flattened into the merged root. A term that both trees
+    /// claim, as the builder would reject within one tree, fails with
+    /// [`MergeError::Conflict`].
+    ///
+    /// ```
+    /// use file_requirements::{FileRequirement, MergeError};
+    ///
+    /// let index: FileRequirement = "(idx.ctab AND (idx.sshash OR idx.ssi))".parse().unwrap();
+    /// let reads: FileRequirement = "(r1.fq AND r2.fq)".parse().unwrap();
+    /// let req = index.clone().merge(reads).unwrap();
+    /// assert_eq!(
+    ///     req.to_string(),
+    ///     "(idx.ctab AND (idx.sshash OR idx.ssi) AND r1.fq AND r2.fq)"
+    /// );
+    ///
+    /// let err = index.merge("(idx.ssi)".parse().unwrap()).unwrap_err();
+    /// assert!(matches!(err, MergeError::Conflict { path } if path == "idx.ssi"));
+    /// ```
+    ///
+    /// [`FileRequirementBuilder::build`]: crate::FileRequirementBuilder::build
+    pub fn merge(self, other: FileRequirement) -> Result<FileRequirement, MergeError> {
+        let mut terms = root_terms(self);
+        terms.extend(root_terms(other));
+        checked(terms)
+    }
+
+    /// Like [`merge`](Self::merge), but a root-level term of `other` equal to
+    /// one of `self` is kept once instead of failing, so components that
+    /// both need the same file can declare it independently.
+    ///
+    /// Only whole root-level terms are compared: a shared path inside an
+    /// `OR` or `XOR` group, or one constrained differently, still conflicts.
+    pub fn merge_deduplicated(self, other: FileRequirement) -> Result<FileRequirement, MergeError> {
+        let mut terms = root_terms(self);
+        for term in root_terms(other) {
+            if !terms.contains(&term) {
+                terms.push(term);
+            }
+        }
+        checked(terms)
+    }
+}
+
+/// The children of an unnamed, unhinted `AND` root, or the tree itself.
+fn root_terms(tree: FileRequirement) -> Vec<FileRequirement> {
+    match tree {
+        FileRequirement::All(children) => children,
+        term => vec![term],
+    }
+}
+
+fn checked(terms: Vec<FileRequirement>) -> Result<FileRequirement, MergeError> {
+    let mut seen = SeenTerms::default();
+    for term in &terms {
+        seen.claim_tree(term).map_err(|err| match err {
+            FileRequirementBuildError::DuplicateFile { path } => MergeError::Conflict { path },
+            other => unreachable!("claiming terms only reports duplicates: {other}"),
+        })?;
+    }
+    Ok(FileRequirement::All(terms))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> FileRequirement {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn conflicts_are_found_in_nested_groups() {
+        let index = parse(r#"(idx.ctab AND named("index", (idx.sshash XOR idx.ssi)))"#);
+        let err = index
+            .clone()
+            .merge(parse("(idx.ssi OR idx.tsv)"))
+            .unwrap_err();
+        assert!(matches!(&err, MergeError::Conflict { path } if path == "idx.ssi"));
+        assert!(err.to_string().contains("required by both trees"));
+
+        let req = index
+            .merge(parse(r#"(reads.fq OR glob("*.fq", 2))"#))
+            .unwrap();
+        assert_eq!(
+            req.to_string(),
+            r#"(idx.ctab AND named("index", (idx.sshash XOR idx.ssi)) AND (reads.fq OR glob("*.fq", 2)))"#
+        );
+    }
+
+    #[test]
+    fn deduplication_keeps_equal_root_terms_once() {
+        let a = parse("(genome.fa AND idx.ctab)");
+        let b = parse("(genome.fa AND r1.fq)");
+        assert!(a.clone().merge(b.clone()).is_err());
+        assert_eq!(
+            a.clone().merge_deduplicated(b).unwrap().to_string(),
+            "(genome.fa AND idx.ctab AND r1.fq)"
+        );
+
+        let err = a
+            .merge_deduplicated(parse("(genome.fa OR genome.fa.gz)"))
+            .unwrap_err();
+        assert!(matches!(err, MergeError::Conflict { path } if path == "genome.fa"));
+    }
+}
@@ -0,0 +1,226 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::{Access, CheckCache, CheckReport, FileMetadata, FileProvider, NodeReport};
+
+/// Counters gathered while checking a requirement expression.
+///
+/// Slow network filesystems show up as a high [`duration`](Self::duration)
+/// relative to [`stat_calls`](Self::stat_calls); the latency of each term is
+/// available from [`LeafReport::latency`](crate::LeafReport::latency).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CheckMetrics {
+    stat_calls: u64,
+    cache_hits: u64,
+    duration: Duration,
+}
+
+impl CheckMetrics {
+    /// Existence and metadata queries that reached the file provider.
+    pub fn stat_calls(&self) -> u64 {
+        self.stat_calls
+    }
+
+    /// Existence and metadata queries answered from an earlier query of the
+    /// same path during the check.
+    pub fn cache_hits(&self) -> u64 {
+        self.cache_hits
+    }
+
+    /// Wall-clock time taken by the whole check.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+}
+
+/// Run `f`, returning its result and how long it took.
+pub(crate) fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let value = f();
+    (value, start.elapsed())
+}
+
+/// Wraps a provider for one check, counting existence and metadata queries and
+/// answering repeated queries of a path from a [`CheckCache`].
+pub(crate) struct Metered<'c, P> {
+    inner: P,
+    cache: Cache<'c>,
+    start: Instant,
+    stat_calls: AtomicU64,
+    cache_hits: AtomicU64,
+}
+
+/// The cache of one check, or one shared between checks.
+enum Cache<'c> {
+    Owned(CheckCache),
+    Shared(&'c CheckCache),
+}
+
+impl std::ops::Deref for Cache<'_> {
+    type Target = CheckCache;
+
+    fn deref(&self) -> &CheckCache {
+        match self {
+            Cache::Owned(cache) => cache,
+            Cache::Shared(cache) => cache,
+        }
+    }
+}
+
+impl<P: FileProvider> Metered<'static, P> {
+    /// Wrap `inner` with a cache private to this check.
+    pub(crate) fn new(inner: P) -> Self {
+        Self::with_cache(inner, Cache::Owned(CheckCache::new()))
+    }
+}
+
+impl<'c, P: FileProvider> Metered<'c, P> {
+    /// Wrap `inner`, sharing `cache` with other checks.
+    pub(crate) fn shared(inner: P, cache: &'c CheckCache) -> Self {
+        Self::with_cache(inner, Cache::Shared(cache))
+    }
+
+    fn with_cache(inner: P, cache: Cache<'c>) -> Self {
+        Self {
+            inner,
+            cache,
+            start: Instant::now(),
+            stat_calls: AtomicU64::new(0),
+            cache_hits: AtomicU64::new(0),
+        }
+    }
+
+    /// Count a query made without going through this provider.
+    #[cfg(feature = "tokio")]
+    pub(crate) fn record_stat(&self) {
+        self.stat_calls.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// The report of a check evaluated through this provider, with the sizes
+    /// and counters it recorded.
+    pub(crate) fn report(&self, mut root: NodeReport) -> CheckReport {
+        root.record_sizes(&self.cache);
+        CheckReport::new(root).with_metrics(self.metrics())
+    }
+
+    /// The counters so far, with the time elapsed since this wrapper was made.
+    pub(crate) fn metrics(&self) -> CheckMetrics {
+        CheckMetrics {
+            stat_calls: self.stat_calls.load(Ordering::Relaxed),
+            cache_hits: self.cache_hits.load(Ordering::Relaxed),
+            duration: self.start.elapsed(),
+        }
+    }
+
+    fn cached_metadata(
+        &self,
+        path: &Path,
+        follow: bool,
+        query: impl FnOnce(&P) -> io::Result<FileMetadata>,
+    ) -> io::Result<FileMetadata> {
+        if let Some(cached) = self.cache.metadata(path, follow) {
+            self.cache_hits.fetch_add(1, Ordering::Relaxed);
+            return cached.ok_or_else(|| io::Error::from(io::ErrorKind::NotFound));
+        }
+        self.stat_calls.fetch_add(1, Ordering::Relaxed);
+        let result = query(&self.inner);
+        match &result {
+            Ok(meta) => self.cache.insert_metadata(path, follow, Some(meta.clone())),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                self.cache.insert_metadata(path, follow, None)
+            }
+            Err(_) => {}
+        }
+        result
+    }
+}
+
+impl<P: FileProvider> FileProvider for Metered<'_, P> {
+    fn try_exists(&self, path: &Path) -> io::Result<bool> {
+        if let Some(exists) = self.cache.exists(path) {
+            self.cache_hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(exists);
+        }
+        self.stat_calls.fetch_add(1, Ordering::Relaxed);
+        let exists = self.inner.try_exists(path)?;
+        self.cache.insert_exists(path, exists);
+        Ok(exists)
+    }
+
+    fn glob(&self, pattern: &str) -> io::Result<Vec<PathBuf>> {
+        self.inner.glob(pattern)
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<FileMetadata> {
+        self.cached_metadata(path, true, |inner| inner.metadata(path))
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> io::Result<FileMetadata> {
+        self.cached_metadata(path, false, |inner| inner.symlink_metadata(path))
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        self.inner.read_dir(path)
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn io::Read + '_>> {
+        self.inner.open(path)
+    }
+
+    fn available_space(&self, path: &Path) -> io::Result<u64> {
+        self.inner.available_space(path)
+    }
+
+    fn access(&self, path: &Path, access: Access) -> io::Result<bool> {
+        self.inner.access(path, access)
+    }
+
+    fn url_exists(&self, url: &str) -> io::Result<bool> {
+        self.inner.url_exists(url)
+    }
+
+    fn archive_contains(&self, archive: &Path, member: &str) -> io::Result<bool> {
+        self.inner.archive_contains(archive, member)
+    }
+
+    fn invalidate(&self, path: &Path) {
+        self.cache.forget(path);
+        self.inner.invalidate(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FileConstraints, FileRequirementBuilder};
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn repeated_stats_of_a_path_are_cache_hits() {
+        let td = tempdir().unwrap();
+        let reference = td.path().join("ref.fa");
+        for name in ["ref.fa", "idx.ctab", "idx.ssi"] {
+            fs::write(td.path().join(name), "").unwrap();
+        }
+
+        let mut b = FileRequirementBuilder::new();
+        for name in ["idx.ctab", "idx.ssi"] {
+            b.require_file_with(
+                td.path().join(name),
+                FileConstraints::new().newer_than(&reference),
+            )
+            .unwrap();
+        }
+        b.require_file(td.path().join("idx.sshash")).unwrap();
+        let report = b.build().check_report();
+
+        let metrics = report.metrics();
+        assert_eq!(metrics.stat_calls(), 4);
+        assert_eq!(metrics.cache_hits(), 1);
+        assert!(metrics.duration() >= report.leaves()[0].latency());
+        assert!(report.leaves()[0].latency() > std::time::Duration::ZERO);
+    }
+}
@@ -0,0 +1,136 @@
+use std::ops::{BitAnd, BitOr};
+use std::path::Path;
+
+use crate::{FileRequirement, FileRequirementBuildError};
+
+/// Direct constructors for assembling trees without the builder, when
+/// duplicate detection is not wanted: repeated paths are accepted.
+impl FileRequirement {
+    /// A single required file.
+    pub fn file<P: AsRef<Path>>(path: P) -> FileRequirement {
+        FileRequirement::File(path.as_ref().to_path_buf())
+    }
+
+    /// An `AND` group of `terms`, which must not be empty.
+    pub fn all<I>(terms: I) -> Result<FileRequirement, FileRequirementBuildError>
+    where
+        I: IntoIterator<Item = FileRequirement>,
+    {
+        group(terms, "AND").map(FileRequirement::All)
+    }
+
+    /// An `OR` group of `terms`, which must not be empty.
+    pub fn any<I>(terms: I) -> Result<FileRequirement, FileRequirementBuildError>
+    where
+        I: IntoIterator<Item = FileRequirement>,
+    {
+        group(terms, "OR").map(FileRequirement::Any)
+    }
+
+    /// An `XOR` group of `terms`, which must not be empty.
+    pub fn exactly_one<I>(terms: I) -> Result<FileRequirement, FileRequirementBuildError>
+    where
+        I: IntoIterator<Item = FileRequirement>,
+    {
+        group(terms, "XOR").map(FileRequirement::ExactlyOne)
+    }
+}
+
+fn group<I>(
+    terms: I,
+    group: &'static str,
+) -> Result<Vec<FileRequirement>, FileRequirementBuildError>
+where
+    I: IntoIterator<Item = FileRequirement>,
+{
+    let terms: Vec<FileRequirement> = terms.into_iter().collect();
+    if terms.is_empty() {
+        return Err(FileRequirementBuildError::EmptyGroup { group });
+    }
+    Ok(terms)
+}
+
+/// `a & b` requires both expressions, as an `AND` group. Operands that are
+/// unnamed, unhinted `AND` groups are flattened into it, so `a & b & c` is
+/// `(a AND b AND c)`.
+///
+/// Unlike the builder, the operators cannot fail: combining expressions that
+/// name the same path is not rejected.
+impl BitAnd for FileRequirement {
+    type Output = FileRequirement;
+
+    fn bitand(self, rhs: FileRequirement) -> FileRequirement {
+        let mut terms = Vec::new();
+        for operand in [self, rhs] {
+            match operand {
+                FileRequirement::All(children) => terms.extend(children),
+                term => terms.push(term),
+            }
+        }
+        FileRequirement::All(terms)
+    }
+}
+
+/// `a | b` accepts either expression, as an `OR` group, flattening unnamed,
+/// unhinted `OR` operands as [`BitAnd`] does `AND` groups.
+impl BitOr for FileRequirement {
+    type Output = FileRequirement;
+
+    fn bitor(self, rhs: FileRequirement) -> FileRequirement {
+        let mut terms = Vec::new();
+        for operand in [self, rhs] {
+            match operand {
+                FileRequirement::Any(children) => terms.extend(children),
+                term => terms.push(term),
+            }
+        }
+        FileRequirement::Any(terms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str) -> FileRequirement {
+        FileRequirement::file(path)
+    }
+
+    #[test]
+    fn constructors_reject_empty_groups() {
+        let req = FileRequirement::all([
+            file("idx.ctab"),
+            FileRequirement::any(["idx.sshash", "idx.ssi"].map(file)).unwrap(),
+        ])
+        .unwrap();
+        assert_eq!(req.to_string(), "(idx.ctab AND (idx.sshash OR idx.ssi))");
+        assert!(matches!(
+            FileRequirement::exactly_one([]),
+            Err(FileRequirementBuildError::EmptyGroup { group: "XOR" })
+        ));
+    }
+
+    #[test]
+    fn operators_flatten_groups_of_the_same_kind() {
+        let req = file("idx.ctab") & (file("idx.sshash") | file("idx.ssi") | file("idx.tsv"));
+        assert_eq!(
+            req.to_string(),
+            "(idx.ctab AND (idx.sshash OR idx.ssi OR idx.tsv))"
+        );
+
+        let req = req & file("reads.fq");
+        assert_eq!(
+            req.to_string(),
+            "(idx.ctab AND (idx.sshash OR idx.ssi OR idx.tsv) AND reads.fq)"
+        );
+
+        let named = FileRequirement::Named {
+            name: "reads".to_owned(),
+            term: Box::new(file("r1.fq") & file("r2.fq")),
+        };
+        assert_eq!(
+            (named & file("idx.ctab")).to_string(),
+            r#"(named("reads", (r1.fq AND r2.fq)) AND idx.ctab)"#
+        );
+    }
+}
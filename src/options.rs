@@ -0,0 +1,816 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::eval::Strategy;
+use crate::template::{expand_vars, home_relative, join_home};
+use crate::{
+    CancelToken, FileKind, FileMetadata, FileProvider, FileRequirement, LeafOutcome, RetryPolicy,
+    Severity,
+};
+
+/// How symbolic links are treated when checking terms.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SymlinkPolicy {
+    /// Follow links; a dangling link looks like a missing path.
+    #[default]
+    Follow,
+    /// Do not follow links; a link (even a dangling one) is checked as itself.
+    NoFollow,
+    /// Follow links, but report dangling links as broken instead of missing.
+    FailOnBroken,
+}
+
+/// Callback invoked with each checked term's path and outcome.
+type ProgressFn = dyn FnMut(&Path, &LeafOutcome) + Send;
+
+/// A per-leaf callback, shared between clones of the options that hold it.
+#[derive(Clone)]
+struct ProgressHook(Arc<Mutex<ProgressFn>>);
+
+impl fmt::Debug for ProgressHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressHook")
+    }
+}
+
+/// Options controlling how a requirement expression is checked.
+#[derive(Debug, Clone, Default)]
+pub struct CheckOptions {
+    symlinks: SymlinkPolicy,
+    canonicalize: bool,
+    base_dir: Option<PathBuf>,
+    expand_env: bool,
+    expand_home: bool,
+    allow_overwrite: bool,
+    fail_on: Severity,
+    suggest_similar: bool,
+    fail_fast: bool,
+    exhaustive_any: bool,
+    retry: Option<RetryPolicy>,
+    on_progress: Option<ProgressHook>,
+    cancel: Option<CancelToken>,
+}
+
+impl CheckOptions {
+    /// The default options: symlinks are followed.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how symbolic links are treated.
+    pub fn symlinks(mut self, policy: SymlinkPolicy) -> Self {
+        self.symlinks = policy;
+        self
+    }
+
+    /// Canonicalize paths before checking them.
+    ///
+    /// Reports and error messages then show resolved absolute paths. A missing
+    /// path is resolved through its parent directory, and a path caught in a
+    /// symlink loop is reported as an IO error.
+    pub fn canonicalize(mut self, enabled: bool) -> Self {
+        self.canonicalize = enabled;
+        self
+    }
+
+    /// Resolve relative paths and glob patterns against `dir` instead of the
+    /// current working directory.
+    pub fn base_dir<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.base_dir = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Expand `$NAME` / `${NAME}` environment variable references in paths and
+    /// glob patterns before checking.
+    ///
+    /// Terms referencing an unset variable are reported as
+    /// [`LeafOutcome::UnsetVariable`].
+    pub fn expand_env(mut self, enabled: bool) -> Self {
+        self.expand_env = enabled;
+        self
+    }
+
+    /// Replace a leading `~` with the current user's home directory in paths and
+    /// glob patterns before checking.
+    pub fn expand_home(mut self, enabled: bool) -> Self {
+        self.expand_home = enabled;
+        self
+    }
+
+    /// Treat existing paths of absent terms as satisfied, reporting them as
+    /// [`LeafOutcome::OverwriteAllowed`] warnings instead of failures.
+    ///
+    /// This is the `--force` switch for output guards.
+    pub fn allow_overwrite(mut self, allowed: bool) -> Self {
+        self.allow_overwrite = allowed;
+        self
+    }
+
+    /// Fail the check on findings of `severity` or above.
+    ///
+    /// The default, [`Severity::Error`], fails only when the expression is not
+    /// satisfied; [`Severity::Warning`] also fails on warnings such as absent
+    /// recommended files. For a check that never fails, inspect
+    /// [`CheckReport::findings`](crate::CheckReport::findings) instead.
+    pub fn fail_on(mut self, severity: Severity) -> Self {
+        self.fail_on = severity;
+        self
+    }
+
+    /// Suggest similarly named files for missing terms, e.g.
+    /// `missing files: idx.ctab (did you mean `Idx.CTAB`?)`.
+    ///
+    /// This lists the parent directory of every missing term, so it is off by
+    /// default. Names differing by letter case, by extension, or by one
+    /// character are suggested.
+    pub fn suggest_similar(mut self, enabled: bool) -> Self {
+        self.suggest_similar = enabled;
+        self
+    }
+
+    /// Stop checking an `AND` group at its first failed term, reporting the
+    /// remaining terms as [`LeafOutcome::Skipped`].
+    ///
+    /// Useful for interactive tools that only need the first problem; the
+    /// error then lists just that failure. By default every term is checked.
+    pub fn fail_fast(mut self, enabled: bool) -> Self {
+        self.fail_fast = enabled;
+        self
+    }
+
+    /// Check every alternative of an `OR` group, even after one is satisfied.
+    ///
+    /// By default later alternatives are reported as
+    /// [`LeafOutcome::Skipped`]; checking them all shows near-misses in the
+    /// report, such as an index format missing a single file.
+    pub fn exhaustive_any(mut self, enabled: bool) -> Self {
+        self.exhaustive_any = enabled;
+        self
+    }
+
+    /// Re-check terms that fail with transient IO errors, as `policy` says.
+    ///
+    /// Useful on automounted or network filesystems, where a momentary hiccup
+    /// would otherwise fail an otherwise valid requirement.
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Call `hook` with the path and outcome of each term as soon as it is
+    /// checked, e.g. to drive a progress bar or log on slow storage.
+    ///
+    /// Terms skipped by short-circuiting are not reported. Clones of these
+    /// options share the hook.
+    pub fn on_progress<F>(mut self, hook: F) -> Self
+    where
+        F: FnMut(&Path, &LeafOutcome) + Send + 'static,
+    {
+        self.on_progress = Some(ProgressHook(Arc::new(Mutex::new(hook))));
+        self
+    }
+
+    /// Stop checking terms once `token` is cancelled, reporting the rest as
+    /// [`LeafOutcome::Cancelled`].
+    pub fn cancel_token(mut self, token: CancelToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// The configured symlink policy.
+    pub fn symlink_policy(&self) -> SymlinkPolicy {
+        self.symlinks
+    }
+
+    /// Whether paths are canonicalized before checking.
+    pub fn canonicalizes(&self) -> bool {
+        self.canonicalize
+    }
+
+    /// Whether environment variables are expanded before checking.
+    pub fn expands_env(&self) -> bool {
+        self.expand_env
+    }
+
+    /// Whether a leading `~` is expanded before checking.
+    pub fn expands_home(&self) -> bool {
+        self.expand_home
+    }
+
+    /// Whether existing outputs are allowed to be overwritten.
+    pub fn allows_overwrite(&self) -> bool {
+        self.allow_overwrite
+    }
+
+    /// The lowest severity that fails the check.
+    pub fn failure_threshold(&self) -> Severity {
+        self.fail_on
+    }
+
+    /// Whether similarly named files are suggested for missing terms.
+    pub fn suggests_similar(&self) -> bool {
+        self.suggest_similar
+    }
+
+    /// Whether `AND` groups stop at their first failed term.
+    pub fn fails_fast(&self) -> bool {
+        self.fail_fast
+    }
+
+    /// Whether every alternative of an `OR` group is checked.
+    pub fn checks_all_alternatives(&self) -> bool {
+        self.exhaustive_any
+    }
+
+    /// The retry policy for transient IO errors, if set.
+    pub fn retry_policy(&self) -> Option<&RetryPolicy> {
+        self.retry.as_ref()
+    }
+
+    /// The directory relative paths are resolved against, if set.
+    pub fn base(&self) -> Option<&Path> {
+        self.base_dir.as_deref()
+    }
+
+    /// The tree to evaluate: `requirement` itself, or a rebased and/or
+    /// canonicalized copy.
+    pub(crate) fn prepare<'a>(&self, requirement: &'a FileRequirement) -> Prepared<'a> {
+        self.prepare_with_vars(requirement, |name| std::env::var(name).ok())
+    }
+
+    /// Like [`prepare`](Self::prepare), reading environment variables from
+    /// `lookup`.
+    fn prepare_with_vars<'a, F>(&self, requirement: &'a FileRequirement, lookup: F) -> Prepared<'a>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let mut prepared = Cow::Borrowed(requirement);
+        let mut unset = Vec::new();
+        if self.expand_env {
+            // Terms with unset variables keep their reference; `evaluate_leaf`
+            // reports them.
+            unset = leaves(requirement)
+                .into_iter()
+                .map(|leaf| {
+                    leaf.leaf_path()
+                        .to_str()
+                        .and_then(|template| expand_vars(template, &lookup).err())
+                })
+                .collect();
+            let expand = |template: &str| {
+                expand_vars(template, &lookup).unwrap_or_else(|_| template.to_owned())
+            };
+            prepared = Cow::Owned(prepared.map_text(&mut |template| expand(template)));
+        }
+        if self.expand_home
+            && let Some(home) = std::env::home_dir()
+        {
+            let expand = |template: &str| match home_relative(template) {
+                Some(rest) => join_home(&home, rest),
+                None => template.to_owned(),
+            };
+            prepared = Cow::Owned(prepared.map_text(&mut |template| expand(template)));
+        }
+        if let Some(base) = &self.base_dir {
+            prepared = Cow::Owned(prepared.rebased(base));
+        }
+        if self.canonicalize {
+            prepared = Cow::Owned(prepared.map_paths(&mut |path| canonical(path)));
+        }
+        // Preparing keeps the shape of the tree, so leaves pair up in order.
+        let unset = leaves(&prepared)
+            .into_iter()
+            .zip(unset)
+            .filter_map(|(leaf, name)| Some((leaf.clone(), name?)))
+            .collect();
+        Prepared {
+            tree: prepared,
+            unset,
+        }
+    }
+
+    /// How groups are evaluated under these options.
+    pub(crate) fn strategy(&self) -> Strategy {
+        Strategy {
+            fail_fast: self.fail_fast,
+            exhaustive_any: self.exhaustive_any,
+        }
+    }
+
+    /// Evaluate a single leaf term of `prepared` against `provider` under
+    /// these options.
+    pub(crate) fn evaluate_leaf<P: FileProvider + ?Sized>(
+        &self,
+        prepared: &Prepared<'_>,
+        leaf: &FileRequirement,
+        provider: &P,
+    ) -> LeafOutcome {
+        if self.cancel.as_ref().is_some_and(CancelToken::is_cancelled) {
+            return LeafOutcome::Cancelled;
+        }
+        let outcome = match prepared.unset.get(leaf) {
+            Some(name) => LeafOutcome::UnsetVariable { name: name.clone() },
+            None => self.evaluate_leaf_quietly(leaf, provider),
+        };
+        if let Some(ProgressHook(hook)) = &self.on_progress {
+            // A hook that panicked earlier is still called.
+            let mut hook = hook.lock().unwrap_or_else(|e| e.into_inner());
+            hook(leaf.leaf_path(), &outcome);
+        }
+        outcome
+    }
+
+    fn evaluate_leaf_quietly<P: FileProvider + ?Sized>(
+        &self,
+        leaf: &FileRequirement,
+        provider: &P,
+    ) -> LeafOutcome {
+        let outcome = match &self.retry {
+            Some(policy) => policy.run(|| self.evaluate_symlinks(leaf, provider)),
+            None => self.evaluate_symlinks(leaf, provider),
+        };
+        if self.allow_overwrite && outcome == LeafOutcome::AlreadyExists {
+            return LeafOutcome::OverwriteAllowed;
+        }
+        outcome
+    }
+
+    fn evaluate_symlinks<P: FileProvider + ?Sized>(
+        &self,
+        leaf: &FileRequirement,
+        provider: &P,
+    ) -> LeafOutcome {
+        match self.symlinks {
+            SymlinkPolicy::Follow => leaf.evaluate_leaf(provider),
+            SymlinkPolicy::NoFollow => leaf.evaluate_leaf(&NoFollow(provider)),
+            SymlinkPolicy::FailOnBroken => match leaf.evaluate_leaf(provider) {
+                LeafOutcome::Missing
+                    if !matches!(
+                        leaf.unwrapped(),
+                        FileRequirement::Glob { .. } | FileRequirement::GlobSize { .. }
+                    ) && provider
+                        .symlink_metadata(leaf.leaf_path())
+                        .is_ok_and(|meta| meta.kind() == FileKind::Symlink) =>
+                {
+                    LeafOutcome::BrokenSymlink
+                }
+                outcome => outcome,
+            },
+        }
+    }
+}
+
+/// A requirement tree prepared for evaluation under [`CheckOptions`].
+pub(crate) struct Prepared<'a> {
+    tree: Cow<'a, FileRequirement>,
+    /// Prepared leaves whose template named an unset variable, with its name.
+    unset: HashMap<FileRequirement, String>,
+}
+
+impl std::ops::Deref for Prepared<'_> {
+    type Target = FileRequirement;
+
+    fn deref(&self) -> &FileRequirement {
+        &self.tree
+    }
+}
+
+/// The leaf terms of `tree`, depth first.
+fn leaves(tree: &FileRequirement) -> Vec<&FileRequirement> {
+    match tree.group() {
+        Some((_, children)) => children.iter().flat_map(leaves).collect(),
+        None => vec![tree],
+    }
+}
+
+/// The canonical form of `path`, falling back to canonicalizing the parent of a
+/// missing path, and finally to the unresolved absolute path.
+///
+/// Any other failure (such as a symlink loop) keeps `path` as is, so the
+/// subsequent check surfaces the same error.
+fn canonical(path: &Path) -> PathBuf {
+    match fs::canonicalize(path) {
+        Ok(resolved) => resolved,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            let resolved_parent = match (path.parent(), path.file_name()) {
+                (Some(parent), Some(name)) if !parent.as_os_str().is_empty() => {
+                    fs::canonicalize(parent)
+                        .ok()
+                        .map(|parent| parent.join(name))
+                }
+                _ => None,
+            };
+            resolved_parent
+                .or_else(|| std::path::absolute(path).ok())
+                .unwrap_or_else(|| path.to_path_buf())
+        }
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+/// Resolves metadata and existence without following symlinks.
+struct NoFollow<'a, P: ?Sized>(&'a P);
+
+impl<P: FileProvider + ?Sized> FileProvider for NoFollow<'_, P> {
+    fn try_exists(&self, path: &Path) -> io::Result<bool> {
+        match self.0.symlink_metadata(path) {
+            Ok(_) => Ok(true),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn glob(&self, pattern: &str) -> io::Result<Vec<PathBuf>> {
+        self.0.glob(pattern)
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<FileMetadata> {
+        self.0.symlink_metadata(path)
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> io::Result<FileMetadata> {
+        self.0.symlink_metadata(path)
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        self.0.read_dir(path)
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn io::Read + '_>> {
+        self.0.open(path)
+    }
+
+    fn available_space(&self, path: &Path) -> io::Result<u64> {
+        self.0.available_space(path)
+    }
+
+    fn access(&self, path: &Path, access: crate::Access) -> io::Result<bool> {
+        self.0.access(path, access)
+    }
+
+    fn url_exists(&self, url: &str) -> io::Result<bool> {
+        self.0.url_exists(url)
+    }
+
+    fn archive_contains(&self, archive: &Path, member: &str) -> io::Result<bool> {
+        self.0.archive_contains(archive, member)
+    }
+
+    fn invalidate(&self, path: &Path) {
+        self.0.invalidate(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CheckOptions, SymlinkPolicy};
+    use crate::{FileRequirementBuilder, LeafOutcome, Severity};
+    use tempfile::tempdir;
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_policy_distinguishes_dangling_links() {
+        let td = tempdir().unwrap();
+        let link = td.path().join("ref.fa");
+        std::os::unix::fs::symlink(td.path().join("gone.fa"), &link).unwrap();
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_file(&link).unwrap();
+        let req = b.build();
+
+        assert!(
+            req.check()
+                .unwrap_err()
+                .to_string()
+                .contains("missing files: ")
+        );
+
+        let broken = CheckOptions::new().symlinks(SymlinkPolicy::FailOnBroken);
+        assert!(matches!(
+            req.check_report_with_options(&broken).leaves()[0].outcome(),
+            LeafOutcome::BrokenSymlink
+        ));
+        assert_eq!(
+            req.check_with_options(&broken).unwrap_err().to_string(),
+            format!(
+                "Required input files were missing or incomplete (broken symlinks: {})",
+                link.display()
+            )
+        );
+
+        let no_follow = CheckOptions::new().symlinks(SymlinkPolicy::NoFollow);
+        assert!(req.check_with_options(&no_follow).is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn canonicalize_resolves_paths_and_reports_loops() {
+        let td = tempdir().unwrap();
+        let root = td.path().canonicalize().unwrap();
+        std::fs::create_dir(root.join("sub")).unwrap();
+        std::os::unix::fs::symlink(root.join("a"), root.join("b")).unwrap();
+        std::os::unix::fs::symlink(root.join("b"), root.join("a")).unwrap();
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_file(td.path().join("sub/../missing.idx"))
+            .unwrap();
+        b.require_file(td.path().join("a")).unwrap();
+        let req = b.build();
+
+        let report = req.check_report_with_options(&CheckOptions::new().canonicalize(true));
+        let leaves = report.leaves();
+        assert_eq!(leaves[0].path(), root.join("missing.idx"));
+        assert_eq!(*leaves[0].outcome(), LeafOutcome::Missing);
+        assert_eq!(leaves[1].path(), root.join("a"));
+        assert!(matches!(leaves[1].outcome(), LeafOutcome::IoError { .. }));
+    }
+
+    #[test]
+    fn check_in_resolves_relative_terms_against_base() {
+        let td = tempdir().unwrap();
+        std::fs::create_dir(td.path().join("reads")).unwrap();
+        std::fs::write(td.path().join("idx.ctab"), "").unwrap();
+        std::fs::write(td.path().join("reads").join("r1.fq"), "").unwrap();
+
+        let req: crate::FileRequirement =
+            r#"(idx.ctab AND glob("reads/*.fq", 1))"#.parse().unwrap();
+        assert!(req.check_in(td.path()).is_ok());
+        assert_eq!(req.to_string(), r#"(idx.ctab AND glob("reads/*.fq", 1))"#);
+
+        let report = req.check_report_with_options(&CheckOptions::new().base_dir("elsewhere"));
+        assert_eq!(
+            report.leaves()[0].path(),
+            std::path::Path::new("elsewhere/idx.ctab")
+        );
+        assert!(!report.is_satisfied());
+    }
+
+    #[test]
+    fn expand_env_reports_unset_variables() {
+        let req: crate::FileRequirement = "${FILE_REQUIREMENTS_UNSET_TEST_VAR}/idx.ctab"
+            .parse()
+            .unwrap();
+        let options = CheckOptions::new().expand_env(true);
+        assert_eq!(
+            req.check_with_options(&options).unwrap_err().to_string(),
+            "Required input files were missing or incomplete (unset environment variables: $FILE_REQUIREMENTS_UNSET_TEST_VAR (in ${FILE_REQUIREMENTS_UNSET_TEST_VAR}/idx.ctab))"
+        );
+    }
+
+    #[test]
+    fn expanded_values_are_not_expanded_again() {
+        let td = tempdir().unwrap();
+        let dir = td.path().join("${FILE_REQUIREMENTS_UNSET_TEST_VAR}");
+        std::fs::create_dir(&dir).unwrap();
+        std::fs::write(dir.join("idx.ctab"), "").unwrap();
+
+        let req: crate::FileRequirement = "${DATA}/idx.ctab".parse().unwrap();
+        let options = CheckOptions::new().expand_env(true);
+        let data = dir.display().to_string();
+        let prepared =
+            options.prepare_with_vars(&req, |name| (name == "DATA").then(|| data.clone()));
+        assert_eq!(prepared.leaf_path(), dir.join("idx.ctab"));
+        assert_eq!(
+            options.evaluate_leaf(&prepared, &prepared, &crate::StdFs),
+            LeafOutcome::Present
+        );
+    }
+
+    #[test]
+    fn absent_outputs_fail_unless_overwrite_is_allowed() {
+        let td = tempdir().unwrap();
+        let out = td.path().join("quant.sf");
+        std::fs::write(&out, "").unwrap();
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_absent(&out).unwrap();
+        b.require_absent(td.path().join("aux_info")).unwrap();
+        let req = b.build();
+        assert_eq!(
+            req.check().unwrap_err().to_string(),
+            format!(
+                "Required input files were missing or incomplete (outputs already exist: {})",
+                out.display()
+            )
+        );
+
+        let report = req.check_report_with_options(&CheckOptions::new().allow_overwrite(true));
+        assert!(report.is_satisfied());
+        assert_eq!(
+            report.warnings(),
+            [format!("output will be overwritten: {}", out.display())]
+        );
+    }
+
+    #[test]
+    fn failure_threshold_decides_which_findings_fail() {
+        let td = tempdir().unwrap();
+        let out = td.path().join("quant.sf");
+        std::fs::write(&out, "").unwrap();
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_absent(&out).unwrap();
+        b.recommend_file(td.path().join("decoys.txt")).unwrap();
+        let req = b.build();
+        let options = CheckOptions::new().allow_overwrite(true);
+
+        let report = req.check_report_with_options(&options);
+        let severities: Vec<Severity> = report.findings().iter().map(|f| f.severity()).collect();
+        assert_eq!(severities, [Severity::Info, Severity::Warning]);
+
+        assert!(req.check_with_options(&options).is_ok());
+        assert_eq!(
+            req.check_with_options(&options.clone().fail_on(Severity::Warning))
+                .unwrap_err()
+                .to_string(),
+            format!(
+                "Required input files were missing or incomplete (recommended file not found: {})",
+                td.path().join("decoys.txt").display()
+            )
+        );
+        assert!(
+            req.check_with_options(&options.fail_on(Severity::Info))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn missing_terms_suggest_similar_names_when_enabled() {
+        let td = tempdir().unwrap();
+        std::fs::write(td.path().join("Idx.CTAB"), "").unwrap();
+        std::fs::write(td.path().join("ref.fa"), "").unwrap();
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_file("idx.ctab").unwrap();
+        let req = b.build();
+        let options = CheckOptions::new().base_dir(td.path());
+        assert!(
+            !req.check_with_options(&options)
+                .unwrap_err()
+                .to_string()
+                .contains("did you mean")
+        );
+        assert_eq!(
+            req.check_with_options(&options.suggest_similar(true))
+                .unwrap_err()
+                .to_string(),
+            format!(
+                "Required input files were missing or incomplete (missing files: {} (did you mean `Idx.CTAB`?))",
+                td.path().join("idx.ctab").display()
+            )
+        );
+    }
+
+    #[test]
+    fn fail_fast_stops_and_groups_at_the_first_failure() {
+        let td = tempdir().unwrap();
+        std::fs::write(td.path().join("a.txt"), "").unwrap();
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_file("a.txt").unwrap();
+        b.require_file("b.txt").unwrap();
+        b.require_any(|any| {
+            any.require_file("c.txt")?;
+            any.require_file("d.txt")?;
+            Ok(())
+        })
+        .unwrap();
+        let req = b.build();
+        let options = CheckOptions::new().base_dir(td.path());
+
+        let report = req.check_report_with_options(&options.clone().fail_fast(true));
+        let outcomes: Vec<&LeafOutcome> = report.leaves().iter().map(|l| l.outcome()).collect();
+        assert_eq!(
+            outcomes,
+            [
+                &LeafOutcome::Present,
+                &LeafOutcome::Missing,
+                &LeafOutcome::Skipped,
+                &LeafOutcome::Skipped
+            ]
+        );
+        assert_eq!(
+            req.check_with_options(&options.fail_fast(true))
+                .unwrap_err()
+                .to_string(),
+            format!(
+                "Required input files were missing or incomplete (missing files: {})",
+                td.path().join("b.txt").display()
+            )
+        );
+    }
+
+    #[test]
+    fn exhaustive_any_reports_near_misses_in_later_alternatives() {
+        let td = tempdir().unwrap();
+        for name in ["idx.sshash", "idx.ssi"] {
+            std::fs::write(td.path().join(name), "").unwrap();
+        }
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_any(|any| {
+            any.require_file("idx.sshash")?;
+            any.require_all(|all| {
+                all.require_file("idx.ssi")?;
+                all.require_file("idx.ssi.mphf")?;
+                Ok(())
+            })?;
+            Ok(())
+        })
+        .unwrap();
+        let req = b.build();
+        let options = CheckOptions::new().base_dir(td.path());
+
+        let outcomes = |options: &CheckOptions| -> Vec<LeafOutcome> {
+            let report = req.check_report_with_options(options);
+            assert!(report.is_satisfied());
+            report
+                .leaves()
+                .iter()
+                .map(|l| l.outcome().clone())
+                .collect()
+        };
+        assert_eq!(
+            outcomes(&options),
+            [
+                LeafOutcome::Present,
+                LeafOutcome::Skipped,
+                LeafOutcome::Skipped
+            ]
+        );
+        assert_eq!(
+            outcomes(&options.exhaustive_any(true)),
+            [
+                LeafOutcome::Present,
+                LeafOutcome::Present,
+                LeafOutcome::Missing
+            ]
+        );
+    }
+
+    #[test]
+    fn progress_hook_sees_each_checked_term() {
+        let td = tempdir().unwrap();
+        std::fs::write(td.path().join("a.txt"), "").unwrap();
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_any(|any| {
+            any.require_file("a.txt")?;
+            any.require_file("b.txt")?;
+            Ok(())
+        })
+        .unwrap();
+        b.require_file("c.txt").unwrap();
+
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = std::sync::Arc::clone(&seen);
+        let options = CheckOptions::new()
+            .base_dir(td.path())
+            .on_progress(move |path, outcome| {
+                let name = path.file_name().unwrap().to_string_lossy().into_owned();
+                sink.lock().unwrap().push((name, outcome.clone()));
+            });
+        b.build().check_report_with_options(&options);
+
+        assert_eq!(
+            *seen.lock().unwrap(),
+            [
+                ("a.txt".to_owned(), LeafOutcome::Present),
+                ("c.txt".to_owned(), LeafOutcome::Missing)
+            ]
+        );
+    }
+
+    #[test]
+    fn cancelled_checks_stop_before_the_remaining_terms() {
+        let td = tempdir().unwrap();
+        let mut b = FileRequirementBuilder::new();
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            b.require_file(name).unwrap();
+        }
+
+        let token = crate::CancelToken::new();
+        let canceller = token.clone();
+        let options = CheckOptions::new()
+            .base_dir(td.path())
+            .cancel_token(token)
+            .on_progress(move |_, _| canceller.cancel());
+        let report = b.build().check_report_with_options(&options);
+
+        assert!(report.is_cancelled());
+        let outcomes: Vec<&LeafOutcome> = report.leaves().iter().map(|l| l.outcome()).collect();
+        assert_eq!(
+            outcomes,
+            [
+                &LeafOutcome::Missing,
+                &LeafOutcome::Cancelled,
+                &LeafOutcome::Cancelled
+            ]
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use std::fmt;
+
+#[cfg(not(unix))]
+compile_error!("the `unix` feature is only supported on Unix targets");
+
+/// The user a file term must be owned by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum Owner {
+    /// The effective user of the checking process.
+    CurrentUser,
+    /// A specific numeric user id.
+    Uid(u32),
+}
+
+impl Owner {
+    /// The numeric user id this owner resolves to.
+    pub fn uid(&self) -> u32 {
+        match *self {
+            // SAFETY: `geteuid` has no preconditions and cannot fail.
+            Owner::CurrentUser => unsafe { libc::geteuid() },
+            Owner::Uid(uid) => uid,
+        }
+    }
+
+    /// Parse the textual `owner=self` / `owner=UID` argument form.
+    pub(crate) fn parse_arg(arg: &str) -> Option<Self> {
+        match arg.strip_prefix("owner=")? {
+            "self" => Some(Owner::CurrentUser),
+            uid => uid.parse().ok().map(Owner::Uid),
+        }
+    }
+
+    pub(crate) fn write_arg(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Owner::CurrentUser => f.write_str("owner=self"),
+            Owner::Uid(uid) => write!(f, "owner={}", uid),
+        }
+    }
+}
+
+/// Renders the owner in prose, e.g. `uid 1000`.
+impl fmt::Display for Owner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Owner::CurrentUser => write!(f, "the current user (uid {})", self.uid()),
+            Owner::Uid(uid) => write!(f, "uid {}", uid),
+        }
+    }
+}
+
+/// Parse the textual `group=GID` argument form.
+pub(crate) fn parse_group_arg(arg: &str) -> Option<u32> {
+    arg.strip_prefix("group=")?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Owner;
+    use crate::{FileConstraints, FileRequirement, FileRequirementBuilder};
+    use std::fs;
+    use std::os::unix::fs::MetadataExt;
+    use tempfile::tempdir;
+
+    #[test]
+    fn ownership_constraints_compare_uid_and_gid() {
+        let td = tempdir().unwrap();
+        let shared = td.path().join("shared.idx");
+        fs::write(&shared, "").unwrap();
+        let meta = fs::metadata(&shared).unwrap();
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_file_with(
+            &shared,
+            FileConstraints::new()
+                .owner(Owner::CurrentUser)
+                .group(meta.gid()),
+        )
+        .unwrap();
+        let req = b.build();
+        assert!(req.check().is_ok());
+        let parsed: FileRequirement = req.to_string().parse().unwrap();
+        assert_eq!(parsed, req);
+
+        let other = meta.uid().wrapping_add(1);
+        let mut b = FileRequirementBuilder::new();
+        b.require_file_with(&shared, FileConstraints::new().owner(Owner::Uid(other)))
+            .unwrap();
+        assert_eq!(
+            b.build().check().unwrap_err().to_string(),
+            format!(
+                "Required input files were missing or incomplete (unexpected ownership: {} (owned by uid {}, gid {}; expected owner uid {}))",
+                shared.display(),
+                meta.uid(),
+                meta.gid(),
+                other
+            )
+        );
+    }
+}
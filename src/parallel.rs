@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+
+use rayon::prelude::*;
+
+use crate::metrics::{Metered, timed};
+use crate::{CheckReport, FileRequirement, FileRequirementCheckError, StdFs};
+
+impl FileRequirement {
+    /// Validate this requirement expression, stat'ing file terms in parallel.
+    pub fn check_parallel(&self) -> Result<(), FileRequirementCheckError> {
+        let report = self.check_report_parallel();
+        if report.is_satisfied() {
+            Ok(())
+        } else {
+            Err(FileRequirementCheckError::from_report(&report))
+        }
+    }
+
+    /// Parallel counterpart of [`FileRequirement::check_report`].
+    ///
+    /// Every distinct leaf term is evaluated on the rayon thread pool, then the
+    /// results are aggregated exactly as in the sequential check, so reports and
+    /// error messages are identical.
+    pub fn check_report_parallel(&self) -> CheckReport {
+        let metered = Metered::new(StdFs);
+        let outcomes: HashMap<_, _> = self
+            .distinct_leaves()
+            .into_par_iter()
+            .map(|leaf| (leaf, timed(|| leaf.evaluate_leaf(&metered))))
+            .collect();
+        metered.report(self.evaluate_outcomes(&outcomes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FileRequirementBuilder;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn parallel_check_matches_sequential_check() {
+        let td = tempdir().unwrap();
+        let mut b = FileRequirementBuilder::new();
+        for i in 0..64 {
+            let path = td.path().join(format!("shard_{i}.rad"));
+            if i % 3 != 0 {
+                fs::write(&path, "").unwrap();
+            }
+            b.require_file(path).unwrap();
+        }
+        let req = b.build();
+
+        let sequential = req.check().unwrap_err().to_string();
+        let parallel = req.check_parallel().unwrap_err().to_string();
+        assert_eq!(sequential, parallel);
+        assert!(parallel.contains("shard_63.rad"));
+    }
+}
@@ -0,0 +1,750 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::scheme::SeenTerms;
+use crate::{FileConstraints, FileRequirement, FileRequirementBuildError};
+
+/// Errors produced while parsing the textual requirement syntax.
+#[derive(Debug, Error)]
+pub enum FileRequirementParseError {
+    /// The input ended before the expression was complete.
+    #[error("Unexpected end of input.")]
+    UnexpectedEnd,
+    /// A token appeared where it is not allowed.
+    #[error("Unexpected `{token}` at offset {offset}.")]
+    UnexpectedToken { token: String, offset: usize },
+    /// A quoted path was not closed.
+    #[error("Unterminated quoted path starting at offset {offset}.")]
+    UnterminatedQuote { offset: usize },
+    /// Different operators were used in the same group.
+    #[error(
+        "Operator `{found}` at offset {offset} cannot be mixed with `{expected}` in the same group; add parentheses."
+    )]
+    MixedOperators {
+        expected: &'static str,
+        found: &'static str,
+        offset: usize,
+    },
+    /// A term function such as `glob(...)` is not known.
+    #[error("Unknown term function `{name}` at offset {offset}.")]
+    UnknownFunction { name: String, offset: usize },
+    /// A term function received malformed arguments.
+    #[error("Invalid arguments to `{name}` at offset {offset}: {message}.")]
+    InvalidArguments {
+        name: String,
+        offset: usize,
+        message: String,
+    },
+    /// The parsed expression violates a builder invariant.
+    #[error(transparent)]
+    Build(#[from] FileRequirementBuildError),
+}
+
+/// Parses the syntax produced by [`FileRequirement`]'s `Display` impl.
+///
+/// - terms are bare paths, or double-quoted paths with `\"` and `\\` escapes
+/// - glob terms are written `glob("pattern", min_matches)`, and glob terms with
+///   a minimum total size `glob_size("pattern", bytes)`
+/// - at-least-k shard terms are written `shards("part_{}.rad", START..END, k)`
+/// - directory terms are written `dir(path)`, `non_empty_dir(path)` or
+///   `writable_dir(path)`, and entry counts `dir_entries(path, N)` or
+///   `dir_entries(path, N, "*.txt")`
+/// - paths that must not exist are written `absent(path)`
+/// - warn-only recommended files are written `recommend(path)`
+/// - free-space terms are written `free_space(path, bytes)`
+/// - paths that must share a filesystem are written `same_filesystem(a, b)`
+/// - programs that must be on `PATH` are written `executable(name)`
+/// - archive members are written `archive_member(archive, member)`
+/// - URLs that must answer a `HEAD` request are written `url("https://...")`
+/// - environment variables are written `env(NAME)`, optionally followed by
+///   `non_empty` and/or `matches=REGEX`
+/// - constrained file terms are written `file(path, constraint, ...)`, where the
+///   constraints are `regular`, `readable`, `writable`, `executable`, `size>=N`,
+///   `size<=N`, `size=MIN..MAX`, `age<=SECSs` / `age>=SECSs`, `newer_than=PATH`,
+///   `magic=gzip|bam|cram|HEX`, `stable=SECSs`,
+///   with the `unix` feature `owner=self|UID` / `group=GID`, and with the
+///   `checksum` feature `sha256=HEX` / `md5=HEX`, and with the `gzip` feature
+///   `intact_gzip`
+/// - named terms and groups are written `named("name", term)`, and terms or
+///   groups with a remediation hint `hint("text", term)`
+/// - groups are parenthesized and joined by a single operator: `AND`, `OR` or `XOR`
+/// - the outermost parentheses may be omitted, and a lone term parses as a file
+/// - a single-term group such as `(a.txt)` parses as an `AND` group
+impl FromStr for FileRequirement {
+    type Err = FileRequirementParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_with_schemes(s, [])
+    }
+}
+
+/// Parse `s`, accepting paths that use one of `schemes`.
+pub(crate) fn parse_with_schemes<'s>(
+    s: &str,
+    schemes: impl IntoIterator<Item = &'s str>,
+) -> Result<FileRequirement, FileRequirementParseError> {
+    let mut seen_terms = SeenTerms::default();
+    seen_terms.allow_schemes(schemes);
+    let mut parser = Parser {
+        tokens: tokenize(s)?,
+        pos: 0,
+        seen_terms,
+    };
+    let expr = parser.parse_sequence(false)?;
+    match parser.tokens.get(parser.pos) {
+        None => Ok(expr),
+        Some(token) => Err(token.unexpected()),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    And,
+    Or,
+    Xor,
+}
+
+impl Operator {
+    fn keyword(self) -> &'static str {
+        match self {
+            Operator::And => "AND",
+            Operator::Or => "OR",
+            Operator::Xor => "XOR",
+        }
+    }
+
+    fn from_keyword(word: &str) -> Option<Self> {
+        match word {
+            "AND" => Some(Operator::And),
+            "OR" => Some(Operator::Or),
+            "XOR" => Some(Operator::Xor),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum TokenKind {
+    Open,
+    Close,
+    Comma,
+    /// A function name immediately followed by `(`, e.g. `glob(`.
+    Call(String),
+    Operator(Operator),
+    Path(String),
+}
+
+#[derive(Debug)]
+struct Token {
+    kind: TokenKind,
+    offset: usize,
+}
+
+impl Token {
+    fn unexpected(&self) -> FileRequirementParseError {
+        let token = match &self.kind {
+            TokenKind::Open => "(".to_string(),
+            TokenKind::Close => ")".to_string(),
+            TokenKind::Comma => ",".to_string(),
+            TokenKind::Call(name) => format!("{}(", name),
+            TokenKind::Operator(op) => op.keyword().to_string(),
+            TokenKind::Path(path) => path.clone(),
+        };
+        FileRequirementParseError::UnexpectedToken {
+            token,
+            offset: self.offset,
+        }
+    }
+}
+
+fn is_delimiter(c: char) -> bool {
+    matches!(c, '(' | ')' | '"' | ',')
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, FileRequirementParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+    while let Some(&(offset, c)) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token {
+                    kind: TokenKind::Open,
+                    offset,
+                });
+            }
+            ',' => {
+                chars.next();
+                tokens.push(Token {
+                    kind: TokenKind::Comma,
+                    offset,
+                });
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token {
+                    kind: TokenKind::Close,
+                    offset,
+                });
+            }
+            '"' => {
+                chars.next();
+                let mut value = String::new();
+                let mut closed = false;
+                while let Some((_, c)) = chars.next() {
+                    match c {
+                        '"' => {
+                            closed = true;
+                            break;
+                        }
+                        '\\' => match chars.next() {
+                            Some((_, escaped)) => value.push(escaped),
+                            None => break,
+                        },
+                        c => value.push(c),
+                    }
+                }
+                if !closed {
+                    return Err(FileRequirementParseError::UnterminatedQuote { offset });
+                }
+                tokens.push(Token {
+                    kind: TokenKind::Path(value),
+                    offset,
+                });
+            }
+            _ => {
+                let mut value = String::new();
+                while let Some(&(_, c)) = chars.peek() {
+                    if c.is_whitespace() || is_delimiter(c) {
+                        break;
+                    }
+                    value.push(c);
+                    chars.next();
+                }
+                let kind = match Operator::from_keyword(&value) {
+                    Some(op) => TokenKind::Operator(op),
+                    None if matches!(chars.peek(), Some((_, '('))) => {
+                        chars.next();
+                        TokenKind::Call(value)
+                    }
+                    None => TokenKind::Path(value),
+                };
+                tokens.push(Token { kind, offset });
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    seen_terms: SeenTerms,
+}
+
+impl Parser {
+    /// Parse operands joined by one operator, up to `)` (when `nested`) or end of input.
+    fn parse_sequence(
+        &mut self,
+        nested: bool,
+    ) -> Result<FileRequirement, FileRequirementParseError> {
+        let mut children = Vec::new();
+        let mut operator = None;
+        loop {
+            match self.tokens.get(self.pos) {
+                Some(Token {
+                    kind: TokenKind::Close,
+                    ..
+                }) if nested && children.is_empty() => break,
+                _ => children.push(self.parse_operand()?),
+            }
+            match self.tokens.get(self.pos) {
+                None if !nested => break,
+                None => return Err(FileRequirementParseError::UnexpectedEnd),
+                Some(Token {
+                    kind: TokenKind::Close,
+                    ..
+                }) if nested => break,
+                Some(Token {
+                    kind: TokenKind::Operator(op),
+                    offset,
+                }) => {
+                    match operator {
+                        Some(expected) if expected != *op => {
+                            return Err(FileRequirementParseError::MixedOperators {
+                                expected: Operator::keyword(expected),
+                                found: op.keyword(),
+                                offset: *offset,
+                            });
+                        }
+                        _ => operator = Some(*op),
+                    }
+                    self.pos += 1;
+                }
+                Some(token) => return Err(token.unexpected()),
+            }
+        }
+
+        if !nested && operator.is_none() && children.len() == 1 {
+            return Ok(children.remove(0));
+        }
+        match operator.unwrap_or(Operator::And) {
+            Operator::And if children.is_empty() => {
+                Err(FileRequirementBuildError::EmptyGroup { group: "AND" }.into())
+            }
+            Operator::And => Ok(FileRequirement::All(children)),
+            Operator::Or => Ok(FileRequirement::Any(children)),
+            Operator::Xor => Ok(FileRequirement::ExactlyOne(children)),
+        }
+    }
+
+    fn parse_operand(&mut self) -> Result<FileRequirement, FileRequirementParseError> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .ok_or(FileRequirementParseError::UnexpectedEnd)?;
+        match &token.kind {
+            TokenKind::Open => {
+                self.pos += 1;
+                let group = self.parse_sequence(true)?;
+                self.pos += 1;
+                Ok(group)
+            }
+            TokenKind::Path(path) => {
+                let path = PathBuf::from(path);
+                self.insert_term(&path)?;
+                self.pos += 1;
+                Ok(FileRequirement::File(path))
+            }
+            TokenKind::Call(name) if name == "named" || name == "hint" => {
+                let wrapper = name.clone();
+                self.pos += 1;
+                let label = self.expect_path()?;
+                self.expect(TokenKind::Comma)?;
+                let term = Box::new(self.parse_operand()?);
+                self.expect(TokenKind::Close)?;
+                Ok(if wrapper == "named" {
+                    FileRequirement::Named { name: label, term }
+                } else {
+                    FileRequirement::Hinted { hint: label, term }
+                })
+            }
+            TokenKind::Call(name) => {
+                let (name, offset) = (name.clone(), token.offset);
+                self.pos += 1;
+                let args = self.parse_arguments()?;
+                self.parse_call(&name, offset, args)
+            }
+            TokenKind::Close | TokenKind::Comma | TokenKind::Operator(_) => Err(token.unexpected()),
+        }
+    }
+}
+
+impl Parser {
+    /// Consume a path token and return its text.
+    fn expect_path(&mut self) -> Result<String, FileRequirementParseError> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .ok_or(FileRequirementParseError::UnexpectedEnd)?;
+        match &token.kind {
+            TokenKind::Path(value) => {
+                self.pos += 1;
+                Ok(value.clone())
+            }
+            _ => Err(token.unexpected()),
+        }
+    }
+
+    /// Consume a token of the given kind.
+    fn expect(&mut self, kind: TokenKind) -> Result<(), FileRequirementParseError> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .ok_or(FileRequirementParseError::UnexpectedEnd)?;
+        if token.kind != kind {
+            return Err(token.unexpected());
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn insert_term(&mut self, term: &Path) -> Result<(), FileRequirementParseError> {
+        Ok(self.seen_terms.claim_path(term)?)
+    }
+
+    /// Parse comma-separated arguments up to and including the closing `)`.
+    fn parse_arguments(&mut self) -> Result<Vec<String>, FileRequirementParseError> {
+        let mut args = Vec::new();
+        loop {
+            let token = self
+                .tokens
+                .get(self.pos)
+                .ok_or(FileRequirementParseError::UnexpectedEnd)?;
+            match (&token.kind, args.is_empty()) {
+                (TokenKind::Close, true) => {}
+                (TokenKind::Path(arg), _) => {
+                    args.push(arg.clone());
+                    self.pos += 1;
+                }
+                _ => return Err(token.unexpected()),
+            }
+            let token = self
+                .tokens
+                .get(self.pos)
+                .ok_or(FileRequirementParseError::UnexpectedEnd)?;
+            self.pos += 1;
+            match token.kind {
+                TokenKind::Close => return Ok(args),
+                TokenKind::Comma => {}
+                _ => return Err(token.unexpected()),
+            }
+        }
+    }
+
+    fn parse_call(
+        &mut self,
+        name: &str,
+        offset: usize,
+        args: Vec<String>,
+    ) -> Result<FileRequirement, FileRequirementParseError> {
+        let invalid = |message: &str| FileRequirementParseError::InvalidArguments {
+            name: name.to_string(),
+            offset,
+            message: message.to_string(),
+        };
+        match name {
+            "glob" => {
+                let [pattern, min_matches] = <[String; 2]>::try_from(args)
+                    .map_err(|_| invalid("expected a pattern and a minimum match count"))?;
+                let min_matches = min_matches
+                    .parse()
+                    .map_err(|_| invalid("minimum match count must be a non-negative integer"))?;
+                if let Err(e) = glob::Pattern::new(&pattern) {
+                    return Err(FileRequirementBuildError::InvalidGlob {
+                        pattern,
+                        message: e.to_string(),
+                    }
+                    .into());
+                }
+                self.insert_term(Path::new(&pattern))?;
+                Ok(FileRequirement::Glob {
+                    pattern,
+                    min_matches,
+                })
+            }
+            "glob_size" => {
+                let [pattern, min_bytes] = <[String; 2]>::try_from(args)
+                    .map_err(|_| invalid("expected a pattern and a minimum total size"))?;
+                let min_bytes = min_bytes
+                    .parse()
+                    .map_err(|_| invalid("minimum total size must be a byte count"))?;
+                if let Err(e) = glob::Pattern::new(&pattern) {
+                    return Err(FileRequirementBuildError::InvalidGlob {
+                        pattern,
+                        message: e.to_string(),
+                    }
+                    .into());
+                }
+                self.insert_term(Path::new(&pattern))?;
+                Ok(FileRequirement::GlobSize { pattern, min_bytes })
+            }
+            "shards" => {
+                let [template, range, min] = <[String; 3]>::try_from(args)
+                    .map_err(|_| invalid("expected a template, a range and a minimum count"))?;
+                let (start, end) = range
+                    .split_once("..")
+                    .and_then(|(start, end)| Some((start.parse().ok()?, end.parse().ok()?)))
+                    .ok_or_else(|| invalid("shard range must be written `START..END`"))?;
+                let min = min
+                    .parse()
+                    .map_err(|_| invalid("minimum count must be a non-negative integer"))?;
+                crate::shards::validate(&template, &(start..end), min)?;
+                self.insert_term(Path::new(&template))?;
+                Ok(FileRequirement::Shards {
+                    template,
+                    start,
+                    end,
+                    min,
+                })
+            }
+            "file" => {
+                let mut args = args.into_iter();
+                let path =
+                    PathBuf::from(args.next().ok_or_else(|| invalid("expected a file path"))?);
+                let mut constraints = FileConstraints::new();
+                for arg in args {
+                    constraints
+                        .apply_arg(&arg)
+                        .map_err(|message| invalid(&message))?;
+                }
+                self.insert_term(&path)?;
+                Ok(FileRequirement::FileWith { path, constraints })
+            }
+            "dir" | "non_empty_dir" => {
+                let [path] = <[String; 1]>::try_from(args)
+                    .map_err(|_| invalid("expected a single directory path"))?;
+                let path = PathBuf::from(path);
+                self.insert_term(&path)?;
+                Ok(FileRequirement::Dir {
+                    path,
+                    non_empty: name == "non_empty_dir",
+                })
+            }
+            "dir_entries" => {
+                let mut args = args.into_iter();
+                let (Some(path), Some(min_entries)) = (args.next(), args.next()) else {
+                    return Err(invalid(
+                        "expected a directory path and a minimum entry count",
+                    ));
+                };
+                let min_entries = min_entries
+                    .parse()
+                    .map_err(|_| invalid("minimum entry count must be a non-negative integer"))?;
+                let pattern = args.next();
+                if args.next().is_some() {
+                    return Err(invalid("expected at most one entry pattern"));
+                }
+                if let Some(pattern) = &pattern
+                    && let Err(e) = glob::Pattern::new(pattern)
+                {
+                    return Err(FileRequirementBuildError::InvalidGlob {
+                        pattern: pattern.clone(),
+                        message: e.to_string(),
+                    }
+                    .into());
+                }
+                let path = PathBuf::from(path);
+                self.insert_term(&path)?;
+                Ok(FileRequirement::DirEntries {
+                    path,
+                    pattern,
+                    min_entries,
+                })
+            }
+            "writable_dir" => {
+                let [path] = <[String; 1]>::try_from(args)
+                    .map_err(|_| invalid("expected a single directory path"))?;
+                let path = PathBuf::from(path);
+                self.insert_term(&path)?;
+                Ok(FileRequirement::WritableDir(path))
+            }
+            "free_space" => {
+                let [path, bytes] = <[String; 2]>::try_from(args)
+                    .map_err(|_| invalid("expected a path and a byte count"))?;
+                let bytes = bytes
+                    .parse()
+                    .map_err(|_| invalid("byte count must be a non-negative integer"))?;
+                Ok(FileRequirement::FreeSpace {
+                    path: PathBuf::from(path),
+                    bytes,
+                })
+            }
+            "same_filesystem" => {
+                let [path, other] =
+                    <[String; 2]>::try_from(args).map_err(|_| invalid("expected two paths"))?;
+                Ok(FileRequirement::SameFilesystem {
+                    path: PathBuf::from(path),
+                    other: PathBuf::from(other),
+                })
+            }
+            "env" => {
+                let mut args = args.into_iter();
+                let name = args
+                    .next()
+                    .ok_or_else(|| invalid("expected a variable name"))?;
+                crate::eval::check_env_var_name(&name)?;
+                let (mut non_empty, mut pattern) = (false, None);
+                for arg in args {
+                    if arg == "non_empty" {
+                        non_empty = true;
+                    } else if let Some(re) = arg.strip_prefix("matches=") {
+                        #[cfg(feature = "regex")]
+                        crate::eval::compile_anchored(re)?;
+                        pattern = Some(re.to_owned());
+                    } else {
+                        return Err(invalid(&format!("unknown env constraint `{}`", arg)));
+                    }
+                }
+                Ok(FileRequirement::EnvVar {
+                    name,
+                    non_empty,
+                    pattern,
+                })
+            }
+            "executable" => {
+                let [name] = <[String; 1]>::try_from(args)
+                    .map_err(|_| invalid("expected a single program name"))?;
+                let name = PathBuf::from(name);
+                self.insert_term(&name)?;
+                Ok(FileRequirement::Executable(name))
+            }
+            "archive_member" => {
+                let [archive, member] = <[String; 2]>::try_from(args)
+                    .map_err(|_| invalid("expected an archive path and a member path"))?;
+                let archive = PathBuf::from(archive);
+                self.seen_terms.claim_member(&archive, &member)?;
+                Ok(FileRequirement::ArchiveMember { archive, member })
+            }
+            "url" => {
+                let [url] =
+                    <[String; 1]>::try_from(args).map_err(|_| invalid("expected a single URL"))?;
+                self.seen_terms.claim(Path::new(&url))?;
+                Ok(FileRequirement::Url(url))
+            }
+            "recommend" => {
+                let [path] =
+                    <[String; 1]>::try_from(args).map_err(|_| invalid("expected a single path"))?;
+                let path = PathBuf::from(path);
+                self.insert_term(&path)?;
+                Ok(FileRequirement::Recommended(path))
+            }
+            "absent" => {
+                let [path] =
+                    <[String; 1]>::try_from(args).map_err(|_| invalid("expected a single path"))?;
+                let path = PathBuf::from(path);
+                self.insert_term(&path)?;
+                Ok(FileRequirement::Absent(path))
+            }
+            _ => Err(FileRequirementParseError::UnknownFunction {
+                name: name.to_string(),
+                offset,
+            }),
+        }
+    }
+}
+
+/// Write a path term, quoting it when the bare form would not parse back.
+pub(crate) fn write_path(f: &mut fmt::Formatter<'_>, path: &Path) -> fmt::Result {
+    let rendered = path.display().to_string();
+    let needs_quotes = rendered.is_empty()
+        || Operator::from_keyword(&rendered).is_some()
+        || rendered
+            .chars()
+            .any(|c| c.is_whitespace() || is_delimiter(c));
+    if !needs_quotes {
+        return f.write_str(&rendered);
+    }
+    write_quoted(f, &rendered)
+}
+
+/// Write a double-quoted string with `"` and `\` escaped.
+pub(crate) fn write_quoted(f: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result {
+    f.write_str("\"")?;
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            f.write_str("\\")?;
+        }
+        write!(f, "{}", c)?;
+    }
+    f.write_str("\"")
+}
+
+/// Displays a string double-quoted, as [`write_quoted`] writes it.
+pub(crate) struct DisplayQuoted<'a>(pub(crate) &'a str);
+
+impl fmt::Display for DisplayQuoted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_quoted(f, self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FileRequirementParseError;
+    use crate::{
+        Age, FileConstraints, FileRequirement, FileRequirementBuildError, FileRequirementBuilder,
+        Size,
+    };
+    use std::time::Duration;
+
+    #[test]
+    fn display_output_parses_back_to_the_same_tree() {
+        let mut b = FileRequirementBuilder::new();
+        b.require_file("idx.ctab").unwrap();
+        b.require_file("my index (v2).bin").unwrap();
+        b.require_file("OR").unwrap();
+        b.require_any(|any| {
+            any.require_file("idx.sshash")?;
+            any.require_exactly_one(|one| {
+                one.require_file(r#"quoted "name".ssi"#)?;
+                one.require_file("idx.ssi.gz")?;
+                Ok(())
+            })?;
+            Ok(())
+        })
+        .unwrap();
+        b.require_glob("reads/*,v2.fastq.gz", 2).unwrap();
+        b.require_non_empty_dir("out dir").unwrap();
+        b.require_file_with(
+            "idx.refinfo",
+            FileConstraints::new()
+                .regular_file()
+                .size(Size::Between(1, 64))
+                .age(Age::AtMost(Duration::from_millis(1500)))
+                .newer_than("ref genome.fa"),
+        )
+        .unwrap();
+        let req = b.build();
+
+        let rendered = req.to_string();
+        assert_eq!(
+            rendered,
+            r#"(idx.ctab AND "my index (v2).bin" AND "OR" AND (idx.sshash OR ("quoted \"name\".ssi" XOR idx.ssi.gz)) AND glob("reads/*,v2.fastq.gz", 2) AND non_empty_dir("out dir") AND file(idx.refinfo, regular, size=1..64, age<=1.500000000s, "newer_than=ref genome.fa"))"#
+        );
+        let parsed: FileRequirement = rendered.parse().unwrap();
+        assert_eq!(parsed, req);
+    }
+
+    #[test]
+    fn parser_accepts_bare_top_level_and_rejects_mixed_operators() {
+        let parsed: FileRequirement = "a.txt AND (b.txt OR c.txt)".parse().unwrap();
+        assert_eq!(parsed.to_string(), "(a.txt AND (b.txt OR c.txt))");
+        assert_eq!(
+            "a.txt".parse::<FileRequirement>().unwrap(),
+            FileRequirement::File("a.txt".into())
+        );
+
+        let err = "(a.txt AND b.txt OR c.txt)"
+            .parse::<FileRequirement>()
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            FileRequirementParseError::MixedOperators { offset: 17, .. }
+        ));
+
+        let err = "(a.txt AND frob(b.txt))"
+            .parse::<FileRequirement>()
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            FileRequirementParseError::UnknownFunction { offset: 11, .. }
+        ));
+
+        let err = "(a.txt OR a.txt)".parse::<FileRequirement>().unwrap_err();
+        assert!(matches!(
+            err,
+            FileRequirementParseError::Build(FileRequirementBuildError::DuplicateFile { .. })
+        ));
+    }
+
+    #[test]
+    fn alternate_display_is_indented_and_parses_back() {
+        let req: FileRequirement =
+            r#"a.txt AND named("index format", (b.txt OR (c.txt AND d.txt)))"#
+                .parse()
+                .unwrap();
+        let pretty = format!("{:#}", req);
+        assert_eq!(
+            pretty,
+            "(\n    a.txt\n    AND named(\"index format\", (\n        b.txt\n        OR (\n            c.txt\n            AND d.txt\n        )\n    ))\n)"
+        );
+        assert_eq!(pretty.parse::<FileRequirement>().unwrap(), req);
+    }
+}
@@ -0,0 +1,76 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{CheckReport, FileRequirement};
+
+impl FileRequirement {
+    /// Re-check this requirement expression every `interval` until it is
+    /// satisfied or `timeout` elapses, returning the last report.
+    ///
+    /// Unlike watching for changes, polling works on filesystems without change
+    /// notifications, such as NFS or FUSE mounts. Check the returned report's
+    /// [`is_satisfied`](CheckReport::is_satisfied) to tell success from a
+    /// timeout.
+    pub fn poll_until_satisfied(&self, interval: Duration, timeout: Duration) -> CheckReport {
+        self.poll_until_satisfied_with(interval, timeout, |_| {})
+    }
+
+    /// Like [`FileRequirement::poll_until_satisfied`], calling `on_round` with
+    /// the report of every round, including the last.
+    pub fn poll_until_satisfied_with<F>(
+        &self,
+        interval: Duration,
+        timeout: Duration,
+        mut on_round: F,
+    ) -> CheckReport
+    where
+        F: FnMut(&CheckReport),
+    {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let report = self.check_report();
+            on_round(&report);
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if report.is_satisfied() || remaining.is_zero() {
+                return report;
+            }
+            thread::sleep(interval.min(remaining));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FileRequirementBuilder;
+    use std::fs;
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    #[test]
+    fn polling_reports_each_round_until_satisfied() {
+        let td = tempdir().unwrap();
+        let path = td.path().join("done.flag");
+        let mut b = FileRequirementBuilder::new();
+        b.require_file(&path).unwrap();
+        let req = b.build();
+
+        let mut rounds = 0;
+        let report = req.poll_until_satisfied_with(
+            Duration::from_millis(1),
+            Duration::from_secs(30),
+            |report| {
+                rounds += 1;
+                assert_eq!(report.is_satisfied(), rounds > 3);
+                if rounds == 3 {
+                    fs::write(&path, "").unwrap();
+                }
+            },
+        );
+        assert!(report.is_satisfied());
+        assert_eq!(rounds, 4);
+
+        fs::remove_file(td.path().join("done.flag")).unwrap();
+        let report = req.poll_until_satisfied(Duration::from_millis(1), Duration::from_millis(5));
+        assert!(!report.is_satisfied());
+    }
+}
@@ -0,0 +1,128 @@
+use std::path::{Path, PathBuf};
+
+use crate::{CheckOptions, CheckReport, FileRequirement, FileRequirementCheckError};
+
+/// A combined preflight check: file requirements plus environment variables,
+/// programs on `PATH` and free disk space, evaluated as one conjunction with a
+/// single report.
+///
+/// ```no_run
+/// use file_requirements::{FileRequirement, Preflight};
+///
+/// let index: FileRequirement = "(idx.ctab AND (idx.sshash OR idx.ssi))".parse()?;
+/// Preflight::new()
+///     .files(index)
+///     .non_empty_env_var("REF_DIR")
+///     .executable("samtools")
+///     .free_space("out", 10 << 30)
+///     .check()?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Preflight {
+    terms: Vec<FileRequirement>,
+    options: CheckOptions,
+}
+
+impl Preflight {
+    /// An empty preflight check, which is trivially satisfied.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require a file requirement expression to be satisfied.
+    pub fn files(mut self, requirement: FileRequirement) -> Self {
+        self.terms.push(requirement);
+        self
+    }
+
+    /// Require the environment variable `name` to be set.
+    pub fn env_var<S: Into<String>>(mut self, name: S) -> Self {
+        self.terms.push(FileRequirement::EnvVar {
+            name: name.into(),
+            non_empty: false,
+            pattern: None,
+        });
+        self
+    }
+
+    /// Require the environment variable `name` to be set to a non-empty value.
+    pub fn non_empty_env_var<S: Into<String>>(mut self, name: S) -> Self {
+        self.terms.push(FileRequirement::EnvVar {
+            name: name.into(),
+            non_empty: true,
+            pattern: None,
+        });
+        self
+    }
+
+    /// Require the program `name` to be found on `PATH`.
+    pub fn executable<P: AsRef<Path>>(mut self, name: P) -> Self {
+        self.terms
+            .push(FileRequirement::Executable(name.as_ref().to_path_buf()));
+        self
+    }
+
+    /// Require at least `bytes` of free space on the filesystem containing `path`.
+    pub fn free_space<P: AsRef<Path>>(mut self, path: P, bytes: u64) -> Self {
+        self.terms.push(FileRequirement::FreeSpace {
+            path: PathBuf::from(path.as_ref()),
+            bytes,
+        });
+        self
+    }
+
+    /// Check with `options` instead of the defaults.
+    pub fn options(mut self, options: CheckOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// The combined requirement expression: an `AND` of every added check.
+    pub fn requirement(&self) -> FileRequirement {
+        FileRequirement::All(self.terms.clone())
+    }
+
+    /// Evaluate every check and return the combined report.
+    pub fn check_report(&self) -> CheckReport {
+        self.requirement().check_report_with_options(&self.options)
+    }
+
+    /// Evaluate every check, failing with one message covering all problems.
+    pub fn check(&self) -> Result<(), FileRequirementCheckError> {
+        self.requirement().check_with_options(&self.options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Preflight;
+    use crate::{FileRequirement, NodeReport};
+    use tempfile::tempdir;
+
+    #[test]
+    fn preflight_combines_all_checks_into_one_report() {
+        let td = tempdir().unwrap();
+        std::fs::write(td.path().join("idx.ctab"), "").unwrap();
+        let index: FileRequirement = "(idx.ctab AND idx.ssi)".parse().unwrap();
+
+        let preflight = Preflight::new()
+            .files(index)
+            .env_var("FILE_REQUIREMENTS_UNSET_TEST_VAR")
+            .executable("file-requirements-no-such-tool")
+            .options(crate::CheckOptions::new().base_dir(td.path()));
+        let report = preflight.check_report();
+        let NodeReport::Group(root) = report.root() else {
+            panic!("preflight root is a group");
+        };
+        assert_eq!(root.children().len(), 3);
+
+        let message = preflight.check().unwrap_err().to_string();
+        assert!(message.contains(&format!(
+            "missing files: {}",
+            td.path().join("idx.ssi").display()
+        )));
+        assert!(message.contains("executables not found on PATH: file-requirements-no-such-tool"));
+        assert!(message.contains("unset environment variables: $FILE_REQUIREMENTS_UNSET_TEST_VAR"));
+    }
+}
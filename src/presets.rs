@@ -0,0 +1,234 @@
+use std::path::Path;
+
+use crate::{FileConstraints, FileRequirement, with_suffix};
+
+/// Layout versions of a salmon index directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SalmonIndex {
+    /// The quasi-mapping index of salmon 0.x (`hash.bin`, `sa.bin`, ...).
+    V0,
+    /// The pufferfish-based index of salmon 1.x, dense or sparse.
+    V1,
+}
+
+/// Layout versions of a piscem index, which is a set of files sharing a
+/// prefix rather than a directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PiscemIndex {
+    /// A k-mer dictionary in one `{prefix}.sshash` file.
+    Sshash,
+    /// A k-mer dictionary split into `{prefix}.ssi` and `{prefix}.ssi.mphf`.
+    Ssi,
+    /// Either dictionary layout.
+    AnyFormat,
+}
+
+/// Files of a pufferfish index besides its position table.
+const PUFFERFISH_FILES: &[&str] = &[
+    "info.json",
+    "ctable.bin",
+    "ctg_offsets.bin",
+    "mphf.bin",
+    "rank.bin",
+    "refAccumLengths.bin",
+    "reflengths.bin",
+    "refseq.bin",
+    "seq.bin",
+    "complete_ref_lens.bin",
+];
+
+/// Position tables of a sparse pufferfish index.
+const PUFFERFISH_SPARSE_FILES: &[&str] = &[
+    "presence.bin",
+    "sample_pos.bin",
+    "extension.bin",
+    "direction.bin",
+];
+
+/// Files of a salmon 0.x quasi-mapping index.
+const SALMON_V0_FILES: &[&str] = &[
+    "header.json",
+    "versionInfo.json",
+    "hash.bin",
+    "sa.bin",
+    "txpInfo.bin",
+    "rsd.bin",
+    "refInfo.json",
+];
+
+/// Files of an alevin-fry quantification directory, with the names reported
+/// when they are missing or empty.
+const QUANT_FILES: &[(&str, &str)] = &[
+    ("quants matrix", "alevin/quants_mat.mtx"),
+    ("barcodes", "alevin/quants_mat_rows.txt"),
+    ("features", "alevin/quants_mat_cols.txt"),
+    ("quantification metadata", "quant.json"),
+];
+
+/// Files of a piscem index besides its k-mer dictionary.
+const PISCEM_EXTENSIONS: &[&str] = &["ctab", "ectab", "refinfo", "sigs.json"];
+
+fn files_in(dir: &Path, names: &[&str]) -> Vec<FileRequirement> {
+    names
+        .iter()
+        .map(|name| FileRequirement::File(dir.join(name)))
+        .collect()
+}
+
+fn with_extensions(prefix: &Path, extensions: &[&str]) -> Vec<FileRequirement> {
+    extensions
+        .iter()
+        .map(|extension| FileRequirement::File(with_suffix(prefix, extension)))
+        .collect()
+}
+
+fn pufferfish_terms(dir: &Path) -> Vec<FileRequirement> {
+    let mut terms = files_in(dir, PUFFERFISH_FILES);
+    terms.push(named(
+        "pufferfish position table",
+        FileRequirement::Any(vec![
+            FileRequirement::File(dir.join("pos.bin")),
+            FileRequirement::All(files_in(dir, PUFFERFISH_SPARSE_FILES)),
+        ]),
+    ));
+    terms
+}
+
+fn named(name: &str, term: FileRequirement) -> FileRequirement {
+    FileRequirement::Named {
+        name: name.to_owned(),
+        term: Box::new(term),
+    }
+}
+
+impl FileRequirement {
+    /// The files of a pufferfish index in `dir`, with either the dense
+    /// (`pos.bin`) or the sparse position tables.
+    pub fn pufferfish_index<P: AsRef<Path>>(dir: P) -> FileRequirement {
+        FileRequirement::All(pufferfish_terms(dir.as_ref())).with_hint("run `pufferfish index`")
+    }
+
+    /// The files of a salmon index in `dir`, in the layout of `version`.
+    ///
+    /// ```
+    /// use file_requirements::{FileRequirement, SalmonIndex};
+    ///
+    /// let index = FileRequirement::salmon_index("gencode_v44_idx", SalmonIndex::V1);
+    /// assert!(index.paths().iter().any(|p| p.ends_with("versionInfo.json")));
+    /// ```
+    pub fn salmon_index<P: AsRef<Path>>(dir: P, version: SalmonIndex) -> FileRequirement {
+        let dir = dir.as_ref();
+        let index = match version {
+            SalmonIndex::V0 => FileRequirement::All(files_in(dir, SALMON_V0_FILES)),
+            SalmonIndex::V1 => {
+                let mut terms = files_in(dir, &["versionInfo.json", "duplicate_clusters.tsv"]);
+                terms.extend(pufferfish_terms(dir));
+                FileRequirement::All(terms)
+            }
+        };
+        index.with_hint("run `salmon index`")
+    }
+
+    /// The files of a piscem index sharing `prefix`, such as `idx.ctab` and
+    /// `idx.sshash` for the prefix `idx`, in the layout of `version`.
+    pub fn piscem_index<P: AsRef<Path>>(prefix: P, version: PiscemIndex) -> FileRequirement {
+        let prefix = prefix.as_ref();
+        let sshash = || FileRequirement::File(with_suffix(prefix, "sshash"));
+        let ssi = || FileRequirement::All(with_extensions(prefix, &["ssi", "ssi.mphf"]));
+        let mut terms = with_extensions(prefix, PISCEM_EXTENSIONS);
+        terms.push(match version {
+            PiscemIndex::Sshash => sshash(),
+            PiscemIndex::Ssi => ssi(),
+            PiscemIndex::AnyFormat => named(
+                "piscem dictionary",
+                FileRequirement::Any(vec![sshash(), ssi()]),
+            ),
+        });
+        FileRequirement::All(terms).with_hint("run `piscem build`")
+    }
+
+    /// The files of a complete alevin-fry quantification in `dir`: the quants
+    /// matrix, its barcode and feature lists, and the `quant.json` metadata.
+    ///
+    /// Each file must be non-empty, so the same requirement serves downstream
+    /// tools reading `dir` and, checked after `alevin-fry quant`, confirms the
+    /// run wrote all of its output rather than stopping part way.
+    pub fn quant_output<P: AsRef<Path>>(dir: P) -> FileRequirement {
+        let dir = dir.as_ref();
+        let terms = QUANT_FILES
+            .iter()
+            .map(|(name, file)| {
+                named(
+                    name,
+                    FileRequirement::FileWith {
+                        path: dir.join(file),
+                        constraints: FileConstraints::new().non_empty(),
+                    },
+                )
+            })
+            .collect();
+        FileRequirement::All(terms).with_hint("run `alevin-fry quant`")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn sparse_salmon_indices_satisfy_the_v1_layout() {
+        let td = tempdir().unwrap();
+        let names = PUFFERFISH_FILES
+            .iter()
+            .chain(PUFFERFISH_SPARSE_FILES)
+            .chain(&["versionInfo.json", "duplicate_clusters.tsv"]);
+        for name in names {
+            fs::write(td.path().join(name), "").unwrap();
+        }
+
+        assert!(
+            FileRequirement::salmon_index(td.path(), SalmonIndex::V1)
+                .check()
+                .is_ok()
+        );
+        let err = FileRequirement::salmon_index(td.path(), SalmonIndex::V0)
+            .check()
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("hint: run `salmon index`"));
+    }
+
+    #[test]
+    fn empty_quant_files_leave_the_output_incomplete() {
+        let td = tempdir().unwrap();
+        fs::create_dir(td.path().join("alevin")).unwrap();
+        for (_, file) in QUANT_FILES {
+            fs::write(td.path().join(file), "x").unwrap();
+        }
+        let req = FileRequirement::quant_output(td.path());
+        assert!(req.check().is_ok());
+
+        fs::write(td.path().join("alevin/quants_mat.mtx"), "").unwrap();
+        let err = req.check().unwrap_err().to_string();
+        assert!(err.contains("quants matrix"));
+        assert!(err.contains("hint: run `alevin-fry quant`"));
+    }
+
+    #[test]
+    fn piscem_layouts_differ_in_their_dictionary() {
+        let render = |version| FileRequirement::piscem_index("idx", version).to_string();
+        assert_eq!(
+            render(PiscemIndex::Ssi),
+            r#"hint("run `piscem build`", (idx.ctab AND idx.ectab AND idx.refinfo AND idx.sigs.json AND (idx.ssi AND idx.ssi.mphf)))"#
+        );
+        assert!(
+            render(PiscemIndex::AnyFormat).contains(
+                r#"named("piscem dictionary", (idx.sshash OR (idx.ssi AND idx.ssi.mphf)))"#
+            )
+        );
+    }
+}
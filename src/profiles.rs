@@ -0,0 +1,279 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::scheme::SeenTerms;
+use crate::{FileRequirement, FileRequirementBuildError, FileRequirementParseError};
+
+/// A set of named requirement profiles read from a TOML spec, so one file can
+/// describe the inputs of every mode a tool supports.
+///
+/// Each profile lists its terms under `requires`, either as an expression in
+/// the textual syntax or in the `serde` form, and may name the profiles it
+/// builds on under `extends`:
+///
+/// ```
+/// use file_requirements::Profiles;
+///
+/// let profiles = Profiles::from_toml(r#"
+///     [profiles.common]
+///     requires = "reads/r1.fq.gz"
+///
+///     [profiles.sshash]
+///     description = "piscem sshash index"
+///     extends = ["common"]
+///     requires = "(idx.sshash AND idx.ctab)"
+/// "#).unwrap();
+/// let req = profiles.select(&["sshash"]).unwrap();
+/// assert_eq!(
+///     req.to_string(),
+///     r#"(named("common", reads/r1.fq.gz) AND named("sshash", (idx.sshash AND idx.ctab)))"#
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Profiles {
+    profiles: BTreeMap<String, Profile>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Profile {
+    description: Option<String>,
+    extends: Vec<String>,
+    requires: Option<FileRequirement>,
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(deny_unknown_fields)]
+struct SpecFile {
+    #[serde(default)]
+    profiles: BTreeMap<String, ProfileSpec>,
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(deny_unknown_fields)]
+struct ProfileSpec {
+    description: Option<String>,
+    #[serde(default)]
+    extends: Vec<String>,
+    requires: Option<Requires>,
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(untagged)]
+enum Requires {
+    Expression(String),
+    Tree(FileRequirement),
+}
+
+/// Errors produced while loading profiles or selecting among them.
+#[derive(Debug, Error)]
+pub enum ProfileError {
+    /// The spec file could not be read.
+    #[error("Failed to read `{path}`: {source}")]
+    Io {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+    /// The spec is not valid TOML, or not in the profile layout.
+    #[error("Invalid profile spec: {0}")]
+    Toml(#[from] toml::de::Error),
+    /// A profile's `requires` expression does not parse.
+    #[error("Invalid requirement in profile `{profile}`: {source}")]
+    Parse {
+        profile: String,
+        #[source]
+        source: FileRequirementParseError,
+    },
+    /// A selected or extended profile is not defined.
+    #[error("Unknown profile `{name}` (available: {available})")]
+    UnknownProfile { name: String, available: String },
+    /// A profile extends itself, directly or through others.
+    #[error("Profile `{name}` extends itself")]
+    Cycle { name: String },
+    /// The selected profiles cannot be combined, for instance because two of
+    /// them require the same path.
+    #[error("Selected profiles conflict: {0}")]
+    Conflict(#[source] FileRequirementBuildError),
+}
+
+impl Profiles {
+    /// Parse a TOML spec with a `[profiles.<name>]` table per profile.
+    pub fn from_toml(text: &str) -> Result<Self, ProfileError> {
+        let spec: SpecFile = toml::from_str(text)?;
+        let mut profiles = BTreeMap::new();
+        for (name, spec) in spec.profiles {
+            let requires = match spec.requires {
+                None => None,
+                Some(Requires::Tree(tree)) => Some(tree),
+                Some(Requires::Expression(text)) => {
+                    Some(text.trim().parse().map_err(|source| ProfileError::Parse {
+                        profile: name.clone(),
+                        source,
+                    })?)
+                }
+            };
+            let profile = Profile {
+                description: spec.description,
+                extends: spec.extends,
+                requires,
+            };
+            profiles.insert(name, profile);
+        }
+        Ok(Self { profiles })
+    }
+
+    /// Read and parse the TOML spec at `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ProfileError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|source| ProfileError::Io {
+            path: path.display().to_string(),
+            source,
+        })?;
+        Self::from_toml(&text)
+    }
+
+    /// The JSON Schema of the profile spec layout, for validating spec files
+    /// (as TOML read into JSON) before loading them.
+    #[cfg(feature = "schemars")]
+    pub fn json_schema() -> schemars::Schema {
+        schemars::schema_for!(SpecFile)
+    }
+
+    /// The profile names, in sorted order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.profiles.keys().map(String::as_str)
+    }
+
+    /// The `description` of the profile `name`, if it has one.
+    pub fn description(&self, name: &str) -> Option<&str> {
+        self.profiles.get(name)?.description.as_deref()
+    }
+
+    /// The requirement of the profiles `names` together with everything they
+    /// extend, each named after its profile.
+    ///
+    /// A profile reached more than once is included once, after the profiles
+    /// it extends. The combined expression follows the builder's rules, so
+    /// selecting profiles that require the same path is an error.
+    pub fn select(&self, names: &[&str]) -> Result<FileRequirement, ProfileError> {
+        let mut order = Vec::new();
+        for name in names {
+            self.visit(name, &mut Vec::new(), &mut order)?;
+        }
+        let terms: Vec<FileRequirement> = order
+            .into_iter()
+            .filter_map(|name| {
+                let term = self.profiles[name].requires.clone()?;
+                Some(FileRequirement::Named {
+                    name: name.to_owned(),
+                    term: Box::new(term),
+                })
+            })
+            .collect();
+        let combined = FileRequirement::All(terms);
+        SeenTerms::default()
+            .claim_tree(&combined)
+            .map_err(ProfileError::Conflict)?;
+        Ok(combined)
+    }
+
+    fn visit<'a>(
+        &'a self,
+        name: &str,
+        path: &mut Vec<&'a str>,
+        order: &mut Vec<&'a str>,
+    ) -> Result<(), ProfileError> {
+        let Some((name, profile)) = self.profiles.get_key_value(name) else {
+            return Err(ProfileError::UnknownProfile {
+                name: name.to_owned(),
+                available: self.names().collect::<Vec<_>>().join(", "),
+            });
+        };
+        if path.contains(&name.as_str()) {
+            return Err(ProfileError::Cycle { name: name.clone() });
+        }
+        if order.contains(&name.as_str()) {
+            return Ok(());
+        }
+        path.push(name);
+        for parent in &profile.extends {
+            self.visit(parent, path, order)?;
+        }
+        path.pop();
+        order.push(name);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: &str = r#"
+        [profiles.common]
+        requires = "(reads/r1.fq.gz AND reads/r2.fq.gz)"
+
+        [profiles.sshash]
+        extends = ["common"]
+        requires = "(idx.sshash AND idx.ctab)"
+
+        [profiles.ssi]
+        extends = ["common"]
+        requires = { all = [{ file = "idx.ssi" }, { file = "idx.ctab" }] }
+
+        [profiles.loop]
+        extends = ["loop"]
+    "#;
+
+    #[test]
+    fn selected_profiles_include_what_they_extend_once() {
+        let profiles = Profiles::from_toml(SPEC).unwrap();
+        assert_eq!(
+            profiles.names().collect::<Vec<_>>(),
+            ["common", "loop", "sshash", "ssi"]
+        );
+        let req = profiles.select(&["common", "sshash"]).unwrap();
+        assert_eq!(
+            req.to_string(),
+            r#"(named("common", (reads/r1.fq.gz AND reads/r2.fq.gz)) AND named("sshash", (idx.sshash AND idx.ctab)))"#
+        );
+    }
+
+    #[test]
+    fn unknown_cyclic_and_conflicting_selections_are_rejected() {
+        let profiles = Profiles::from_toml(SPEC).unwrap();
+        assert!(matches!(
+            profiles.select(&["kallisto"]),
+            Err(ProfileError::UnknownProfile { .. })
+        ));
+        assert!(matches!(
+            profiles.select(&["loop"]),
+            Err(ProfileError::Cycle { .. })
+        ));
+        assert!(matches!(
+            profiles.select(&["sshash", "ssi"]),
+            Err(ProfileError::Conflict(
+                FileRequirementBuildError::DuplicateFile { .. }
+            ))
+        ));
+        assert!(matches!(
+            Profiles::from_toml("[profiles.bad]\nrequires = \"(a AND\"\n"),
+            Err(ProfileError::Parse { .. })
+        ));
+
+        // Trees the textual syntax would not accept back still combine.
+        let profiles = Profiles::from_toml(
+            "[profiles.remote]\nrequires = { file = \"s3://refs/genome.fa\" }\n",
+        )
+        .unwrap();
+        assert!(profiles.select(&["remote"]).is_ok());
+    }
+}
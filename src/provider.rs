@@ -0,0 +1,651 @@
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashSet};
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Type of an existing filesystem entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum FileKind {
+    /// A regular file.
+    File,
+    /// A directory.
+    Dir,
+    /// A symbolic link that was not followed.
+    Symlink,
+    /// Any other entry type (socket, device, ...).
+    Other,
+}
+
+/// Metadata of an existing path, as reported by a [`FileProvider`].
+#[derive(Debug, Clone)]
+pub struct FileMetadata {
+    kind: FileKind,
+    len: u64,
+    modified: Option<SystemTime>,
+    owner: Option<(u32, u32)>,
+    device: Option<u64>,
+}
+
+impl FileMetadata {
+    /// Metadata for an entry of the given kind, with zero length and no
+    /// modification time.
+    pub fn new(kind: FileKind) -> Self {
+        Self {
+            kind,
+            len: 0,
+            modified: None,
+            owner: None,
+            device: None,
+        }
+    }
+
+    /// Set the length in bytes.
+    pub fn with_len(mut self, len: u64) -> Self {
+        self.len = len;
+        self
+    }
+
+    /// Set the last modification time.
+    pub fn with_modified(mut self, modified: SystemTime) -> Self {
+        self.modified = Some(modified);
+        self
+    }
+
+    /// Set the owning user and group ids.
+    pub fn with_owner(mut self, uid: u32, gid: u32) -> Self {
+        self.owner = Some((uid, gid));
+        self
+    }
+
+    /// Set the id of the device (filesystem) holding the entry.
+    pub fn with_device(mut self, device: u64) -> Self {
+        self.device = Some(device);
+        self
+    }
+
+    /// The entry type.
+    pub fn kind(&self) -> FileKind {
+        self.kind
+    }
+
+    /// Whether the entry is a directory.
+    pub fn is_dir(&self) -> bool {
+        self.kind == FileKind::Dir
+    }
+
+    /// Whether the entry is a regular file.
+    pub fn is_file(&self) -> bool {
+        self.kind == FileKind::File
+    }
+
+    /// The length in bytes.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Whether the length is zero.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The last modification time, if known.
+    pub fn modified(&self) -> Option<SystemTime> {
+        self.modified
+    }
+
+    /// The owning `(uid, gid)`, if known.
+    pub fn owner(&self) -> Option<(u32, u32)> {
+        self.owner
+    }
+
+    /// The id of the device holding the entry, if known; paths on the same
+    /// filesystem share it. Reported by [`StdFs`] on Unix.
+    pub fn device(&self) -> Option<u64> {
+        self.device
+    }
+}
+
+impl From<&fs::Metadata> for FileMetadata {
+    fn from(meta: &fs::Metadata) -> Self {
+        let file_type = meta.file_type();
+        let kind = if file_type.is_file() {
+            FileKind::File
+        } else if file_type.is_dir() {
+            FileKind::Dir
+        } else if file_type.is_symlink() {
+            FileKind::Symlink
+        } else {
+            FileKind::Other
+        };
+        let converted = FileMetadata::new(kind).with_len(meta.len());
+        #[cfg(unix)]
+        let converted = {
+            use std::os::unix::fs::MetadataExt;
+            converted
+                .with_owner(meta.uid(), meta.gid())
+                .with_device(meta.dev())
+        };
+        match meta.modified() {
+            Ok(modified) => converted.with_modified(modified),
+            Err(_) => converted,
+        }
+    }
+}
+
+/// A permission the current user may hold on an existing path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Access {
+    /// Contents can be read (entries listed, for directories).
+    Read,
+    /// Contents can be modified.
+    Write,
+    /// The file can be executed (directory searched).
+    Execute,
+}
+
+impl Access {
+    /// Every access kind, in declaration order.
+    pub const ALL: [Access; 3] = [Access::Read, Access::Write, Access::Execute];
+
+    pub(crate) fn adjective(self) -> &'static str {
+        match self {
+            Access::Read => "readable",
+            Access::Write => "writable",
+            Access::Execute => "executable",
+        }
+    }
+}
+
+fn unsupported(what: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("{} not supported by this file provider", what),
+    )
+}
+
+/// Source of truth for file term existence during evaluation.
+///
+/// Implement this to check requirements against an in-memory or mock filesystem.
+pub trait FileProvider {
+    /// Whether `path` exists, following the same contract as [`Path::try_exists`].
+    fn try_exists(&self, path: &Path) -> io::Result<bool>;
+
+    /// Existing paths matching a glob `pattern`.
+    ///
+    /// The default implementation reports [`io::ErrorKind::Unsupported`].
+    fn glob(&self, pattern: &str) -> io::Result<Vec<PathBuf>> {
+        let _ = pattern;
+        Err(unsupported("glob terms are"))
+    }
+
+    /// Metadata of `path`, following symlinks.
+    ///
+    /// Missing paths must report [`io::ErrorKind::NotFound`]. The default
+    /// implementation reports [`io::ErrorKind::Unsupported`].
+    fn metadata(&self, path: &Path) -> io::Result<FileMetadata> {
+        let _ = path;
+        Err(unsupported("metadata is"))
+    }
+
+    /// Metadata of `path` itself, without following a final symlink.
+    ///
+    /// The default implementation defers to [`FileProvider::metadata`], which
+    /// suits providers without symlinks.
+    fn symlink_metadata(&self, path: &Path) -> io::Result<FileMetadata> {
+        self.metadata(path)
+    }
+
+    /// Entries of the directory at `path`.
+    ///
+    /// The default implementation reports [`io::ErrorKind::Unsupported`].
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let _ = path;
+        Err(unsupported("directory listing is"))
+    }
+
+    /// Open the file at `path` for reading its contents.
+    ///
+    /// The default implementation reports [`io::ErrorKind::Unsupported`].
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read + '_>> {
+        let _ = path;
+        Err(unsupported("reading file contents is"))
+    }
+
+    /// Bytes available to unprivileged users on the filesystem containing the
+    /// existing `path`.
+    ///
+    /// The default implementation reports [`io::ErrorKind::Unsupported`].
+    fn available_space(&self, path: &Path) -> io::Result<u64> {
+        let _ = path;
+        Err(unsupported("free space queries are"))
+    }
+
+    /// Whether the current user holds `access` on the existing `path`.
+    ///
+    /// The default implementation reports [`io::ErrorKind::Unsupported`].
+    fn access(&self, path: &Path, access: Access) -> io::Result<bool> {
+        let _ = (path, access);
+        Err(unsupported("permission checks are"))
+    }
+
+    /// Whether the `http://` or `https://` `url` answers a `HEAD` request
+    /// successfully.
+    ///
+    /// The default implementation reports [`io::ErrorKind::Unsupported`].
+    fn url_exists(&self, url: &str) -> io::Result<bool> {
+        let _ = url;
+        Err(unsupported("URL checks are"))
+    }
+
+    /// Whether the archive at `archive` contains `member`.
+    ///
+    /// With the `archive` feature, the default implementation reads the archive
+    /// through [`FileProvider::open`]; otherwise it reports
+    /// [`io::ErrorKind::Unsupported`].
+    fn archive_contains(&self, archive: &Path, member: &str) -> io::Result<bool> {
+        #[cfg(feature = "archive")]
+        {
+            crate::archive::contains(self, archive, member)
+        }
+        #[cfg(not(feature = "archive"))]
+        {
+            let _ = (archive, member);
+            Err(unsupported("archive member checks are"))
+        }
+    }
+
+    /// Forget anything cached about `path`, so the next query sees it afresh.
+    ///
+    /// Called between samples of constraints that watch a file change. The
+    /// default implementation does nothing, which suits uncached providers.
+    fn invalidate(&self, path: &Path) {
+        let _ = path;
+    }
+}
+
+/// [`FileProvider`] backed by the local filesystem through `std::fs`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdFs;
+
+impl FileProvider for StdFs {
+    fn try_exists(&self, path: &Path) -> io::Result<bool> {
+        path.try_exists()
+    }
+
+    fn glob(&self, pattern: &str) -> io::Result<Vec<PathBuf>> {
+        let paths = glob::glob(pattern)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+        paths.map(|entry| entry.map_err(io::Error::from)).collect()
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<FileMetadata> {
+        fs::metadata(path).map(|meta| FileMetadata::from(&meta))
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> io::Result<FileMetadata> {
+        fs::symlink_metadata(path).map(|meta| FileMetadata::from(&meta))
+    }
+
+    /// Queried with `statvfs` when the `free-space` feature is enabled on Unix;
+    /// otherwise reports [`io::ErrorKind::Unsupported`].
+    fn available_space(&self, path: &Path) -> io::Result<u64> {
+        #[cfg(all(feature = "free-space", unix))]
+        {
+            statvfs_available(path)
+        }
+        #[cfg(not(all(feature = "free-space", unix)))]
+        {
+            let _ = path;
+            Err(unsupported("free space queries are"))
+        }
+    }
+
+    /// Sent with `ureq` when the `http` feature is enabled; otherwise reports
+    /// [`io::ErrorKind::Unsupported`]. `404` and `410` responses mean the URL
+    /// does not exist.
+    fn url_exists(&self, url: &str) -> io::Result<bool> {
+        #[cfg(feature = "http")]
+        {
+            match ureq::head(url).call() {
+                Ok(_) => Ok(true),
+                Err(ureq::Error::StatusCode(404 | 410)) => Ok(false),
+                Err(e) => Err(e.into_io()),
+            }
+        }
+        #[cfg(not(feature = "http"))]
+        {
+            let _ = url;
+            Err(unsupported("URL checks are"))
+        }
+    }
+
+    /// Zip archives are opened directly, so only their central directory is
+    /// read.
+    #[cfg(feature = "archive")]
+    fn archive_contains(&self, archive: &Path, member: &str) -> io::Result<bool> {
+        crate::archive::file_contains(archive, member)
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect()
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read + '_>> {
+        Ok(Box::new(fs::File::open(path)?))
+    }
+
+    /// Read and write access are probed by opening the path (without truncating
+    /// or creating it), and write access to a directory by creating and removing
+    /// a temporary file in it; execute access is approximated from the Unix mode
+    /// bits, or from the file extension on other platforms.
+    fn access(&self, path: &Path, access: Access) -> io::Result<bool> {
+        let meta = fs::metadata(path)?;
+        let probe = match access {
+            Access::Read if meta.is_dir() => fs::read_dir(path).map(drop),
+            Access::Read => fs::File::open(path).map(drop),
+            Access::Write if meta.is_dir() => probe_dir_write(path),
+            Access::Write => fs::OpenOptions::new().write(true).open(path).map(drop),
+            Access::Execute => return Ok(is_executable(path, &meta)),
+        };
+        match probe {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == io::ErrorKind::PermissionDenied => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(all(feature = "free-space", unix))]
+fn statvfs_available(path: &Path) -> io::Result<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+    // SAFETY: `c_path` is NUL-terminated and `stat` is valid for writes.
+    if unsafe { libc::statvfs(c_path.as_ptr(), stat.as_mut_ptr()) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: `statvfs` succeeded, so `stat` is initialized.
+    let stat = unsafe { stat.assume_init() };
+    // The field types differ between platforms.
+    #[allow(clippy::useless_conversion)]
+    let available = u64::from(stat.f_bavail).saturating_mul(u64::from(stat.f_frsize));
+    Ok(available)
+}
+
+/// Create and remove a uniquely named empty file in `dir`.
+fn probe_dir_write(dir: &Path) -> io::Result<()> {
+    let nanos = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.subsec_nanos());
+    let probe = dir.join(format!(
+        ".file-requirements-probe-{}-{}",
+        std::process::id(),
+        nanos
+    ));
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&probe)?;
+    fs::remove_file(&probe)
+}
+
+#[cfg(unix)]
+fn is_executable(_path: &Path, meta: &fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    meta.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path, meta: &fs::Metadata) -> bool {
+    meta.is_dir()
+        || path.extension().is_some_and(|ext| {
+            ["exe", "bat", "cmd", "com"]
+                .iter()
+                .any(|known| ext.eq_ignore_ascii_case(known))
+        })
+}
+
+/// Match members of a path listing against a glob pattern.
+fn glob_listing<'a, I>(members: I, pattern: &str) -> io::Result<Vec<PathBuf>>
+where
+    I: IntoIterator<Item = &'a PathBuf>,
+{
+    let pattern = glob::Pattern::new(pattern)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+    let mut matches: Vec<PathBuf> = members
+        .into_iter()
+        .filter(|path| pattern.matches_path(path))
+        .cloned()
+        .collect();
+    matches.sort();
+    Ok(matches)
+}
+
+/// Members are files; any proper ancestor of a member is a directory.
+fn listing_metadata<'a, I>(members: I, path: &Path) -> io::Result<FileMetadata>
+where
+    I: IntoIterator<Item = &'a PathBuf>,
+{
+    let mut kind = None;
+    for member in members {
+        if member == path {
+            kind = Some(FileKind::File);
+            break;
+        }
+        if member.starts_with(path) {
+            kind = Some(FileKind::Dir);
+        }
+    }
+    kind.map(FileMetadata::new)
+        .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+}
+
+/// Direct children of `path` implied by the listing.
+fn listing_read_dir<'a, I>(members: I, path: &Path) -> io::Result<Vec<PathBuf>>
+where
+    I: IntoIterator<Item = &'a PathBuf>,
+{
+    let children: BTreeSet<PathBuf> = members
+        .into_iter()
+        .filter_map(|member| {
+            let rest = member.strip_prefix(path).ok()?;
+            let first = rest.components().next()?;
+            Some(path.join(first))
+        })
+        .collect();
+    Ok(children.into_iter().collect())
+}
+
+/// A set of paths acts as a listing: exactly its members exist.
+impl FileProvider for HashSet<PathBuf> {
+    fn try_exists(&self, path: &Path) -> io::Result<bool> {
+        Ok(self.contains(path))
+    }
+
+    fn glob(&self, pattern: &str) -> io::Result<Vec<PathBuf>> {
+        glob_listing(self, pattern)
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<FileMetadata> {
+        listing_metadata(self, path)
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        listing_read_dir(self, path)
+    }
+}
+
+/// A set of paths acts as a listing: exactly its members exist.
+impl FileProvider for BTreeSet<PathBuf> {
+    fn try_exists(&self, path: &Path) -> io::Result<bool> {
+        Ok(self.contains(path))
+    }
+
+    fn glob(&self, pattern: &str) -> io::Result<Vec<PathBuf>> {
+        glob_listing(self, pattern)
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<FileMetadata> {
+        listing_metadata(self, path)
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        listing_read_dir(self, path)
+    }
+}
+
+impl<P: FileProvider + ?Sized> FileProvider for &P {
+    fn try_exists(&self, path: &Path) -> io::Result<bool> {
+        (**self).try_exists(path)
+    }
+
+    fn glob(&self, pattern: &str) -> io::Result<Vec<PathBuf>> {
+        (**self).glob(pattern)
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<FileMetadata> {
+        (**self).metadata(path)
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> io::Result<FileMetadata> {
+        (**self).symlink_metadata(path)
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        (**self).read_dir(path)
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read + '_>> {
+        (**self).open(path)
+    }
+
+    fn available_space(&self, path: &Path) -> io::Result<u64> {
+        (**self).available_space(path)
+    }
+
+    fn access(&self, path: &Path, access: Access) -> io::Result<bool> {
+        (**self).access(path, access)
+    }
+
+    fn url_exists(&self, url: &str) -> io::Result<bool> {
+        (**self).url_exists(url)
+    }
+
+    fn archive_contains(&self, archive: &Path, member: &str) -> io::Result<bool> {
+        (**self).archive_contains(archive, member)
+    }
+
+    fn invalidate(&self, path: &Path) {
+        (**self).invalidate(path)
+    }
+}
+
+/// Adapts an existence predicate closure to [`FileProvider`].
+pub(crate) struct PredicateProvider<F> {
+    pred: RefCell<F>,
+}
+
+impl<F> PredicateProvider<F> {
+    pub(crate) fn new(pred: F) -> Self {
+        Self {
+            pred: RefCell::new(pred),
+        }
+    }
+}
+
+impl<F> FileProvider for PredicateProvider<F>
+where
+    F: FnMut(&Path) -> io::Result<bool>,
+{
+    fn try_exists(&self, path: &Path) -> io::Result<bool> {
+        (self.pred.borrow_mut())(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FileProvider;
+    use crate::{FileRequirement, FileRequirementBuilder, LeafOutcome};
+    use std::collections::HashSet;
+    use std::io;
+    use std::path::{Path, PathBuf};
+
+    struct MockFs {
+        files: HashSet<PathBuf>,
+        denied: HashSet<PathBuf>,
+    }
+
+    impl FileProvider for MockFs {
+        fn try_exists(&self, path: &Path) -> io::Result<bool> {
+            if self.denied.contains(path) {
+                return Err(io::Error::from(io::ErrorKind::PermissionDenied));
+            }
+            Ok(self.files.contains(path))
+        }
+    }
+
+    #[test]
+    fn check_with_provider_uses_the_supplied_filesystem() {
+        let fs = MockFs {
+            files: ["idx.ctab", "idx.sshash"].map(PathBuf::from).into(),
+            denied: ["idx.refinfo"].map(PathBuf::from).into(),
+        };
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_file("idx.ctab").unwrap();
+        b.require_any(|any| {
+            any.require_file("idx.ssi")?;
+            any.require_file("idx.sshash")?;
+            Ok(())
+        })
+        .unwrap();
+        let mut req = b.build();
+        assert!(req.check_with_provider(&fs).is_ok());
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_file("idx.refinfo").unwrap();
+        req = b.build();
+        let report = req.check_report_with_provider(&fs);
+        assert!(matches!(
+            report.leaves()[0].outcome(),
+            LeafOutcome::IoError {
+                kind: io::ErrorKind::PermissionDenied,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn check_against_evaluates_a_listing() {
+        let req: FileRequirement = "(bundle/idx.ctab AND (bundle/idx.sshash OR bundle/idx.ssi))"
+            .parse()
+            .unwrap();
+        let listing = ["bundle/idx.ctab", "bundle/idx.ssi", "bundle/README"].map(PathBuf::from);
+        assert!(req.check_against(listing.clone()).is_ok());
+
+        let globbed: FileRequirement = r#"glob("bundle/idx.*", 2)"#.parse().unwrap();
+        assert!(globbed.check_against(listing).is_ok());
+
+        let err = req
+            .check_against([PathBuf::from("bundle/idx.ctab")])
+            .unwrap_err();
+        assert!(err.to_string().contains("bundle/idx.sshash"));
+    }
+}
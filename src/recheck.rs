@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::metrics::{Metered, timed};
+use crate::{CheckReport, FileRequirement, LeafOutcome, StdFs};
+
+impl FileRequirement {
+    /// Check this requirement expression again after `previous`, a report of
+    /// an earlier check of it, re-evaluating only the terms that did not pass.
+    ///
+    /// Terms reported [`Present`](LeafOutcome::Present) keep their outcome
+    /// without touching the filesystem; failed terms, and terms that were
+    /// skipped but now need evaluating, are checked afresh. This keeps watch
+    /// and retry loops cheap, at the cost of not noticing files that vanished
+    /// since `previous`.
+    pub fn recheck(&self, previous: &CheckReport) -> CheckReport {
+        let passed: HashMap<&FileRequirement, LeafOutcome> = previous
+            .leaves()
+            .into_iter()
+            .filter(|leaf| *leaf.outcome() == LeafOutcome::Present)
+            .map(|leaf| (leaf.declared_term(), leaf.outcome().clone()))
+            .collect();
+        let metered = Metered::new(StdFs);
+        let root = self.evaluate(&mut |leaf| match passed.get(leaf) {
+            Some(outcome) => (outcome.clone(), Duration::ZERO),
+            None => timed(|| leaf.evaluate_leaf(&metered)),
+        });
+        metered.report(root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FileRequirementBuilder;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn recheck_only_stats_previously_failed_terms() {
+        let td = tempdir().unwrap();
+        for name in ["a.txt", "b.txt"] {
+            fs::write(td.path().join(name), "").unwrap();
+        }
+
+        let mut b = FileRequirementBuilder::new();
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            b.require_file(td.path().join(name)).unwrap();
+        }
+        let req = b.build();
+        let report = req.check_report();
+        assert!(!report.is_satisfied());
+
+        fs::write(td.path().join("c.txt"), "").unwrap();
+        let report = req.recheck(&report);
+        assert!(report.is_satisfied());
+        assert_eq!(report.metrics().stat_calls(), 1);
+    }
+}
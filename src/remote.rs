@@ -0,0 +1,244 @@
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Instant, SystemTime};
+
+use object_store::ObjectStore;
+use object_store::path::Path as ObjectPath;
+
+use crate::async_check::LeafTask;
+use crate::metrics::Metered;
+use crate::{
+    CancelToken, CheckReport, FileKind, FileMetadata, FileProvider, FileRequirement,
+    FileRequirementCheckError, LeafOutcome, StdFs,
+};
+
+impl FileRequirement {
+    /// Validate this requirement expression against objects in `store`, such
+    /// as an S3, GCS or Azure bucket.
+    ///
+    /// Must be called from within a tokio runtime.
+    pub async fn check_object_store(
+        &self,
+        store: Arc<dyn ObjectStore>,
+    ) -> Result<(), FileRequirementCheckError> {
+        let report = self.check_report_object_store(store).await;
+        if report.is_satisfied() {
+            Ok(())
+        } else {
+            Err(FileRequirementCheckError::from_report(&report))
+        }
+    }
+
+    /// Like [`FileRequirement::check_report_async`], resolving paths as object
+    /// keys in `store`.
+    ///
+    /// File, absent and recommended terms are checked with a `HEAD` request
+    /// per object, at most 64 at a time and started round-robin across the
+    /// alternatives of each group; size and age constraints are checked
+    /// against the object's metadata, while constraints needing the object's
+    /// contents or permissions report [`io::ErrorKind::Unsupported`], as do
+    /// directory and glob terms. Environment variable, executable, free-space
+    /// and same-filesystem terms are checked locally.
+    pub async fn check_report_object_store(&self, store: Arc<dyn ObjectStore>) -> CheckReport {
+        self.check_report_object_store_limited(store, MAX_REQUESTS_IN_FLIGHT)
+            .await
+    }
+
+    /// Like [`FileRequirement::check_report_object_store`], with at most
+    /// `max_in_flight` terms being checked at any time.
+    ///
+    /// A limit of zero is treated as one.
+    pub async fn check_report_object_store_limited(
+        &self,
+        store: Arc<dyn ObjectStore>,
+        max_in_flight: usize,
+    ) -> CheckReport {
+        self.check_report_async_with(None, Some(max_in_flight), object_task(store))
+            .await
+    }
+
+    /// Like [`FileRequirement::check_report_object_store`], stopping early
+    /// once `token` is cancelled.
+    ///
+    /// Terms still pending at that point are abandoned and reported as
+    /// [`LeafOutcome::Cancelled`]; see [`CheckReport::is_cancelled`].
+    pub async fn check_report_object_store_with_cancel(
+        &self,
+        store: Arc<dyn ObjectStore>,
+        token: &CancelToken,
+    ) -> CheckReport {
+        self.check_report_async_with(
+            Some(token),
+            Some(MAX_REQUESTS_IN_FLIGHT),
+            object_task(store),
+        )
+        .await
+    }
+}
+
+/// Requests in flight when checking against an object store without a limit
+/// of its own.
+const MAX_REQUESTS_IN_FLIGHT: usize = 64;
+
+/// Check path-based terms against `store`, and other terms locally.
+fn object_task(
+    store: Arc<dyn ObjectStore>,
+) -> impl Fn(&FileRequirement, Arc<Metered<'static, StdFs>>) -> LeafTask {
+    move |leaf, metered| match leaf.unwrapped() {
+        FileRequirement::EnvVar { .. }
+        | FileRequirement::Executable(_)
+        | FileRequirement::Url(_)
+        | FileRequirement::FreeSpace { .. }
+        | FileRequirement::SameFilesystem { .. } => LeafTask::blocking(leaf, metered),
+        _ => {
+            let owned = leaf.clone();
+            let store = Arc::clone(&store);
+            LeafTask::Async(Box::pin(async move {
+                metered.record_stat();
+                let begin = Instant::now();
+                let outcome = evaluate_object(&owned, store.as_ref()).await;
+                (outcome, begin.elapsed())
+            }))
+        }
+    }
+}
+
+/// Check a path-based leaf term against the object with the same key.
+async fn evaluate_object(leaf: &FileRequirement, store: &dyn ObjectStore) -> LeafOutcome {
+    let head = |path: &Path| {
+        let location = path.to_str().map(ObjectPath::parse);
+        async move {
+            let location = match location {
+                Some(Ok(location)) => location,
+                Some(Err(e)) => return Err(io::Error::new(io::ErrorKind::InvalidInput, e)),
+                None => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "object keys must be valid UTF-8",
+                    ));
+                }
+            };
+            match store.head(&location).await {
+                Ok(meta) => Ok(Some(
+                    FileMetadata::new(FileKind::File)
+                        .with_len(meta.size)
+                        .with_modified(SystemTime::from(meta.last_modified)),
+                )),
+                Err(object_store::Error::NotFound { .. }) => Ok(None),
+                Err(e) => Err(io::Error::other(e)),
+            }
+        }
+    };
+    match leaf.unwrapped() {
+        FileRequirement::File(path) => match head(path).await {
+            Ok(meta) => LeafOutcome::from_exists(Ok(meta.is_some())),
+            Err(e) => LeafOutcome::from_io_error(e),
+        },
+        FileRequirement::FileWith { path, constraints } => match head(path).await {
+            Ok(Some(meta)) => constraints.evaluate(path, &meta, &MetadataOnly),
+            Ok(None) => LeafOutcome::Missing,
+            Err(e) => LeafOutcome::from_io_error(e),
+        },
+        FileRequirement::Absent(path) => match head(path).await {
+            Ok(None) => LeafOutcome::Present,
+            Ok(Some(_)) => LeafOutcome::AlreadyExists,
+            Err(e) => LeafOutcome::from_io_error(e),
+        },
+        FileRequirement::Recommended(path) => {
+            LeafOutcome::recommended(head(path).await.map(|meta| meta.is_some()))
+        }
+        _ => LeafOutcome::from_io_error(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "only file, absent and recommended terms can be checked in an object store",
+        )),
+    }
+}
+
+/// Offers nothing beyond the metadata already fetched, so constraints needing
+/// more report an unsupported operation.
+struct MetadataOnly;
+
+impl FileProvider for MetadataOnly {
+    fn try_exists(&self, _: &Path) -> io::Result<bool> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "object store terms support only size and age constraints",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FileConstraints, FileRequirementBuilder, LeafOutcome, Size};
+    use object_store::ObjectStore;
+    use object_store::memory::InMemory;
+    use object_store::path::Path as ObjectPath;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn object_store_terms_check_existence_and_size() {
+        let store = Arc::new(InMemory::new());
+        store
+            .put(&ObjectPath::from("refs/genome.fa"), b"ACGT".to_vec().into())
+            .await
+            .unwrap();
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_file("refs/genome.fa").unwrap();
+        b.require_file_with("refs/genome.fa.fai", Size::AtLeast(1))
+            .unwrap();
+        b.require_absent("out/quant.sf").unwrap();
+        let req = b.build();
+
+        let report = req.check_report_object_store(store.clone()).await;
+        let outcomes: Vec<&LeafOutcome> = report.leaves().iter().map(|l| l.outcome()).collect();
+        assert_eq!(
+            outcomes,
+            [
+                &LeafOutcome::Present,
+                &LeafOutcome::Missing,
+                &LeafOutcome::Present
+            ]
+        );
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_file_with(
+            "refs/genome.fa",
+            FileConstraints::new().size(Size::AtLeast(5)),
+        )
+        .unwrap();
+        let report = b.build().check_report_object_store(store).await;
+        assert_eq!(
+            report.leaves()[0].outcome(),
+            &LeafOutcome::SizeOutOfRange { actual: 4 }
+        );
+    }
+
+    #[tokio::test]
+    async fn object_store_checks_are_limited_and_cancellable() {
+        let store = Arc::new(InMemory::new());
+        let mut b = FileRequirementBuilder::new();
+        b.require_any(|any| {
+            for i in 0..5 {
+                any.require_file(format!("shards/part_{}.rad", i))?;
+            }
+            Ok(())
+        })
+        .unwrap();
+        let req = b.build();
+
+        let report = req
+            .check_report_object_store_limited(store.clone(), 1)
+            .await;
+        assert!(!report.is_satisfied());
+        assert_eq!(report.metrics().stat_calls(), 5);
+
+        let token = crate::CancelToken::new();
+        token.cancel();
+        let report = req
+            .check_report_object_store_with_cancel(store, &token)
+            .await;
+        assert!(report.is_cancelled());
+    }
+}
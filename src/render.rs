@@ -0,0 +1,517 @@
+use crate::{CheckReport, FileRequirement, LeafOutcome, NodeReport};
+
+impl FileRequirement {
+    /// Render the expression as a Graphviz DOT digraph: boxes for groups,
+    /// ellipses for terms, with edges from each group to its children.
+    ///
+    /// Use [`CheckReport::to_dot`] for a graph colored by check status.
+    pub fn to_dot(&self) -> String {
+        Diagram::from_requirement(self).to_dot()
+    }
+
+    /// Render the expression as a Mermaid flowchart, mirroring
+    /// [`to_dot`](Self::to_dot), for Markdown documents and wikis.
+    ///
+    /// Use [`CheckReport::to_mermaid`] for a chart colored by check status.
+    pub fn to_mermaid(&self) -> String {
+        Diagram::from_requirement(self).to_mermaid()
+    }
+}
+
+impl CheckReport {
+    /// Render the checked tree as a Graphviz DOT digraph, as
+    /// [`FileRequirement::to_dot`] does, with nodes filled green when satisfied,
+    /// red when failed and gray when skipped, and failing terms labeled with
+    /// their outcome.
+    pub fn to_dot(&self) -> String {
+        Diagram::from_report(self.root()).to_dot()
+    }
+
+    /// Render the checked tree as a Mermaid flowchart, colored as in
+    /// [`CheckReport::to_dot`].
+    pub fn to_mermaid(&self) -> String {
+        Diagram::from_report(self.root()).to_mermaid()
+    }
+}
+
+/// Check status of a diagram node.
+#[derive(Clone, Copy)]
+enum Status {
+    Satisfied,
+    Failed,
+    Skipped,
+}
+
+impl Status {
+    /// The Mermaid class name.
+    fn class(self) -> &'static str {
+        match self {
+            Status::Satisfied => "satisfied",
+            Status::Failed => "failed",
+            Status::Skipped => "skipped",
+        }
+    }
+
+    fn of(node: &NodeReport) -> Self {
+        if node.is_skipped() {
+            Status::Skipped
+        } else if node.is_satisfied() {
+            Status::Satisfied
+        } else {
+            Status::Failed
+        }
+    }
+}
+
+struct DiagramNode {
+    label: String,
+    is_group: bool,
+    status: Option<Status>,
+}
+
+/// A requirement tree flattened into numbered nodes and parent-child edges,
+/// shared by the diagram exporters.
+#[derive(Default)]
+struct Diagram {
+    nodes: Vec<DiagramNode>,
+    edges: Vec<(usize, usize)>,
+}
+
+impl Diagram {
+    fn from_requirement(requirement: &FileRequirement) -> Self {
+        fn add(diagram: &mut Diagram, node: &FileRequirement) -> usize {
+            let Some((kind, children)) = node.group() else {
+                let label = match node.name() {
+                    Some(name) => format!("{}\n{}", name, node.unwrapped()),
+                    None => node.unwrapped().to_string(),
+                };
+                return diagram.push(label, false, None);
+            };
+            let label = match node.name() {
+                Some(name) => format!("{} ({})", name, kind.operator()),
+                None => kind.operator().to_owned(),
+            };
+            let id = diagram.push(label, true, None);
+            for child in children {
+                diagram.edges.push((id, diagram.nodes.len()));
+                add(diagram, child);
+            }
+            id
+        }
+        let mut diagram = Diagram::default();
+        add(&mut diagram, requirement);
+        diagram
+    }
+
+    fn from_report(root: &NodeReport) -> Self {
+        fn add(diagram: &mut Diagram, node: &NodeReport) -> usize {
+            let status = Some(Status::of(node));
+            let group = match node {
+                NodeReport::Leaf(leaf) => {
+                    let mut label = match leaf.name() {
+                        Some(name) => format!("{}\n{}", name, leaf.term()),
+                        None => leaf.term().to_string(),
+                    };
+                    if !matches!(leaf.outcome(), LeafOutcome::Present | LeafOutcome::Skipped) {
+                        label.push_str(&format!("\n({})", leaf.outcome()));
+                    }
+                    return diagram.push(label, false, status);
+                }
+                NodeReport::Group(group) => group,
+            };
+            let label = match group.name() {
+                Some(name) => format!("{} ({})", name, group.kind().operator()),
+                None => group.kind().operator().to_owned(),
+            };
+            let id = diagram.push(label, true, status);
+            for child in group.children() {
+                diagram.edges.push((id, diagram.nodes.len()));
+                add(diagram, child);
+            }
+            id
+        }
+        let mut diagram = Diagram::default();
+        add(&mut diagram, root);
+        diagram
+    }
+
+    fn push(&mut self, label: String, is_group: bool, status: Option<Status>) -> usize {
+        self.nodes.push(DiagramNode {
+            label,
+            is_group,
+            status,
+        });
+        self.nodes.len() - 1
+    }
+
+    fn to_dot(&self) -> String {
+        let mut out = String::from("digraph requirements {\n");
+        for (id, node) in self.nodes.iter().enumerate() {
+            let shape = if node.is_group { "box" } else { "ellipse" };
+            out.push_str(&format!(
+                "    n{} [label=\"{}\", shape={}",
+                id,
+                dot_escape(&node.label),
+                shape
+            ));
+            if let Some(status) = node.status {
+                let color = match status {
+                    Status::Satisfied => "palegreen",
+                    Status::Failed => "lightcoral",
+                    Status::Skipped => "lightgray",
+                };
+                out.push_str(&format!(", style=filled, fillcolor={}", color));
+            }
+            out.push_str("];\n");
+        }
+        for (parent, child) in &self.edges {
+            out.push_str(&format!("    n{} -> n{};\n", parent, child));
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+impl Diagram {
+    fn to_mermaid(&self) -> String {
+        let mut out = String::from("flowchart TD\n");
+        for (id, node) in self.nodes.iter().enumerate() {
+            let label = mermaid_escape(&node.label);
+            if node.is_group {
+                out.push_str(&format!("    n{}[\"{}\"]", id, label));
+            } else {
+                out.push_str(&format!("    n{}([\"{}\"])", id, label));
+            }
+            if let Some(status) = node.status {
+                out.push_str(&format!(":::{}", status.class()));
+            }
+            out.push('\n');
+        }
+        for (parent, child) in &self.edges {
+            out.push_str(&format!("    n{} --> n{}\n", parent, child));
+        }
+        if self.nodes.iter().any(|node| node.status.is_some()) {
+            out.push_str("    classDef satisfied fill:#98fb98\n");
+            out.push_str("    classDef failed fill:#f08080\n");
+            out.push_str("    classDef skipped fill:#d3d3d3\n");
+        }
+        out
+    }
+}
+
+/// Escape a label for a double-quoted Mermaid string using entity codes, with
+/// line breaks as `<br>`.
+fn mermaid_escape(label: &str) -> String {
+    label
+        .replace('#', "#35;")
+        .replace('"', "#quot;")
+        .replace('<', "#lt;")
+        .replace('>', "#gt;")
+        .replace('\n', "<br>")
+}
+
+/// Escape a label for a double-quoted DOT string, keeping `\n` line breaks.
+fn dot_escape(label: &str) -> String {
+    label
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+impl CheckReport {
+    /// Render the checked tree with one node per line and a status symbol:
+    /// `✓` satisfied, `✗` failed, `-` skipped.
+    ///
+    /// Failing and noteworthy leaves give their outcome in parentheses:
+    ///
+    /// ```text
+    /// ✗ AND
+    ///   ✓ idx.ctab
+    ///   ✗ index format (OR)
+    ///     ✗ idx.sshash (missing)
+    ///     ✗ AND
+    ///       ✓ idx.ssi
+    ///       ✗ idx.ssi.mphf (missing)
+    /// ```
+    pub fn render_tree(&self) -> String {
+        let mut out = String::new();
+        render_node(self.root(), 0, false, &mut out);
+        out
+    }
+
+    /// [`render_tree`](Self::render_tree) with ANSI colors: green for satisfied
+    /// nodes, red for failures, yellow for warnings and dim for skipped nodes.
+    #[cfg(feature = "color")]
+    pub fn render_tree_colored(&self) -> String {
+        let mut out = String::new();
+        render_node(self.root(), 0, true, &mut out);
+        out
+    }
+
+    /// Render the tree for `stream`, colored only if it is a terminal and the
+    /// `NO_COLOR` environment variable is unset or empty.
+    ///
+    /// ```no_run
+    /// # let report = file_requirements::FileRequirement::All(vec![]).check_report();
+    /// eprint!("{}", report.render_tree_for(&std::io::stderr()));
+    /// ```
+    #[cfg(feature = "color")]
+    pub fn render_tree_for<T: std::io::IsTerminal>(&self, stream: &T) -> String {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        if stream.is_terminal() && !no_color {
+            self.render_tree_colored()
+        } else {
+            self.render_tree()
+        }
+    }
+}
+
+impl CheckReport {
+    /// Render the file terms as a Markdown table of status, term, size and
+    /// remediation hint, e.g. for CI job summaries.
+    ///
+    /// A term without a hint shows that of its nearest enclosing group. Sizes
+    /// are those the check recorded, for file terms whose metadata it read,
+    /// so rendering touches no files.
+    pub fn render_markdown(&self) -> String {
+        let mut out = String::from("| Status | Term | Size | Hint |\n| --- | --- | ---: | --- |\n");
+        markdown_rows(self.root(), None, &mut out);
+        out
+    }
+}
+
+fn markdown_rows(node: &NodeReport, inherited_hint: Option<&str>, out: &mut String) {
+    let leaf = match node {
+        NodeReport::Leaf(leaf) => leaf,
+        NodeReport::Group(group) => {
+            for child in group.children() {
+                markdown_rows(child, group.hint().or(inherited_hint), out);
+            }
+            return;
+        }
+    };
+    let status = match leaf.outcome() {
+        LeafOutcome::Present => "✓ present".to_owned(),
+        outcome => format!("{} {}", status_symbol(node), outcome),
+    };
+    let term = match leaf.name() {
+        Some(name) => format!("{} (`{}`)", name, leaf.term()),
+        None => format!("`{}`", leaf.term()),
+    };
+    let size = leaf
+        .size()
+        .map_or_else(String::new, |bytes| format!("{} byte(s)", bytes));
+    let hint = leaf.hint().or(inherited_hint).unwrap_or_default();
+    out.push_str(&format!(
+        "| {} | {} | {} | {} |\n",
+        markdown_escape(&status),
+        markdown_escape(&term),
+        size,
+        markdown_escape(hint)
+    ));
+}
+
+/// Escape a Markdown table cell.
+fn markdown_escape(cell: &str) -> String {
+    cell.replace('|', "\\|").replace('\n', " ")
+}
+
+fn status_symbol(node: &NodeReport) -> &'static str {
+    if node.is_skipped() {
+        "-"
+    } else if node.is_satisfied() {
+        "✓"
+    } else {
+        "✗"
+    }
+}
+
+/// ANSI SGR code for a tree line, by the node's status.
+fn line_color(node: &NodeReport) -> &'static str {
+    match node {
+        _ if node.is_skipped() => "2",
+        NodeReport::Leaf(leaf)
+            if matches!(
+                leaf.outcome(),
+                LeafOutcome::OverwriteAllowed
+                    | LeafOutcome::RecommendedMissing
+                    | LeafOutcome::RecommendedUnreadable { .. }
+            ) =>
+        {
+            "33"
+        }
+        _ if node.is_satisfied() => "32",
+        _ => "31",
+    }
+}
+
+fn render_node(node: &NodeReport, depth: usize, color: bool, out: &mut String) {
+    let text = match node {
+        NodeReport::Leaf(leaf) => {
+            let term = match leaf.name() {
+                Some(name) => format!("{} ({})", name, leaf.term()),
+                None => leaf.term().to_string(),
+            };
+            match leaf.outcome() {
+                LeafOutcome::Present => term,
+                outcome => format!("{} ({})", term, outcome),
+            }
+        }
+        NodeReport::Group(group) => match group.name() {
+            Some(name) => format!("{} ({})", name, group.kind().operator()),
+            None => group.kind().operator().to_owned(),
+        },
+    };
+    out.push_str(&"  ".repeat(depth));
+    if color {
+        out.push_str(&format!("\x1b[{}m", line_color(node)));
+    }
+    out.push_str(status_symbol(node));
+    out.push(' ');
+    out.push_str(&text);
+    if color {
+        out.push_str("\x1b[0m");
+    }
+    out.push('\n');
+    if let NodeReport::Group(group) = node {
+        for child in group.children() {
+            render_node(child, depth + 1, color, out);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FileConstraints, FileRequirementBuilder};
+    use tempfile::tempdir;
+
+    #[test]
+    fn rendered_tree_marks_each_node() {
+        let td = tempdir().unwrap();
+        std::fs::write(td.path().join("idx.sshash"), "").unwrap();
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_file("idx.ctab").unwrap();
+        b.require_any_named("index format", |any| {
+            any.require_file("idx.sshash")?;
+            any.require_file("idx.ssi")?;
+            Ok(())
+        })
+        .unwrap();
+        let report = b.build().rebased(td.path()).check_report();
+        let rendered = report
+            .render_tree()
+            .replace(&format!("{}/", td.path().display()), "");
+        assert_eq!(
+            rendered,
+            "✗ AND\n  ✗ idx.ctab (missing)\n  ✓ index format (OR)\n    ✓ idx.sshash\n    - idx.ssi (skipped)\n"
+        );
+    }
+
+    #[test]
+    fn dot_export_lists_nodes_and_edges() {
+        let req: crate::FileRequirement = r#"a.txt AND named("index", (b.txt OR "c \"d\".txt"))"#
+            .parse()
+            .unwrap();
+        assert_eq!(
+            req.to_dot(),
+            r#"digraph requirements {
+    n0 [label="AND", shape=box];
+    n1 [label="a.txt", shape=ellipse];
+    n2 [label="index (OR)", shape=box];
+    n3 [label="b.txt", shape=ellipse];
+    n4 [label="\"c \\\"d\\\".txt\"", shape=ellipse];
+    n0 -> n1;
+    n0 -> n2;
+    n2 -> n3;
+    n2 -> n4;
+}
+"#
+        );
+
+        let td = tempdir().unwrap();
+        let report = req.rebased(td.path()).check_report();
+        let dot = report.to_dot();
+        assert!(dot.contains("n0 [label=\"AND\", shape=box, style=filled, fillcolor=lightcoral];"));
+        assert!(
+            dot.contains("\\n(missing)\", shape=ellipse, style=filled, fillcolor=lightcoral];")
+        );
+    }
+
+    #[test]
+    fn mermaid_export_mirrors_the_dot_graph() {
+        let req: crate::FileRequirement =
+            r#"a.txt AND named("index", (b.txt OR "c#1.txt"))"#.parse().unwrap();
+        assert_eq!(
+            req.to_mermaid(),
+            r##"flowchart TD
+    n0["AND"]
+    n1(["a.txt"])
+    n2["index (OR)"]
+    n3(["b.txt"])
+    n4(["c#35;1.txt"])
+    n0 --> n1
+    n0 --> n2
+    n2 --> n3
+    n2 --> n4
+"##
+        );
+
+        let td = tempdir().unwrap();
+        let chart = req.rebased(td.path()).check_report().to_mermaid();
+        assert!(chart.contains("    n0[\"AND\"]:::failed\n"));
+        assert!(chart.ends_with("    classDef skipped fill:#d3d3d3\n"));
+    }
+
+    #[test]
+    fn markdown_table_lists_status_size_and_hints() {
+        let td = tempdir().unwrap();
+        std::fs::write(td.path().join("idx.ctab"), "12345").unwrap();
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_file_with("idx.ctab", FileConstraints::new().non_empty())
+            .unwrap();
+        b.require_any(|any| {
+            any.require_file("idx.sshash")?;
+            any.require_file("idx.ssi")?
+                .with_hint("rebuild with `salmon index --ssi`");
+            Ok(())
+        })
+        .unwrap()
+        .with_hint("run `salmon index`");
+        let report = b.build().rebased(td.path()).check_report();
+        // Sizes come from the check, not from the files at render time.
+        std::fs::remove_file(td.path().join("idx.ctab")).unwrap();
+        let table = report
+            .render_markdown()
+            .replace(&format!("{}/", td.path().display()), "");
+        assert_eq!(
+            table,
+            "| Status | Term | Size | Hint |
+| --- | --- | ---: | --- |
+| ✓ present | `file(idx.ctab, size>=1)` | 5 byte(s) |  |
+| ✗ missing | `idx.sshash` |  | run `salmon index` |
+| ✗ missing | `idx.ssi` |  | rebuild with `salmon index --ssi` |
+"
+        );
+    }
+
+    #[cfg(feature = "color")]
+    #[test]
+    fn colored_tree_wraps_lines_in_status_colors() {
+        let mut b = FileRequirementBuilder::new();
+        b.require_file("idx.ctab").unwrap();
+        b.recommend_file("decoys.txt").unwrap();
+        let td = tempdir().unwrap();
+        let report = b.build().rebased(td.path()).check_report();
+        let colored = report
+            .render_tree_colored()
+            .replace(&format!("{}/", td.path().display()), "");
+        assert_eq!(
+            colored,
+            "\x1b[31m✗ AND\x1b[0m\n  \x1b[31m✗ idx.ctab (missing)\x1b[0m\n  \x1b[33m✓ recommend(decoys.txt) (recommended, not found)\x1b[0m\n"
+        );
+        let file = tempfile::tempfile().unwrap();
+        assert_eq!(report.render_tree_for(&file), report.render_tree());
+    }
+}
@@ -0,0 +1,947 @@
+use std::fmt;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::parse::DisplayQuoted;
+use crate::{Access, CheckCache, CheckMetrics, FileKind, FileMetadata, FileRequirement};
+
+/// Result of evaluating a single file term.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum LeafOutcome {
+    /// The path exists.
+    Present,
+    /// The path does not exist.
+    Missing,
+    /// An executable term was not found in any `PATH` directory.
+    NotOnPath {
+        /// The directories searched, in `PATH` order.
+        searched: Vec<PathBuf>,
+    },
+    /// An absent term's path exists.
+    AlreadyExists,
+    /// An absent term's path exists, but overwriting was allowed.
+    ///
+    /// This satisfies the term and is listed by [`CheckReport::warnings`].
+    OverwriteAllowed,
+    /// A recommended file could not be found.
+    ///
+    /// This satisfies the term and is listed by [`CheckReport::warnings`].
+    RecommendedMissing,
+    /// A recommended file's existence could not be determined.
+    ///
+    /// Like [`RecommendedMissing`](LeafOutcome::RecommendedMissing), this
+    /// satisfies the term and is listed by [`CheckReport::warnings`].
+    RecommendedUnreadable {
+        /// Kind of the underlying IO error.
+        #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_error_kind"))]
+        kind: io::ErrorKind,
+        /// Rendered IO error message.
+        message: String,
+    },
+    /// The path is a symlink whose target does not exist.
+    BrokenSymlink,
+    /// A regular-file term exists but is some other kind of entry.
+    NotARegularFile {
+        /// The kind of entry found at the path.
+        kind: FileKind,
+    },
+    /// A file term exists but the current user lacks a required permission.
+    PermissionDenied {
+        /// The first required permission that is missing.
+        access: Access,
+    },
+    /// A writable directory term is missing and cannot be created.
+    CannotCreate {
+        /// The nearest existing ancestor, which is not a writable directory.
+        ancestor: PathBuf,
+    },
+    /// The filesystem of a free-space term has too little space available.
+    InsufficientSpace {
+        /// Bytes available to unprivileged users.
+        available: u64,
+    },
+    /// The two paths of a same-filesystem term are on different filesystems.
+    DifferentFilesystem,
+    /// A file term's size is outside the required bounds.
+    ///
+    /// For a glob size term, `actual` is the total size of the matching files.
+    SizeOutOfRange {
+        /// The actual size in bytes.
+        actual: u64,
+    },
+    /// A file term's age is outside the required bounds.
+    AgeOutOfRange {
+        /// The actual modification time.
+        modified: SystemTime,
+    },
+    /// A file term is older than the file it must be newer than.
+    Stale {
+        /// The term's modification time.
+        modified: SystemTime,
+        /// The dependency's modification time.
+        dependency_modified: SystemTime,
+    },
+    /// A file term's contents do not start with the expected signature.
+    MagicMismatch {
+        /// The leading bytes actually read (shorter than expected if the file is).
+        found: Vec<u8>,
+    },
+    /// A file term is not owned by the required user or group.
+    WrongOwner {
+        /// The owning user id.
+        uid: u32,
+        /// The owning group id.
+        gid: u32,
+    },
+    /// A file term's content digest does not match the expected one.
+    DigestMismatch {
+        /// The actual digest, as lowercase hex.
+        actual: String,
+    },
+    /// An archive exists but does not contain the required member.
+    MemberMissing,
+    /// A file term is not an intact gzip or BGZF stream.
+    CorruptGzip {
+        /// What is wrong, e.g. `missing BGZF EOF block`.
+        problem: String,
+    },
+    /// A file term's size or modification time changed while it was sampled.
+    StillChanging,
+    /// A directory term exists but is not a directory.
+    NotADirectory,
+    /// A non-empty directory term is an empty directory.
+    EmptyDirectory,
+    /// A glob, shard or directory entry term matched fewer paths than required.
+    TooFewMatches {
+        /// Number of existing paths that matched.
+        found: usize,
+    },
+    /// The term's path references an environment variable that is not set.
+    UnsetVariable {
+        /// The variable name.
+        name: String,
+    },
+    /// An environment variable term is set to an empty value.
+    EmptyVariable,
+    /// An environment variable term's value does not match its pattern.
+    ///
+    /// The value itself is not kept, as it may be a secret.
+    VariableMismatch {
+        /// The length of the value in characters.
+        length: usize,
+    },
+    /// Existence could not be determined.
+    IoError {
+        /// Kind of the underlying IO error.
+        #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_error_kind"))]
+        kind: io::ErrorKind,
+        /// Rendered IO error message.
+        message: String,
+        /// The raw OS error code, if the error came from the operating system.
+        code: Option<i32>,
+    },
+    /// The term's check had not finished when the check deadline passed.
+    Unresolved,
+    /// The term was not checked because the check was cancelled.
+    Cancelled,
+    /// The term was not evaluated because an enclosing group was already decided.
+    Skipped,
+}
+
+/// Serialize an IO error kind by its variant name, e.g. `"NotFound"`.
+#[cfg(feature = "serde")]
+fn serialize_error_kind<S: serde::Serializer>(
+    kind: &io::ErrorKind,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(&format_args!("{:?}", kind))
+}
+
+impl LeafOutcome {
+    pub(crate) fn from_exists(result: io::Result<bool>) -> Self {
+        match result {
+            Ok(true) => LeafOutcome::Present,
+            Ok(false) => LeafOutcome::Missing,
+            Err(e) => LeafOutcome::from_io_error(e),
+        }
+    }
+
+    /// The outcome of a recommended term whose existence check returned
+    /// `result`.
+    pub(crate) fn recommended(result: io::Result<bool>) -> Self {
+        match result {
+            Ok(true) => LeafOutcome::Present,
+            Ok(false) => LeafOutcome::RecommendedMissing,
+            Err(e) => LeafOutcome::RecommendedUnreadable {
+                kind: e.kind(),
+                message: e.to_string(),
+            },
+        }
+    }
+
+    pub(crate) fn from_io_error(e: io::Error) -> Self {
+        LeafOutcome::IoError {
+            kind: e.kind(),
+            message: e.to_string(),
+            code: e.raw_os_error(),
+        }
+    }
+
+    /// The IO error of an [`IoError`](LeafOutcome::IoError) outcome.
+    ///
+    /// OS errors are rebuilt from their code, so they compare equal in kind,
+    /// code and message to the original; other errors keep their kind and
+    /// message.
+    pub fn io_error(&self) -> Option<io::Error> {
+        match self {
+            LeafOutcome::IoError {
+                code: Some(code), ..
+            } => Some(io::Error::from_raw_os_error(*code)),
+            LeafOutcome::IoError { kind, message, .. } => {
+                Some(io::Error::new(*kind, message.clone()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether this outcome satisfies the term.
+    pub fn is_present(&self) -> bool {
+        matches!(
+            self,
+            LeafOutcome::Present
+                | LeafOutcome::OverwriteAllowed
+                | LeafOutcome::RecommendedMissing
+                | LeafOutcome::RecommendedUnreadable { .. }
+        )
+    }
+}
+
+/// A short description, such as `missing` or `3 byte(s), out of range`.
+impl fmt::Display for LeafOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LeafOutcome::Present => f.write_str("present"),
+            LeafOutcome::Missing => f.write_str("missing"),
+            LeafOutcome::NotOnPath { .. } => f.write_str("not found on PATH"),
+            LeafOutcome::AlreadyExists => f.write_str("already exists"),
+            LeafOutcome::OverwriteAllowed => f.write_str("will be overwritten"),
+            LeafOutcome::RecommendedMissing => f.write_str("recommended, not found"),
+            LeafOutcome::RecommendedUnreadable { message, .. } => {
+                write!(f, "recommended, unreadable: {}", message)
+            }
+            LeafOutcome::BrokenSymlink => f.write_str("broken symlink"),
+            LeafOutcome::NotARegularFile { .. } => f.write_str("not a regular file"),
+            LeafOutcome::PermissionDenied { access } => write!(f, "not {}", access.adjective()),
+            LeafOutcome::CannotCreate { ancestor } => {
+                write!(f, "cannot be created under {}", ancestor.display())
+            }
+            LeafOutcome::InsufficientSpace { available } => {
+                write!(f, "only {} byte(s) available", available)
+            }
+            LeafOutcome::DifferentFilesystem => f.write_str("on different filesystems"),
+            LeafOutcome::SizeOutOfRange { actual } => {
+                write!(f, "{} byte(s), out of range", actual)
+            }
+            LeafOutcome::AgeOutOfRange { .. } => f.write_str("age out of range"),
+            LeafOutcome::Stale { .. } => f.write_str("older than its dependency"),
+            LeafOutcome::MagicMismatch { .. } => f.write_str("unexpected file format"),
+            LeafOutcome::WrongOwner { uid, gid } => {
+                write!(f, "owned by uid {}, gid {}", uid, gid)
+            }
+            LeafOutcome::DigestMismatch { .. } => f.write_str("checksum mismatch"),
+            LeafOutcome::MemberMissing => f.write_str("member not found"),
+            LeafOutcome::CorruptGzip { problem } => f.write_str(problem),
+            LeafOutcome::StillChanging => f.write_str("still changing"),
+            LeafOutcome::NotADirectory => f.write_str("not a directory"),
+            LeafOutcome::EmptyDirectory => f.write_str("empty directory"),
+            LeafOutcome::TooFewMatches { found } => write!(f, "{} match(es)", found),
+            LeafOutcome::UnsetVariable { name } => write!(f, "${} is unset", name),
+            LeafOutcome::EmptyVariable => f.write_str("empty"),
+            LeafOutcome::VariableMismatch { length } => {
+                write!(f, "{}-character value does not match", length)
+            }
+            LeafOutcome::IoError { message, .. } => f.write_str(message),
+            LeafOutcome::Unresolved => f.write_str("not resolved before the deadline"),
+            LeafOutcome::Cancelled => f.write_str("cancelled"),
+            LeafOutcome::Skipped => f.write_str("skipped"),
+        }
+    }
+}
+
+/// How serious a [`Finding`] is.
+///
+/// Severities are ordered from [`Info`](Severity::Info) to
+/// [`Error`](Severity::Error).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Severity {
+    /// Expected and harmless, such as an output overwritten with permission.
+    Info,
+    /// Not fatal by default, such as an absent recommended file.
+    Warning,
+    /// The expression is not satisfied.
+    #[default]
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// A single diagnostic from a [`CheckReport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Finding {
+    severity: Severity,
+    message: String,
+}
+
+impl Finding {
+    pub(crate) fn new(severity: Severity, message: String) -> Self {
+        Self { severity, message }
+    }
+
+    /// How serious the finding is.
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// The human-readable description.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
+
+/// The logical operator of a group node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum GroupKind {
+    /// Conjunction (`AND`).
+    All,
+    /// Disjunction (`OR`).
+    Any,
+    /// Mutually exclusive choice (`XOR`).
+    ExactlyOne,
+}
+
+impl GroupKind {
+    pub(crate) fn operator(self) -> &'static str {
+        match self {
+            GroupKind::All => "AND",
+            GroupKind::Any => "OR",
+            GroupKind::ExactlyOne => "XOR",
+        }
+    }
+}
+
+/// Outcome of a single file term.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LeafReport {
+    term: FileRequirement,
+    outcome: LeafOutcome,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    suggestions: Vec<PathBuf>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Duration::is_zero"))]
+    latency: Duration,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    size: Option<u64>,
+}
+
+impl LeafReport {
+    pub(crate) fn new(term: FileRequirement, outcome: LeafOutcome) -> Self {
+        Self {
+            term,
+            outcome,
+            suggestions: Vec::new(),
+            latency: Duration::ZERO,
+            size: None,
+        }
+    }
+
+    pub(crate) fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    pub(crate) fn set_suggestions(&mut self, suggestions: Vec<PathBuf>) {
+        self.suggestions = suggestions;
+    }
+
+    pub(crate) fn set_outcome(&mut self, outcome: LeafOutcome) {
+        self.outcome = outcome;
+    }
+
+    /// The path of the file term, or the pattern of a glob term.
+    pub fn path(&self) -> &Path {
+        self.term.leaf_path()
+    }
+
+    /// The term as declared, including any name and hint.
+    pub(crate) fn declared_term(&self) -> &FileRequirement {
+        &self.term
+    }
+
+    /// The evaluated term, without its name.
+    pub fn term(&self) -> &FileRequirement {
+        self.term.unwrapped()
+    }
+
+    /// The descriptive name given to the term, if any.
+    pub fn name(&self) -> Option<&str> {
+        self.term.name()
+    }
+
+    /// The remediation hint attached to the term, if any.
+    pub fn hint(&self) -> Option<&str> {
+        self.term.hint()
+    }
+
+    /// The term as it should appear in messages: `name (path)` for named
+    /// terms, or just the path.
+    pub(crate) fn subject(&self) -> String {
+        match self.name() {
+            Some(name) => format!("{} ({})", name, self.path().display()),
+            None => self.path().display().to_string(),
+        }
+    }
+
+    /// The evaluation outcome.
+    pub fn outcome(&self) -> &LeafOutcome {
+        &self.outcome
+    }
+
+    /// Similarly named existing paths for a missing term, when
+    /// [`CheckOptions::suggest_similar`](crate::CheckOptions::suggest_similar)
+    /// is enabled.
+    pub fn suggestions(&self) -> &[PathBuf] {
+        &self.suggestions
+    }
+
+    /// How long evaluating the term took; zero for skipped terms.
+    pub fn latency(&self) -> Duration {
+        self.latency
+    }
+
+    /// The size in bytes of the term's regular file, when the check read its
+    /// metadata, as it does for constrained files.
+    pub fn size(&self) -> Option<u64> {
+        self.size
+    }
+}
+
+/// Outcome of a group node and all of its children.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GroupReport {
+    kind: GroupKind,
+    satisfied: bool,
+    children: Vec<NodeReport>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    name: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    hint: Option<String>,
+}
+
+impl GroupReport {
+    pub(crate) fn new(kind: GroupKind, satisfied: bool, children: Vec<NodeReport>) -> Self {
+        Self {
+            kind,
+            satisfied,
+            children,
+            name: None,
+            hint: None,
+        }
+    }
+
+    pub(crate) fn with_name(mut self, name: Option<&str>) -> Self {
+        self.name = name.map(str::to_owned);
+        self
+    }
+
+    pub(crate) fn with_hint(mut self, hint: Option<&str>) -> Self {
+        self.hint = hint.map(str::to_owned);
+        self
+    }
+
+    /// The descriptive name given to the group, if any.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// The remediation hint attached to the group, if any.
+    pub fn hint(&self) -> Option<&str> {
+        self.hint.as_deref()
+    }
+
+    /// The logical operator of this group.
+    pub fn kind(&self) -> GroupKind {
+        self.kind
+    }
+
+    /// Whether the group was satisfied.
+    pub fn is_satisfied(&self) -> bool {
+        self.satisfied
+    }
+
+    /// Reports for the children, in declaration order.
+    pub fn children(&self) -> &[NodeReport] {
+        &self.children
+    }
+
+    /// For a satisfied `OR` / `XOR` group, the index of the alternative that
+    /// satisfied it: the first one that passed, since later ones are skipped.
+    ///
+    /// `None` for `AND` groups and unsatisfied groups.
+    pub fn satisfied_branch(&self) -> Option<usize> {
+        if self.kind == GroupKind::All || !self.satisfied {
+            return None;
+        }
+        self.children.iter().position(NodeReport::is_satisfied)
+    }
+
+    /// The [`label`](NodeReport::label) of the
+    /// [`satisfied_branch`](Self::satisfied_branch).
+    pub fn satisfied_branch_label(&self) -> Option<String> {
+        self.satisfied_branch()
+            .map(|index| self.children[index].label())
+    }
+}
+
+/// A node of a [`CheckReport`], mirroring the shape of the checked expression.
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "snake_case")
+)]
+// Leaves far outnumber groups, so boxing them would cost more than it saves.
+#[allow(clippy::large_enum_variant)]
+pub enum NodeReport {
+    /// A file term.
+    Leaf(LeafReport),
+    /// An `AND` / `OR` / `XOR` group.
+    Group(GroupReport),
+}
+
+impl NodeReport {
+    /// Record on each file leaf the size the check saw, from the metadata
+    /// `cache` of the provider it used.
+    pub(crate) fn record_sizes(&mut self, cache: &CheckCache) {
+        match self {
+            NodeReport::Leaf(leaf) => {
+                leaf.size = match (&leaf.outcome, leaf.term.unwrapped()) {
+                    (LeafOutcome::SizeOutOfRange { actual }, _) => Some(*actual),
+                    (LeafOutcome::Skipped, _) => None,
+                    (
+                        _,
+                        FileRequirement::File(path)
+                        | FileRequirement::FileWith { path, .. }
+                        | FileRequirement::Recommended(path),
+                    ) => cache
+                        .metadata(path, true)
+                        .flatten()
+                        .filter(FileMetadata::is_file)
+                        .map(|meta| meta.len()),
+                    _ => None,
+                };
+            }
+            NodeReport::Group(group) => {
+                for child in &mut group.children {
+                    child.record_sizes(cache);
+                }
+            }
+        }
+    }
+
+    /// Whether this node was satisfied.
+    pub fn is_satisfied(&self) -> bool {
+        match self {
+            NodeReport::Leaf(leaf) => leaf.outcome.is_present(),
+            NodeReport::Group(group) => group.satisfied,
+        }
+    }
+
+    /// Whether the node was not evaluated because an enclosing group was
+    /// already decided.
+    pub fn is_skipped(&self) -> bool {
+        match self {
+            NodeReport::Leaf(leaf) => leaf.outcome == LeafOutcome::Skipped,
+            NodeReport::Group(group) => group.children.iter().all(NodeReport::is_skipped),
+        }
+    }
+
+    /// A short label: the node's name; otherwise a leaf's path, or the labels
+    /// of an `AND` group's parts joined by `+`, or the rendered expression.
+    pub fn label(&self) -> String {
+        match self {
+            NodeReport::Leaf(leaf) => match leaf.name() {
+                Some(name) => name.to_owned(),
+                None => leaf.path().display().to_string(),
+            },
+            NodeReport::Group(group) => match (&group.name, group.kind) {
+                (Some(name), _) => name.clone(),
+                (None, GroupKind::All) => group
+                    .children
+                    .iter()
+                    .map(NodeReport::label)
+                    .collect::<Vec<_>>()
+                    .join("+"),
+                (None, _) => self.to_string(),
+            },
+        }
+    }
+
+    /// The smallest set of failed leaves whose fixing satisfies this node, or
+    /// `None` if fixing leaves cannot help (an `XOR` group with several
+    /// satisfied alternatives).
+    fn minimal_fix(&self) -> Option<Vec<&LeafReport>> {
+        if self.is_satisfied() {
+            return Some(Vec::new());
+        }
+        let group = match self {
+            NodeReport::Leaf(leaf) => return Some(vec![leaf]),
+            NodeReport::Group(group) => group,
+        };
+        let cheapest = || {
+            group
+                .children
+                .iter()
+                .filter_map(NodeReport::minimal_fix)
+                .min_by_key(Vec::len)
+        };
+        match group.kind {
+            GroupKind::All => {
+                let mut fix = Vec::new();
+                for child in &group.children {
+                    fix.extend(child.minimal_fix()?);
+                }
+                Some(fix)
+            }
+            GroupKind::Any => cheapest(),
+            GroupKind::ExactlyOne if group.children.iter().any(NodeReport::is_satisfied) => None,
+            GroupKind::ExactlyOne => cheapest(),
+        }
+    }
+
+    fn collect_groups<'a>(&'a self, out: &mut Vec<&'a GroupReport>) {
+        if let NodeReport::Group(group) = self {
+            out.push(group);
+            for child in &group.children {
+                child.collect_groups(out);
+            }
+        }
+    }
+
+    fn collect_leaves_mut<'a>(&'a mut self, out: &mut Vec<&'a mut LeafReport>) {
+        match self {
+            NodeReport::Leaf(leaf) => out.push(leaf),
+            NodeReport::Group(group) => {
+                for child in &mut group.children {
+                    child.collect_leaves_mut(out);
+                }
+            }
+        }
+    }
+
+    fn collect_leaves<'a>(&'a self, out: &mut Vec<&'a LeafReport>) {
+        match self {
+            NodeReport::Leaf(leaf) => out.push(leaf),
+            NodeReport::Group(group) => {
+                for child in &group.children {
+                    child.collect_leaves(out);
+                }
+            }
+        }
+    }
+}
+
+impl NodeReport {
+    /// Append the rendered node to `out`, recording the byte range of every
+    /// failed leaf term in `spans`.
+    pub(crate) fn render_with_spans<'a>(
+        &'a self,
+        out: &mut String,
+        spans: &mut Vec<(Range<usize>, &'a LeafReport)>,
+    ) {
+        let name = match self {
+            NodeReport::Leaf(leaf) => leaf.name(),
+            NodeReport::Group(group) => group.name(),
+        };
+        if let Some(name) = name {
+            out.push_str(&format!("named({}, ", DisplayQuoted(name)));
+        }
+        match self {
+            NodeReport::Leaf(leaf) => {
+                let start = out.len();
+                out.push_str(&leaf.term().to_string());
+                if !self.is_satisfied() && !self.is_skipped() {
+                    spans.push((start..out.len(), leaf));
+                }
+            }
+            NodeReport::Group(group) => {
+                out.push('(');
+                for (i, child) in group.children.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(&format!(" {} ", group.kind.operator()));
+                    }
+                    child.render_with_spans(out, spans);
+                }
+                out.push(')');
+            }
+        }
+        if name.is_some() {
+            out.push(')');
+        }
+    }
+}
+
+impl fmt::Display for NodeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut rendered = String::new();
+        self.render_with_spans(&mut rendered, &mut Vec::new());
+        f.write_str(&rendered)
+    }
+}
+
+/// Structured result of checking a requirement expression.
+///
+/// With the `serde` feature enabled, a report serializes as a map with the
+/// overall `satisfied` flag, the `missing_files`, `io_errors` (path, error kind
+/// and message) and `unsatisfied_groups` of its failing parts, as listed by
+/// [`FileRequirementCheckError`](crate::FileRequirementCheckError), all `findings`, and the
+/// evaluated tree as `root`, so orchestrators can ingest failures without
+/// parsing the error message.
+#[derive(Debug, Clone)]
+pub struct CheckReport {
+    root: NodeReport,
+    metrics: CheckMetrics,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for CheckReport {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        #[derive(serde::Serialize)]
+        struct IoErrorEntry<'a> {
+            path: &'a Path,
+            #[serde(serialize_with = "serialize_error_kind")]
+            kind: io::ErrorKind,
+            message: String,
+        }
+
+        let mut ctx = crate::CheckContext::default();
+        ctx.collect(&self.root);
+        let io_errors: Vec<IoErrorEntry<'_>> = ctx
+            .failed_io
+            .iter()
+            .map(|(path, error)| IoErrorEntry {
+                path,
+                kind: error.kind(),
+                message: error.to_string(),
+            })
+            .collect();
+
+        let mut state = serializer.serialize_struct("CheckReport", 7)?;
+        state.serialize_field("satisfied", &self.is_satisfied())?;
+        state.serialize_field("missing_files", &ctx.missing_paths)?;
+        state.serialize_field("io_errors", &io_errors)?;
+        state.serialize_field("unsatisfied_groups", &ctx.unsatisfied_groups)?;
+        state.serialize_field("findings", &self.findings())?;
+        state.serialize_field("metrics", &self.metrics)?;
+        state.serialize_field("root", &self.root)?;
+        state.end()
+    }
+}
+
+impl CheckReport {
+    pub(crate) fn new(root: NodeReport) -> Self {
+        Self {
+            root,
+            metrics: CheckMetrics::default(),
+        }
+    }
+
+    pub(crate) fn with_metrics(mut self, metrics: CheckMetrics) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Whether the check was cancelled before every term was checked.
+    pub fn is_cancelled(&self) -> bool {
+        self.leaves()
+            .iter()
+            .any(|leaf| *leaf.outcome() == LeafOutcome::Cancelled)
+    }
+
+    /// Stat counts and timings gathered during the check.
+    pub fn metrics(&self) -> CheckMetrics {
+        self.metrics
+    }
+
+    /// Whether the whole expression was satisfied.
+    pub fn is_satisfied(&self) -> bool {
+        self.root.is_satisfied()
+    }
+
+    /// The report for the root node.
+    pub fn root(&self) -> &NodeReport {
+        &self.root
+    }
+
+    /// All file term reports, in depth-first declaration order.
+    pub fn leaves(&self) -> Vec<&LeafReport> {
+        let mut out = Vec::new();
+        self.root.collect_leaves(&mut out);
+        out
+    }
+
+    /// The smallest set of failed file terms whose creation (or repair) would
+    /// satisfy the whole expression, in declaration order.
+    ///
+    /// For each unsatisfied `OR` only the alternative needing the fewest fixes
+    /// is kept, so a wall of errors becomes "create these 2 files". Since a term
+    /// appears at most once in a tree, the result is exact when every term was
+    /// checked. Terms left [`Skipped`](LeafOutcome::Skipped),
+    /// [`Unresolved`](LeafOutcome::Unresolved) or
+    /// [`Cancelled`](LeafOutcome::Cancelled) count as failed, so after a
+    /// fail-fast, timed-out or cancelled check the set may include files that
+    /// already exist. Returns an empty set for a satisfied report, and `None`
+    /// when fixing terms cannot help because an `XOR` group has several
+    /// satisfied alternatives.
+    pub fn minimal_missing_set(&self) -> Option<Vec<&LeafReport>> {
+        self.root.minimal_fix()
+    }
+
+    pub(crate) fn leaves_mut(&mut self) -> Vec<&mut LeafReport> {
+        let mut out = Vec::new();
+        self.root.collect_leaves_mut(&mut out);
+        out
+    }
+
+    /// Every term whose check failed with an IO error, with that error, in
+    /// depth-first declaration order.
+    ///
+    /// Use [`io::Error::kind`] to tell, say, `PermissionDenied` from a transient
+    /// `Interrupted`.
+    pub fn io_errors(&self) -> Vec<(&Path, io::Error)> {
+        self.leaves()
+            .into_iter()
+            .filter_map(|leaf| Some((leaf.path(), leaf.outcome().io_error()?)))
+            .collect()
+    }
+
+    /// All group reports, in depth-first declaration order, starting with the root.
+    pub fn groups(&self) -> Vec<&GroupReport> {
+        let mut out = Vec::new();
+        self.root.collect_groups(&mut out);
+        out
+    }
+
+    /// The first group named `name`, e.g. to ask which alternative of
+    /// `require_any_named("index format", ...)` was used.
+    pub fn group(&self, name: &str) -> Option<&GroupReport> {
+        self.groups()
+            .into_iter()
+            .find(|group| group.name() == Some(name))
+    }
+
+    /// Non-fatal findings, such as outputs that exist but may be overwritten, or
+    /// recommended files that are absent (followed by their hint, if any).
+    pub fn warnings(&self) -> Vec<String> {
+        self.advisories()
+            .into_iter()
+            .map(|finding| finding.message)
+            .collect()
+    }
+
+    /// All findings: one [`Severity::Error`] per kind of failure, as in the
+    /// check error message, followed by the non-fatal findings of
+    /// [`warnings`](Self::warnings) with their severities.
+    pub fn findings(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        if !self.is_satisfied() {
+            let mut ctx = crate::CheckContext::default();
+            ctx.collect(&self.root);
+            findings.extend(
+                crate::FileRequirementCheckError::sections(ctx)
+                    .into_iter()
+                    .map(|section| Finding::new(Severity::Error, section)),
+            );
+        }
+        findings.extend(self.advisories());
+        findings
+    }
+
+    /// Whether the check passes when findings of `fail_on` or above are fatal.
+    pub fn passes(&self, fail_on: Severity) -> bool {
+        self.is_satisfied()
+            && self
+                .advisories()
+                .iter()
+                .all(|finding| finding.severity < fail_on)
+    }
+
+    /// Findings for satisfied leaves that still deserve attention.
+    pub(crate) fn advisories(&self) -> Vec<Finding> {
+        self.leaves()
+            .into_iter()
+            .filter_map(|leaf| {
+                let (severity, message) = match leaf.outcome() {
+                    LeafOutcome::OverwriteAllowed => (
+                        Severity::Info,
+                        format!("output will be overwritten: {}", leaf.subject()),
+                    ),
+                    LeafOutcome::RecommendedMissing => (
+                        Severity::Warning,
+                        format!("recommended file not found: {}", leaf.subject()),
+                    ),
+                    LeafOutcome::RecommendedUnreadable { message, .. } => (
+                        Severity::Warning,
+                        format!(
+                            "recommended file could not be checked: {} ({})",
+                            leaf.subject(),
+                            message
+                        ),
+                    ),
+                    _ => return None,
+                };
+                Some(Finding::new(
+                    severity,
+                    match leaf.hint() {
+                        Some(hint) => format!("{}; {}", message, hint),
+                        None => message,
+                    },
+                ))
+            })
+            .collect()
+    }
+}
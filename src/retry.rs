@@ -0,0 +1,111 @@
+use std::io;
+use std::thread;
+use std::time::Duration;
+
+use crate::LeafOutcome;
+
+/// When to re-check a term whose check failed with an IO error.
+///
+/// A term is retried while its error kind is one of the retryable kinds and
+/// attempts remain, sleeping between attempts with a backoff that doubles each
+/// time. By default only [`io::ErrorKind::Interrupted`] and
+/// [`io::ErrorKind::TimedOut`] are retried.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    backoff: Duration,
+    kinds: Vec<io::ErrorKind>,
+}
+
+impl RetryPolicy {
+    /// Retry up to `max_retries` times, waiting 100 ms before the first retry.
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            backoff: Duration::from_millis(100),
+            kinds: vec![io::ErrorKind::Interrupted, io::ErrorKind::TimedOut],
+        }
+    }
+
+    /// Wait `backoff` before the first retry, doubling it before each further
+    /// retry.
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Also retry errors of `kind`.
+    pub fn retry_on(mut self, kind: io::ErrorKind) -> Self {
+        if !self.kinds.contains(&kind) {
+            self.kinds.push(kind);
+        }
+        self
+    }
+
+    /// The maximum number of retries per term.
+    pub fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    /// Whether an error of `kind` is retried.
+    pub fn retries(&self, kind: io::ErrorKind) -> bool {
+        self.kinds.contains(&kind)
+    }
+
+    /// Run `check`, re-running it while it fails with a retryable IO error.
+    pub(crate) fn run(&self, mut check: impl FnMut() -> LeafOutcome) -> LeafOutcome {
+        let mut backoff = self.backoff;
+        let mut outcome = check();
+        for _ in 0..self.max_retries {
+            match &outcome {
+                LeafOutcome::IoError { kind, .. }
+                | LeafOutcome::RecommendedUnreadable { kind, .. }
+                    if self.retries(*kind) => {}
+                _ => break,
+            }
+            thread::sleep(backoff);
+            backoff = backoff.saturating_mul(2);
+            outcome = check();
+        }
+        outcome
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RetryPolicy;
+    use crate::LeafOutcome;
+    use std::io;
+    use std::time::Duration;
+
+    #[test]
+    fn retries_only_retryable_errors_up_to_the_limit() {
+        let policy = RetryPolicy::new(3).backoff(Duration::ZERO);
+        let error = |kind| LeafOutcome::from_io_error(io::Error::from(kind));
+
+        let mut attempts = 0;
+        let outcome = policy.run(|| {
+            attempts += 1;
+            if attempts < 3 {
+                error(io::ErrorKind::Interrupted)
+            } else {
+                LeafOutcome::Present
+            }
+        });
+        assert_eq!((outcome, attempts), (LeafOutcome::Present, 3));
+
+        attempts = 0;
+        policy.run(|| {
+            attempts += 1;
+            error(io::ErrorKind::TimedOut)
+        });
+        assert_eq!(attempts, 4);
+
+        attempts = 0;
+        policy.run(|| {
+            attempts += 1;
+            error(io::ErrorKind::PermissionDenied)
+        });
+        assert_eq!(attempts, 1);
+    }
+}
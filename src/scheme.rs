@@ -0,0 +1,480 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::{
+    Access, FileMetadata, FileProvider, FileRequirement, FileRequirementBuildError,
+    FileRequirementParseError, StdFs,
+};
+
+/// The URI scheme of `text`, such as `s3` for `s3://bucket/key`.
+///
+/// Single-letter schemes are not recognized, so Windows drive letters are
+/// never mistaken for one.
+pub(crate) fn scheme_of(text: &str) -> Option<&str> {
+    let (scheme, _) = text.split_once("://")?;
+    let mut chars = scheme.chars();
+    let valid = scheme.len() > 1
+        && chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    valid.then_some(scheme)
+}
+
+fn path_scheme(path: &Path) -> Option<&str> {
+    path.to_str().and_then(scheme_of)
+}
+
+/// Terms claimed so far by one builder or parse, and the URI schemes their
+/// paths may use.
+#[derive(Debug, Default)]
+pub(crate) struct SeenTerms {
+    terms: HashSet<PathBuf>,
+    schemes: BTreeSet<String>,
+    /// Whether `OR` / `XOR` branches may claim the same terms.
+    shared: bool,
+    /// Whether duplicate checks are left to a later [`claim_tree`](Self::claim_tree)
+    /// of the enclosing group.
+    deferred: bool,
+    /// Errors recorded instead of returned, when collecting.
+    errors: Option<Vec<FileRequirementBuildError>>,
+    /// Whether the last term was left out after recording an error.
+    skipped: bool,
+    /// The directory relative paths are claimed under.
+    base: Option<PathBuf>,
+}
+
+impl SeenTerms {
+    pub(crate) fn allow_schemes<'s>(&mut self, schemes: impl IntoIterator<Item = &'s str>) {
+        self.schemes.extend(schemes.into_iter().map(str::to_owned));
+    }
+
+    /// Let the branches of `OR` / `XOR` groups claim the same terms.
+    pub(crate) fn share_terms(&mut self) {
+        self.shared = true;
+    }
+
+    pub(crate) fn shares_terms(&self) -> bool {
+        self.shared
+    }
+
+    /// Claim relative paths added from now on as joined onto `base`, as the
+    /// built tree will check them.
+    pub(crate) fn rebase_onto(&mut self, base: &Path) {
+        self.base = Some(base.to_path_buf());
+    }
+
+    /// `path` as it will be checked: joined onto the base directory when
+    /// relative and without a URI scheme.
+    fn rebased<'p>(&self, path: &'p Path) -> Cow<'p, Path> {
+        match &self.base {
+            Some(base) if path.is_relative() && path_scheme(path).is_none() => {
+                Cow::Owned(base.join(path))
+            }
+            _ => Cow::Borrowed(path),
+        }
+    }
+
+    /// Claims for building the branches of a disjunction when terms are
+    /// shared: schemes are checked, duplicates only once the group is
+    /// claimed as a whole.
+    pub(crate) fn deferred(&self) -> SeenTerms {
+        SeenTerms {
+            terms: HashSet::new(),
+            schemes: self.schemes.clone(),
+            shared: true,
+            deferred: true,
+            errors: self.errors.as_ref().map(|_| Vec::new()),
+            skipped: false,
+            base: self.base.clone(),
+        }
+    }
+
+    /// Record errors from now on instead of returning them.
+    pub(crate) fn collect_errors(&mut self) {
+        self.errors.get_or_insert_with(Vec::new);
+    }
+
+    /// Return `error`, or record it when collecting.
+    pub(crate) fn report(
+        &mut self,
+        error: FileRequirementBuildError,
+    ) -> Result<(), FileRequirementBuildError> {
+        match &mut self.errors {
+            Some(errors) => {
+                errors.push(error);
+                self.skipped = true;
+                Ok(())
+            }
+            None => Err(error),
+        }
+    }
+
+    /// Note that a term was added, so hints attach to it.
+    pub(crate) fn mark_added(&mut self) {
+        self.skipped = false;
+    }
+
+    /// Whether the last term was left out after recording an error, so there
+    /// is nothing for a hint to attach to.
+    pub(crate) fn last_skipped(&self) -> bool {
+        self.skipped
+    }
+
+    /// Keep the errors recorded while building the branches of a group.
+    pub(crate) fn absorb_errors(&mut self, branches: SeenTerms) {
+        if let (Some(errors), Some(branch_errors)) = (&mut self.errors, branches.errors) {
+            errors.extend(branch_errors);
+        }
+    }
+
+    pub(crate) fn take_errors(&mut self) -> Vec<FileRequirementBuildError> {
+        self.errors.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// Claim `term`, rejecting a second claim of the same term.
+    pub(crate) fn claim(&mut self, term: &Path) -> Result<(), FileRequirementBuildError> {
+        if self.deferred {
+            return Ok(());
+        }
+        if !self.terms.insert(term.to_path_buf()) {
+            return self.report(FileRequirementBuildError::DuplicateFile {
+                path: term.display().to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Claim the path of a file, directory or glob term, which may only use an
+    /// allowed URI scheme.
+    pub(crate) fn claim_path(&mut self, path: &Path) -> Result<(), FileRequirementBuildError> {
+        self.check_scheme(path)?;
+        let path = self.rebased(path).into_owned();
+        self.claim(&path)
+    }
+
+    /// Claim the name of an executable, which is looked up on `PATH` rather
+    /// than rebased.
+    pub(crate) fn claim_executable(
+        &mut self,
+        name: &Path,
+    ) -> Result<(), FileRequirementBuildError> {
+        self.check_scheme(name)?;
+        self.claim(name)
+    }
+
+    fn check_scheme(&mut self, path: &Path) -> Result<(), FileRequirementBuildError> {
+        match path_scheme(path) {
+            Some(scheme) if !self.schemes.contains(scheme) => {
+                self.report(FileRequirementBuildError::UnknownScheme {
+                    scheme: scheme.to_owned(),
+                    path: path.display().to_string(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Claim `member` of `archive`, keyed as `archive!/member`.
+    pub(crate) fn claim_member(
+        &mut self,
+        archive: &Path,
+        member: &str,
+    ) -> Result<(), FileRequirementBuildError> {
+        self.claim_path(Path::new(&format!("{}!/{}", archive.display(), member)))
+    }
+
+    /// Claim every term of an already built `tree` as the builder would have,
+    /// without checking URI schemes.
+    ///
+    /// When terms are shared, each branch of an `OR` / `XOR` group is checked
+    /// only against the terms claimed outside the group, and a branch equal
+    /// to an earlier one is rejected.
+    pub(crate) fn claim_tree(
+        &mut self,
+        tree: &FileRequirement,
+    ) -> Result<(), FileRequirementBuildError> {
+        match tree {
+            FileRequirement::Named { term, .. } | FileRequirement::Hinted { term, .. } => {
+                self.claim_tree(term)
+            }
+            FileRequirement::Any(children) | FileRequirement::ExactlyOne(children)
+                if self.shared =>
+            {
+                let outside = self.terms.clone();
+                let mut claimed = outside.clone();
+                for (i, child) in children.iter().enumerate() {
+                    if children[..i].contains(child) {
+                        self.report(FileRequirementBuildError::DuplicateFile {
+                            path: child.to_string(),
+                        })?;
+                        continue;
+                    }
+                    self.terms = outside.clone();
+                    self.claim_tree(child)?;
+                    claimed.extend(self.terms.drain());
+                }
+                self.terms = claimed;
+                Ok(())
+            }
+            FileRequirement::All(children)
+            | FileRequirement::Any(children)
+            | FileRequirement::ExactlyOne(children) => {
+                children.iter().try_for_each(|child| self.claim_tree(child))
+            }
+            FileRequirement::ArchiveMember { archive, member } => {
+                let archive = self.rebased(archive).display().to_string();
+                self.claim(Path::new(&format!("{archive}!/{member}")))
+            }
+            FileRequirement::EnvVar { .. }
+            | FileRequirement::FreeSpace { .. }
+            | FileRequirement::SameFilesystem { .. } => Ok(()),
+            FileRequirement::Executable(_) | FileRequirement::Url(_) => {
+                self.claim(tree.leaf_path())
+            }
+            leaf => {
+                let path = self.rebased(leaf.leaf_path()).into_owned();
+                self.claim(&path)
+            }
+        }
+    }
+}
+
+type Backend = Arc<dyn FileProvider + Send + Sync>;
+
+/// A [`FileProvider`] that dispatches each path on its URI scheme.
+///
+/// Paths without a scheme, and `file://` paths, are checked on the local
+/// filesystem; paths such as `s3://bucket/key` go to the backend registered
+/// for their scheme. A builder given the registry with
+/// [`FileRequirementBuilder::schemes`](crate::FileRequirementBuilder::schemes)
+/// rejects terms whose scheme has no backend, so a tree checked against the
+/// registry never meets one at check time.
+///
+/// ```
+/// use std::collections::HashSet;
+/// use std::path::PathBuf;
+/// use file_requirements::{FileRequirementBuilder, SchemeRegistry};
+///
+/// let bucket: HashSet<PathBuf> = [PathBuf::from("s3://refs/genome.fa")].into();
+/// let mut schemes = SchemeRegistry::new();
+/// schemes.register("s3", bucket);
+///
+/// let mut b = FileRequirementBuilder::new();
+/// b.schemes(&schemes);
+/// b.require_file("s3://refs/genome.fa")?;
+/// assert!(b.require_file("gs://refs/genome.fa").is_err());
+/// b.build().check_with_provider(&schemes)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone)]
+pub struct SchemeRegistry {
+    local: Backend,
+    backends: BTreeMap<String, Backend>,
+}
+
+impl std::fmt::Debug for SchemeRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SchemeRegistry")
+            .field("schemes", &self.schemes().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl SchemeRegistry {
+    /// A registry that knows only local paths and the `file` scheme, both
+    /// checked through [`StdFs`].
+    pub fn new() -> Self {
+        Self {
+            local: Arc::new(StdFs),
+            backends: BTreeMap::new(),
+        }
+    }
+
+    /// Check local and `file://` paths with `provider` instead of [`StdFs`].
+    pub fn local<P: FileProvider + Send + Sync + 'static>(&mut self, provider: P) -> &mut Self {
+        self.local = Arc::new(provider);
+        self
+    }
+
+    /// Check paths using `scheme` with `backend`, which receives them whole,
+    /// scheme included. A later registration of the same scheme replaces it.
+    pub fn register<S, P>(&mut self, scheme: S, backend: P) -> &mut Self
+    where
+        S: Into<String>,
+        P: FileProvider + Send + Sync + 'static,
+    {
+        self.backends.insert(scheme.into(), Arc::new(backend));
+        self
+    }
+
+    /// Check `http://` and `https://` file terms with a `HEAD` request, as
+    /// [`require_url`](crate::GroupBuilder::require_url) terms are.
+    ///
+    /// Against [`StdFs`] this requires the `http` feature.
+    pub fn register_http(&mut self) -> &mut Self {
+        self.register("http", Http);
+        self.register("https", Http)
+    }
+
+    /// The schemes this registry can check, `file` included.
+    pub fn schemes(&self) -> impl Iterator<Item = &str> {
+        std::iter::once("file").chain(self.backends.keys().map(String::as_str))
+    }
+
+    /// Parse `text` as [`FileRequirement`]'s `FromStr` impl does, also
+    /// accepting terms that use one of this registry's schemes.
+    pub fn parse(&self, text: &str) -> Result<FileRequirement, FileRequirementParseError> {
+        crate::parse::parse_with_schemes(text, self.schemes())
+    }
+
+    /// The provider for `path`, and the path it should be given.
+    fn route<'p>(&self, path: &'p Path) -> io::Result<(&Backend, Cow<'p, Path>)> {
+        match path_scheme(path) {
+            None => Ok((&self.local, Cow::Borrowed(path))),
+            Some("file") => {
+                let text = path.to_str().unwrap_or_default();
+                Ok((
+                    &self.local,
+                    Cow::Owned(PathBuf::from(&text["file://".len()..])),
+                ))
+            }
+            Some(scheme) => self
+                .backends
+                .get(scheme)
+                .map(|backend| (backend, Cow::Borrowed(path)))
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::Unsupported,
+                        format!("no backend registered for URI scheme `{}`", scheme),
+                    )
+                }),
+        }
+    }
+}
+
+impl Default for SchemeRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FileProvider for SchemeRegistry {
+    fn try_exists(&self, path: &Path) -> io::Result<bool> {
+        let (backend, path) = self.route(path)?;
+        backend.try_exists(&path)
+    }
+
+    fn glob(&self, pattern: &str) -> io::Result<Vec<PathBuf>> {
+        let (backend, pattern) = self.route(Path::new(pattern))?;
+        backend.glob(&pattern.to_string_lossy())
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<FileMetadata> {
+        let (backend, path) = self.route(path)?;
+        backend.metadata(&path)
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> io::Result<FileMetadata> {
+        let (backend, path) = self.route(path)?;
+        backend.symlink_metadata(&path)
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let (backend, path) = self.route(path)?;
+        backend.read_dir(&path)
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn io::Read + '_>> {
+        let (backend, path) = self.route(path)?;
+        backend.open(&path)
+    }
+
+    fn available_space(&self, path: &Path) -> io::Result<u64> {
+        let (backend, path) = self.route(path)?;
+        backend.available_space(&path)
+    }
+
+    fn access(&self, path: &Path, access: Access) -> io::Result<bool> {
+        let (backend, path) = self.route(path)?;
+        backend.access(&path, access)
+    }
+
+    fn archive_contains(&self, archive: &Path, member: &str) -> io::Result<bool> {
+        let (backend, archive) = self.route(archive)?;
+        backend.archive_contains(&archive, member)
+    }
+
+    fn url_exists(&self, url: &str) -> io::Result<bool> {
+        match scheme_of(url).and_then(|scheme| self.backends.get(scheme)) {
+            Some(backend) => backend.url_exists(url),
+            None => self.local.url_exists(url),
+        }
+    }
+
+    fn invalidate(&self, path: &Path) {
+        if let Ok((backend, path)) = self.route(path) {
+            backend.invalidate(&path)
+        }
+    }
+}
+
+/// Answers existence queries for `http(s)://` paths with a `HEAD` request.
+struct Http;
+
+impl FileProvider for Http {
+    fn try_exists(&self, path: &Path) -> io::Result<bool> {
+        self.url_exists(&path.to_string_lossy())
+    }
+
+    fn url_exists(&self, url: &str) -> io::Result<bool> {
+        StdFs.url_exists(url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FileRequirementBuilder;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn terms_of_different_schemes_are_checked_by_their_backend() {
+        let td = tempdir().unwrap();
+        let local = td.path().join("reads.fq");
+        fs::write(&local, "").unwrap();
+        let bucket: HashSet<PathBuf> = [PathBuf::from("s3://refs/genome.fa")].into();
+        let mut schemes = SchemeRegistry::new();
+        schemes.register("s3", bucket);
+
+        let mut b = FileRequirementBuilder::new();
+        b.schemes(&schemes);
+        b.require_file(&local).unwrap();
+        b.require_file(format!("file://{}", local.display()))
+            .unwrap();
+        b.require_file("s3://refs/genome.fa").unwrap();
+        b.require_file("s3://refs/genome.fa.fai").unwrap();
+        let req = b.build();
+
+        let err = req.check_with_provider(&schemes).unwrap_err();
+        assert_eq!(
+            err.missing_files(),
+            [PathBuf::from("s3://refs/genome.fa.fai")]
+        );
+        assert_eq!(schemes.parse(&req.to_string()).unwrap(), req);
+    }
+
+    #[test]
+    fn unregistered_schemes_are_rejected_when_building() {
+        let mut b = FileRequirementBuilder::new();
+        let err = b.require_file("gs://refs/genome.fa").err().unwrap();
+        assert!(matches!(
+            err,
+            FileRequirementBuildError::UnknownScheme { ref scheme, .. } if scheme == "gs"
+        ));
+        assert!("gs://refs/genome.fa".parse::<FileRequirement>().is_err());
+        assert!(b.require_file(r"C:\refs\genome.fa").is_ok());
+    }
+}
@@ -0,0 +1,175 @@
+use std::ops::Range;
+
+use crate::{FileProvider, FileRequirementBuildError, LeafOutcome};
+
+/// Stands in for the shard placeholder while the rest of a template is
+/// rewritten, since no path can contain it.
+const SENTINEL: char = '\0';
+
+/// A template split around its shard placeholder: `part_{:03}.rad` becomes
+/// `part_`, a width of 3, and `.rad`.
+struct Split<'t> {
+    head: &'t str,
+    width: usize,
+    spec: &'t str,
+    tail: &'t str,
+}
+
+/// Split `template` around its single `{}` / `{:0N}` shard placeholder.
+///
+/// Named placeholders such as `{prefix}` are left for
+/// [`FileRequirement::resolve`](crate::FileRequirement::resolve).
+fn split(template: &str) -> Result<Split<'_>, String> {
+    let mut found = None;
+    for (open, _) in template
+        .match_indices("{}")
+        .chain(template.match_indices("{:"))
+    {
+        if found.is_some() {
+            return Err("expected a single shard placeholder".to_owned());
+        }
+        let close = open
+            + template[open..]
+                .find('}')
+                .ok_or("unclosed shard placeholder")?;
+        found = Some((open, close));
+    }
+    let (open, close) = found.ok_or("expected a `{}` or `{:0N}` shard placeholder")?;
+    let spec = &template[open..=close];
+    let width = match &spec[1..spec.len() - 1] {
+        "" => 0,
+        format => format
+            .strip_prefix(":0")
+            .and_then(|width| width.parse().ok())
+            .ok_or_else(|| format!("unsupported shard placeholder `{}`", spec))?,
+    };
+    Ok(Split {
+        head: &template[..open],
+        width,
+        spec,
+        tail: &template[close + 1..],
+    })
+}
+
+/// Check that `template` has one placeholder, and `min` is reachable in `range`.
+pub(crate) fn validate(
+    template: &str,
+    range: &Range<usize>,
+    min: usize,
+) -> Result<(), FileRequirementBuildError> {
+    let invalid = |message: String| FileRequirementBuildError::InvalidShards {
+        template: template.to_owned(),
+        message,
+    };
+    split(template).map_err(invalid)?;
+    if range.is_empty() {
+        return Err(invalid(format!("empty shard range {:?}", range)));
+    }
+    if min > range.len() {
+        return Err(invalid(format!(
+            "cannot require {} of the {} shards in {:?}",
+            min,
+            range.len(),
+            range
+        )));
+    }
+    Ok(())
+}
+
+/// The path of shard `index` of a validated `template`.
+pub(crate) fn expand(template: &str, index: usize) -> String {
+    match split(template) {
+        Ok(split) => format!(
+            "{}{:0width$}{}",
+            split.head,
+            index,
+            split.tail,
+            width = split.width
+        ),
+        Err(_) => template.to_owned(),
+    }
+}
+
+/// `template` rewritten by `f`, which sees the placeholder as a character no
+/// path contains, so path rewrites such as `{name}` substitution leave it be.
+pub(crate) fn map_template(template: &str, f: impl FnOnce(&str) -> String) -> String {
+    match split(template) {
+        Ok(split) => f(&format!("{}{}{}", split.head, SENTINEL, split.tail))
+            .replacen(SENTINEL, split.spec, 1),
+        Err(_) => f(template),
+    }
+}
+
+/// Count the shards of `range` that exist, passing when at least `min` do.
+pub(crate) fn evaluate<P: FileProvider + ?Sized>(
+    template: &str,
+    range: Range<usize>,
+    min: usize,
+    provider: &P,
+) -> LeafOutcome {
+    let mut found = 0;
+    for index in range {
+        match provider.try_exists(expand(template, index).as_ref()) {
+            Ok(true) => found += 1,
+            Ok(false) => {}
+            Err(e) => return LeafOutcome::from_io_error(e),
+        }
+    }
+    if found >= min {
+        LeafOutcome::Present
+    } else {
+        LeafOutcome::TooFewMatches { found }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FileRequirement, FileRequirementBuilder};
+    use std::collections::HashSet;
+    use std::path::PathBuf;
+
+    #[test]
+    fn templates_expand_with_optional_zero_padding() {
+        assert_eq!(expand("part_{}.rad", 7), "part_7.rad");
+        assert_eq!(expand("shard-{:04}.bin", 7), "shard-0007.bin");
+        assert_eq!(
+            map_template("{out}/part_{}.rad", |t| t.replace("{out}", "run1")),
+            "run1/part_{}.rad"
+        );
+        assert!(validate("part.rad", &(0..4), 0).is_err());
+        assert!(validate("part_{}_{}.rad", &(0..4), 0).is_err());
+        assert!(validate("part_{}.rad", &(0..4), 5).is_err());
+    }
+
+    #[test]
+    fn shards_expand_to_terms_or_an_at_least_count() {
+        let mut b = FileRequirementBuilder::new();
+        b.require_shards("part_{}.rad", 0..3).unwrap();
+        assert_eq!(
+            b.build().to_string(),
+            "(part_0.rad AND part_1.rad AND part_2.rad)"
+        );
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_shards_at_least("out/part_{:02}.rad", 0..4, 3)
+            .unwrap();
+        let req = b.build();
+        assert_eq!(
+            req.to_string(),
+            r#"(shards("out/part_{:02}.rad", 0..4, 3))"#
+        );
+        assert_eq!(req.to_string().parse::<FileRequirement>().unwrap(), req);
+
+        let written: HashSet<PathBuf> = ["out/part_00.rad", "out/part_01.rad", "out/part_03.rad"]
+            .into_iter()
+            .map(PathBuf::from)
+            .collect();
+        assert!(req.check_with_provider(&written).is_ok());
+        let partial: HashSet<PathBuf> = [PathBuf::from("out/part_00.rad")].into();
+        let err = req.check_with_provider(&partial).unwrap_err().to_string();
+        assert!(
+            err.contains("too few shards: out/part_{:02}.rad (found 1 of 0..4, need at least 3)")
+        );
+    }
+}
@@ -0,0 +1,447 @@
+use std::path::Path;
+
+use crate::eval::check_env_var_name;
+use crate::{FileConstraints, FileRequirement, Size, shards};
+
+impl FileRequirement {
+    /// Render the expression as a POSIX shell condition, such as
+    /// `[ -e 'idx.ctab' ] && { [ -e 'idx.sshash' ] || [ -e 'idx.ssi' ]; }`,
+    /// for places that cannot run this crate: cluster prologue scripts or a
+    /// Docker `HEALTHCHECK CMD sh -c '...'`.
+    ///
+    /// The condition exits with 0 when the requirement is satisfied. Hints
+    /// and missing recommended files are printed to stderr. Glob patterns are
+    /// expanded by the shell, so `**` matches a single directory level.
+    ///
+    /// Some checks rely on common tools beyond POSIX `sh`: `curl` for URLs,
+    /// `tar` / `unzip` for archive members, `sha256sum` / `md5sum` for
+    /// digests and `gzip` for intact gzip files. Age, stability, owner, group
+    /// and magic-byte constraints are not checked by the script. An
+    /// environment variable term whose name is not a valid shell identifier
+    /// fails with a message instead of being interpolated.
+    pub fn to_shell_script(&self) -> String {
+        let condition = condition(self);
+        // Outermost braces group nothing.
+        let condition = condition
+            .strip_prefix("{ ")
+            .and_then(|inner| inner.strip_suffix("; }"))
+            .unwrap_or(&condition);
+        format!("{}\n", condition)
+    }
+}
+
+fn condition(node: &FileRequirement) -> String {
+    match node {
+        FileRequirement::File(path) => test("-e", path),
+        FileRequirement::FileWith { path, constraints } => file_with(path, constraints),
+        FileRequirement::Glob {
+            pattern,
+            min_matches,
+        } => count_glob(&glob(pattern), *min_matches),
+        FileRequirement::GlobSize { pattern, min_bytes } => format!(
+            "( n=0; for f in {}; do [ -f \"$f\" ] && n=$((n + $(wc -c < \"$f\"))); done; [ \"$n\" -ge {} ] )",
+            glob(pattern),
+            min_bytes
+        ),
+        FileRequirement::Shards {
+            template,
+            start,
+            end,
+            min,
+        } => {
+            let paths: Vec<String> = (*start..*end)
+                .map(|index| quote(&shards::expand(template, index)))
+                .collect();
+            format!(
+                "( n=0; for f in {}; do [ -e \"$f\" ] && n=$((n+1)); done; [ \"$n\" -ge {} ] )",
+                paths.join(" "),
+                min
+            )
+        }
+        FileRequirement::Dir { path, non_empty } => {
+            if *non_empty {
+                format!(
+                    "{{ {} && [ -n \"$(ls -A {} 2>/dev/null)\" ]; }}",
+                    test("-d", path),
+                    quote_path(path)
+                )
+            } else {
+                test("-d", path)
+            }
+        }
+        FileRequirement::DirEntries {
+            path,
+            pattern,
+            min_entries,
+        } => match pattern {
+            Some(pattern) => format!(
+                "( cd {} 2>/dev/null && {} )",
+                quote_path(path),
+                count_glob(&glob(pattern), *min_entries)
+            ),
+            None => format!(
+                "{{ {} && [ $(ls -A {} | wc -l) -ge {} ]; }}",
+                test("-d", path),
+                quote_path(path),
+                min_entries
+            ),
+        },
+        FileRequirement::Absent(path) => test("! -e", path),
+        FileRequirement::Recommended(path) => format!(
+            "{{ {} || echo {} >&2; }}",
+            test("-e", path),
+            quote(&format!(
+                "warning: recommended file `{}` is missing",
+                path.display()
+            ))
+        ),
+        FileRequirement::WritableDir(path) => format!(
+            "( {} [ -d \"$d\" ] && [ -w \"$d\" ] )",
+            nearest_existing("d", path)
+        ),
+        FileRequirement::SameFilesystem { path, other } => format!(
+            "( {} {} m=$(df -P -- \"$d\" | awk 'NR==2 {{print $6}}') && n=$(df -P -- \"$e\" | awk 'NR==2 {{print $6}}') && [ -n \"$m\" ] && [ \"$m\" = \"$n\" ] )",
+            nearest_existing("d", path),
+            nearest_existing("e", other)
+        ),
+        FileRequirement::FreeSpace { path, bytes } => format!(
+            "( {} n=$(df -Pk -- \"$d\" | awk 'NR==2 {{print $4}}') && [ -n \"$n\" ] && [ \"$n\" -ge {} ] )",
+            nearest_existing("d", path),
+            bytes.div_ceil(1024)
+        ),
+        FileRequirement::EnvVar { name, .. } if check_env_var_name(name).is_err() => format!(
+            "{{ echo {} >&2; false; }}",
+            quote(&format!("invalid environment variable name `{}`", name))
+        ),
+        FileRequirement::EnvVar {
+            name,
+            non_empty,
+            pattern,
+        } => {
+            let mut tests = vec![format!("[ -n \"${{{}+x}}\" ]", name)];
+            if *non_empty {
+                tests.push(format!("[ -n \"${{{}:-}}\" ]", name));
+            }
+            if let Some(pattern) = pattern {
+                tests.push(format!(
+                    "printf '%s\\n' \"${{{}:-}}\" | grep -Eqx -- {}",
+                    name,
+                    quote(pattern)
+                ));
+            }
+            conjunction(tests)
+        }
+        FileRequirement::Executable(program) if program.components().count() > 1 => {
+            conjunction(vec![test("-f", program), test("-x", program)])
+        }
+        FileRequirement::Executable(program) => {
+            format!("command -v {} >/dev/null 2>&1", quote_path(program))
+        }
+        FileRequirement::Url(url) => format!("curl -fsIL {} >/dev/null 2>&1", quote(url)),
+        FileRequirement::ArchiveMember { archive, member } => {
+            let is_zip = archive
+                .extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("zip"));
+            let list = if is_zip { "unzip -Z1" } else { "tar -tf" };
+            format!(
+                "{} {} 2>/dev/null | grep -qxF -- {}",
+                list,
+                quote_path(archive),
+                quote(member)
+            )
+        }
+        FileRequirement::Named { term, .. } => condition(term),
+        FileRequirement::Hinted { hint, term } => format!(
+            "{{ {} || {{ echo {} >&2; false; }}; }}",
+            condition(term),
+            quote(&format!("hint: {}", hint))
+        ),
+        FileRequirement::All(children) if children.is_empty() => "true".to_owned(),
+        FileRequirement::Any(children) if children.is_empty() => "false".to_owned(),
+        FileRequirement::All(children) => group(children, " && "),
+        FileRequirement::Any(children) => group(children, " || "),
+        FileRequirement::ExactlyOne(children) => {
+            let counts: Vec<String> = children
+                .iter()
+                .map(|child| format!("{} && n=$((n+1));", condition(child)))
+                .collect();
+            format!("( n=0; {} [ \"$n\" -eq 1 ] )", counts.join(" "))
+        }
+    }
+}
+
+fn group(children: &[FileRequirement], operator: &str) -> String {
+    let conditions: Vec<String> = children.iter().map(condition).collect();
+    if conditions.len() == 1 {
+        return conditions.into_iter().next().unwrap_or_default();
+    }
+    format!("{{ {}; }}", conditions.join(operator))
+}
+
+/// The tests joined with `&&`, braced if there are several.
+fn conjunction(tests: Vec<String>) -> String {
+    if tests.len() == 1 {
+        tests.into_iter().next().unwrap_or_default()
+    } else {
+        format!("{{ {}; }}", tests.join(" && "))
+    }
+}
+
+fn file_with(path: &Path, constraints: &FileConstraints) -> String {
+    let mut tests = vec![test("-e", path)];
+    for (enabled, flag) in [
+        (constraints.regular_file, "-f"),
+        (constraints.readable, "-r"),
+        (constraints.writable, "-w"),
+        (constraints.executable, "-x"),
+    ] {
+        if enabled {
+            tests.push(test(flag, path));
+        }
+    }
+    let byte_count = || format!("$(wc -c 2>/dev/null < {})", quote_path(path));
+    match constraints.size {
+        Some(Size::AtLeast(1)) => tests.push(test("-s", path)),
+        Some(Size::AtLeast(min)) => tests.push(format!("[ {} -ge {} ]", byte_count(), min)),
+        Some(Size::AtMost(max)) => tests.push(format!("[ {} -le {} ]", byte_count(), max)),
+        Some(Size::Between(min, max)) => tests.push(format!(
+            "{{ n={}; [ \"$n\" -ge {} ] && [ \"$n\" -le {} ]; }}",
+            byte_count(),
+            min,
+            max
+        )),
+        None => {}
+    }
+    if let Some(dependency) = &constraints.newer_than {
+        tests.push(format!(
+            "[ {} -nt {} ]",
+            quote_path(path),
+            quote_path(dependency)
+        ));
+    }
+    #[cfg(feature = "checksum")]
+    if let Some(digest) = &constraints.digest {
+        tests.push(format!(
+            "[ \"$({}sum < {} | cut -d' ' -f1)\" = {} ]",
+            digest.algorithm(),
+            quote_path(path),
+            quote(digest.hex())
+        ));
+    }
+    #[cfg(feature = "gzip")]
+    if constraints.intact_gzip {
+        tests.push(format!("gzip -t {} 2>/dev/null", quote_path(path)));
+    }
+    conjunction(tests)
+}
+
+/// Statements setting the shell variable `var` to `path` or, if that does not
+/// exist yet, its nearest existing ancestor, as the check resolves it.
+fn nearest_existing(var: &str, path: &Path) -> String {
+    format!(
+        "{var}={}; while [ ! -e \"${var}\" ]; do {var}=$(dirname -- \"${var}\"); done;",
+        quote_path(path)
+    )
+}
+
+/// A subshell that succeeds when the shell glob `pattern` matches at least
+/// `min` paths.
+fn count_glob(pattern: &str, min: usize) -> String {
+    if min == 0 {
+        return "true".to_owned();
+    }
+    format!(
+        "( set -- {}; [ -e \"$1\" ] && [ \"$#\" -ge {} ] )",
+        pattern, min
+    )
+}
+
+fn test(flag: &str, path: &Path) -> String {
+    format!("[ {} {} ]", flag, quote_path(path))
+}
+
+fn quote_path(path: &Path) -> String {
+    quote(&path.to_string_lossy())
+}
+
+/// `text` single-quoted for the shell.
+fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
+
+/// A glob `pattern` for the shell: `*`, `?` and bracket expressions stay
+/// unquoted so the shell expands them, and everything else is quoted.
+fn glob(pattern: &str) -> String {
+    let mut out = String::new();
+    let mut literal = String::new();
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        let special = match c {
+            '*' => {
+                while chars.peek() == Some(&'*') {
+                    chars.next();
+                }
+                "*".to_owned()
+            }
+            '?' => "?".to_owned(),
+            '[' => {
+                let mut class = String::from("[");
+                for c in chars.by_ref() {
+                    class.push(c);
+                    if c == ']' && class.len() > 2 {
+                        break;
+                    }
+                }
+                class
+            }
+            c => {
+                literal.push(c);
+                continue;
+            }
+        };
+        if !literal.is_empty() {
+            out.push_str(&quote(&literal));
+            literal.clear();
+        }
+        out.push_str(&special);
+    }
+    if !literal.is_empty() || out.is_empty() {
+        out.push_str(&quote(&literal));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FileRequirementBuilder;
+
+    #[test]
+    fn groups_become_shell_conditions() {
+        let mut b = FileRequirementBuilder::new();
+        b.require_file("idx/idx.ctab").unwrap();
+        b.require_any(|any| {
+            any.require_file("idx/idx.sshash")?;
+            any.require_file("it's.ssi")?;
+            Ok(())
+        })
+        .unwrap();
+        b.require_glob("reads/*.fq.gz", 2).unwrap();
+        assert_eq!(
+            b.build().to_shell_script(),
+            concat!(
+                "[ -e 'idx/idx.ctab' ]",
+                " && { [ -e 'idx/idx.sshash' ] || [ -e 'it'\\''s.ssi' ]; }",
+                " && ( set -- 'reads/'*'.fq.gz'; [ -e \"$1\" ] && [ \"$#\" -ge 2 ] )\n"
+            )
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn scripts_agree_with_the_check() {
+        use std::fs;
+        use std::process::Command;
+        use tempfile::tempdir;
+
+        let td = tempdir().unwrap();
+        let req: FileRequirement = r#"(file(a.txt, size>=2) AND (b.txt XOR c.txt) AND non_empty_dir(d) AND glob("d/*.fq", 1))"#
+            .parse()
+            .unwrap();
+        let script = req.to_shell_script();
+        let run = || {
+            Command::new("sh")
+                .arg("-c")
+                .arg(&script)
+                .current_dir(td.path())
+                .output()
+                .unwrap()
+                .status
+                .success()
+        };
+        assert!(!run());
+
+        fs::write(td.path().join("a.txt"), "ok").unwrap();
+        fs::write(td.path().join("b.txt"), "").unwrap();
+        fs::create_dir(td.path().join("d")).unwrap();
+        fs::write(td.path().join("d/r1.fq"), "").unwrap();
+        assert!(run());
+
+        fs::write(td.path().join("c.txt"), "").unwrap();
+        assert!(!run());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn missing_paths_and_sizes_are_checked_like_the_rust_check() {
+        use std::fs;
+        use std::process::Command;
+        use tempfile::tempdir;
+
+        let td = tempdir().unwrap();
+        let run = |req: &FileRequirement| {
+            Command::new("sh")
+                .arg("-c")
+                .arg(req.to_shell_script())
+                .current_dir(td.path())
+                .output()
+                .unwrap()
+                .status
+                .success()
+        };
+        let staging: FileRequirement =
+            r#"(same_filesystem(., not_yet/stage) AND free_space(not_yet/out, 1) AND glob_size("*.fq", 5))"#
+                .parse()
+                .unwrap();
+        assert!(!run(&staging));
+        fs::write(td.path().join("r1.fq"), "abc").unwrap();
+        fs::write(td.path().join("r2.fq"), "de").unwrap();
+        assert!(run(&staging));
+        #[cfg(feature = "free-space")]
+        staging.check_in(td.path()).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn invalid_env_var_names_are_not_interpolated() {
+        use std::process::Command;
+
+        let name = "X}$(echo INJECTED >&2)${Y";
+        let mut b = FileRequirementBuilder::new();
+        assert!(matches!(
+            b.require_env_var(name),
+            Err(crate::FileRequirementBuildError::InvalidEnvVar { .. })
+        ));
+        assert!(matches!(
+            format!("env({:?})", name).parse::<FileRequirement>(),
+            Err(crate::FileRequirementParseError::Build(
+                crate::FileRequirementBuildError::InvalidEnvVar { .. }
+            ))
+        ));
+        #[cfg(feature = "serde")]
+        for (name, valid) in [("SALMON_INDEX", true), (name, false)] {
+            let term = serde_json::json!({
+                "env_var": {"name": name, "non_empty": false, "pattern": null}
+            });
+            assert_eq!(
+                serde_json::from_value::<FileRequirement>(term).is_ok(),
+                valid
+            );
+        }
+
+        let req = FileRequirement::EnvVar {
+            name: name.to_owned(),
+            non_empty: true,
+            pattern: None,
+        };
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(req.to_shell_script())
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(!stderr.contains("INJECTED\n"));
+        assert!(stderr.contains("invalid environment variable name"));
+    }
+}
@@ -0,0 +1,231 @@
+use std::collections::HashSet;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::Manifest;
+
+/// How [`Manifest::stage_into`] materializes each path in the staging
+/// directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StageMode {
+    /// Copy files, and directories recursively.
+    Copy,
+    /// Link to the absolute source path.
+    Symlink,
+    /// Hard-link files, and the files of directories recursively; the staging
+    /// directory must be on the same filesystem as the sources.
+    Hardlink,
+}
+
+/// A path of a [`Manifest`] and where staging puts it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Staged {
+    source: PathBuf,
+    target: PathBuf,
+}
+
+impl Staged {
+    /// The path named by the manifest.
+    pub fn source(&self) -> &Path {
+        &self.source
+    }
+
+    /// The path in the staging directory.
+    pub fn target(&self) -> &Path {
+        &self.target
+    }
+}
+
+/// Errors produced while staging a manifest.
+#[derive(Debug, Error)]
+pub enum StageError {
+    /// The staging directory already has an entry where a path would go.
+    #[error("`{target}` already exists; stage into a clean directory")]
+    TargetExists { target: String },
+    /// A path could not be staged.
+    #[error("Failed to stage `{source_path}` as `{target}`: {source}")]
+    Io {
+        source_path: String,
+        target: String,
+        #[source]
+        source: io::Error,
+    },
+}
+
+impl Manifest {
+    /// Where [`stage_into`](Self::stage_into) would put each path, without
+    /// touching the filesystem beyond checking for existing targets: a dry run.
+    ///
+    /// Each path keeps its file name; when several share one, later ones get
+    /// a numeric suffix before the extension, so `a/reads.fq.gz` and
+    /// `b/reads.fq.gz` become `reads.fq.gz` and `reads-2.fq.gz`.
+    pub fn stage_plan<P: AsRef<Path>>(&self, dir: P) -> Result<Vec<Staged>, StageError> {
+        let dir = dir.as_ref();
+        let mut taken = HashSet::new();
+        let mut plan = Vec::new();
+        for source in self.files().iter().chain(self.dirs()) {
+            let name = source
+                .file_name()
+                .map(OsString::from)
+                .unwrap_or_else(|| OsString::from("root"));
+            let mut target = dir.join(&name);
+            let mut n = 1;
+            while !taken.insert(target.clone()) {
+                n += 1;
+                target = dir.join(numbered(&name, n));
+            }
+            if target.symlink_metadata().is_ok() {
+                return Err(StageError::TargetExists {
+                    target: target.display().to_string(),
+                });
+            }
+            plan.push(Staged {
+                source: source.clone(),
+                target,
+            });
+        }
+        Ok(plan)
+    }
+
+    /// Materialize every file and directory of the manifest in `dir`, which is
+    /// created if needed, and return where each was put.
+    ///
+    /// Nothing is staged if any target already exists. Staging stops at the
+    /// first error, leaving the paths staged so far in place.
+    pub fn stage_into<P: AsRef<Path>>(
+        &self,
+        dir: P,
+        mode: StageMode,
+    ) -> Result<Vec<Staged>, StageError> {
+        let dir = dir.as_ref();
+        let plan = self.stage_plan(dir)?;
+        fs::create_dir_all(dir).map_err(|source| StageError::Io {
+            source_path: dir.display().to_string(),
+            target: dir.display().to_string(),
+            source,
+        })?;
+        for staged in &plan {
+            stage(&staged.source, &staged.target, mode).map_err(|source| StageError::Io {
+                source_path: staged.source.display().to_string(),
+                target: staged.target.display().to_string(),
+                source,
+            })?;
+        }
+        Ok(plan)
+    }
+}
+
+/// `name` with `-n` inserted before its first extension.
+fn numbered(name: &OsStr, n: usize) -> OsString {
+    let name = name.to_string_lossy();
+    let (stem, extensions) = match name.find('.').filter(|&at| at > 0) {
+        Some(at) => name.split_at(at),
+        None => (name.as_ref(), ""),
+    };
+    format!("{}-{}{}", stem, n, extensions).into()
+}
+
+fn stage(source: &Path, target: &Path, mode: StageMode) -> io::Result<()> {
+    match mode {
+        StageMode::Symlink => symlink(&std::path::absolute(source)?, target),
+        StageMode::Copy | StageMode::Hardlink if source.is_dir() => {
+            fs::create_dir(target)?;
+            for entry in fs::read_dir(source)? {
+                let entry = entry?;
+                stage(&entry.path(), &target.join(entry.file_name()), mode)?;
+            }
+            Ok(())
+        }
+        StageMode::Copy => fs::copy(source, target).map(drop),
+        StageMode::Hardlink => fs::hard_link(source, target),
+    }
+}
+
+#[cfg(unix)]
+fn symlink(source: &Path, target: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(source, target)
+}
+
+#[cfg(windows)]
+fn symlink(source: &Path, target: &Path) -> io::Result<()> {
+    if source.is_dir() {
+        std::os::windows::fs::symlink_dir(source, target)
+    } else {
+        std::os::windows::fs::symlink_file(source, target)
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn symlink(_source: &Path, _target: &Path) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "symbolic links are not supported on this platform",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FileRequirementBuilder;
+    use tempfile::tempdir;
+
+    #[test]
+    fn colliding_names_are_numbered_and_existing_targets_refused() {
+        let td = tempdir().unwrap();
+        let p = |name: &str| td.path().join(name);
+        for dir in ["a", "b", "ref"] {
+            fs::create_dir(p(dir)).unwrap();
+        }
+        fs::write(p("a/reads.fq.gz"), "a").unwrap();
+        fs::write(p("b/reads.fq.gz"), "b").unwrap();
+        fs::write(p("ref/genome.fa"), ">chr1").unwrap();
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_file(p("a/reads.fq.gz")).unwrap();
+        b.require_file(p("b/reads.fq.gz")).unwrap();
+        b.require_dir(p("ref")).unwrap();
+        let manifest = b.build().manifest().unwrap();
+
+        let work = p("work");
+        let plan = manifest.stage_plan(&work).unwrap();
+        let targets: Vec<_> = plan.iter().map(Staged::target).collect();
+        assert_eq!(
+            targets,
+            [
+                work.join("reads.fq.gz"),
+                work.join("reads-2.fq.gz"),
+                work.join("ref")
+            ]
+        );
+        assert!(!work.exists());
+
+        manifest.stage_into(&work, StageMode::Copy).unwrap();
+        assert_eq!(fs::read_to_string(work.join("reads-2.fq.gz")).unwrap(), "b");
+        assert!(work.join("ref/genome.fa").is_file());
+        assert!(matches!(
+            manifest.stage_into(&work, StageMode::Copy),
+            Err(StageError::TargetExists { .. })
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_point_at_absolute_sources() {
+        let td = tempdir().unwrap();
+        fs::write(td.path().join("ref.fa"), ">chr1").unwrap();
+        let mut b = FileRequirementBuilder::new();
+        b.require_file(td.path().join("ref.fa")).unwrap();
+        let manifest = b.build().manifest().unwrap();
+
+        let staged = manifest
+            .stage_into(td.path().join("work"), StageMode::Symlink)
+            .unwrap();
+        let link = fs::read_link(staged[0].target()).unwrap();
+        assert!(link.is_absolute());
+        assert_eq!(fs::read_to_string(staged[0].target()).unwrap(), ">chr1");
+    }
+}
@@ -0,0 +1,100 @@
+use std::path::{Path, PathBuf};
+
+use crate::{CheckReport, FileProvider, LeafOutcome};
+
+/// At most this many suggestions are kept per missing term.
+const MAX_SUGGESTIONS: usize = 3;
+
+impl CheckReport {
+    /// Attach "did you mean" suggestions to every missing leaf.
+    pub(crate) fn attach_suggestions<P: FileProvider + ?Sized>(&mut self, provider: &P) {
+        for leaf in self.leaves_mut() {
+            if *leaf.outcome() == LeafOutcome::Missing {
+                let suggestions = similar_entries(leaf.path(), provider);
+                leaf.set_suggestions(suggestions);
+            }
+        }
+    }
+}
+
+/// Entries next to `path` whose names look like a typo of its file name.
+///
+/// Listing errors (such as a missing parent directory) yield no suggestions.
+fn similar_entries<P: FileProvider + ?Sized>(path: &Path, provider: &P) -> Vec<PathBuf> {
+    let Some(wanted) = path.file_name().and_then(|name| name.to_str()) else {
+        return Vec::new();
+    };
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let Ok(entries) = provider.read_dir(parent) else {
+        return Vec::new();
+    };
+    let mut similar: Vec<PathBuf> = entries
+        .into_iter()
+        .filter(|entry| {
+            entry
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| is_similar(wanted, name))
+        })
+        .map(|entry| match entry.file_name() {
+            Some(name) => path.with_file_name(name),
+            None => entry,
+        })
+        .collect();
+    similar.sort();
+    similar.truncate(MAX_SUGGESTIONS);
+    similar
+}
+
+/// Whether `candidate` differs from `wanted` only by letter case, by extension,
+/// or by a single inserted, removed or replaced character.
+fn is_similar(wanted: &str, candidate: &str) -> bool {
+    if wanted == candidate {
+        return false;
+    }
+    let wanted = wanted.to_lowercase();
+    let candidate = candidate.to_lowercase();
+    let stem = |name: &str| name.split('.').next().map(str::to_owned);
+    wanted == candidate
+        || (wanted.contains('.') && candidate.contains('.') && stem(&wanted) == stem(&candidate))
+        || within_one_edit(&wanted, &candidate)
+}
+
+/// Whether the Levenshtein distance between `a` and `b` is at most one.
+fn within_one_edit(a: &str, b: &str) -> bool {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    if long.len() - short.len() > 1 {
+        return false;
+    }
+    let prefix = short.iter().zip(&long).take_while(|(x, y)| x == y).count();
+    if prefix == short.len() {
+        return true;
+    }
+    // Skip the first differing character: replaced if the lengths match,
+    // inserted into `long` otherwise.
+    let resume = if short.len() == long.len() {
+        prefix + 1
+    } else {
+        prefix
+    };
+    short[resume..] == long[prefix + 1..]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_similar;
+
+    #[test]
+    fn similarity_covers_case_extension_and_single_edits() {
+        assert!(is_similar("idx.ctab", "Idx.CTAB"));
+        assert!(is_similar("reads.fq.gz", "reads.fastq.gz"));
+        assert!(is_similar("sample_1.fq", "sample_2.fq"));
+        assert!(is_similar("idx.ssi.mphf", "idx.ssi.mph"));
+        assert!(!is_similar("idx.ctab", "idx.ctab"));
+        assert!(!is_similar("idx.ctab", "ref.fa"));
+    }
+}
@@ -0,0 +1,263 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::{FileRequirement, FileRequirementBuildError};
+
+/// Errors produced while substituting placeholders in a requirement template.
+#[derive(Debug, Error)]
+pub enum FileRequirementResolveError {
+    /// A `{name}` placeholder has no value.
+    #[error("Unresolved placeholder `{{{name}}}` in `{template}`")]
+    UnresolvedPlaceholder { name: String, template: String },
+    /// A `{` has no matching `}`, or a lone `}` appears.
+    #[error("Unbalanced brace in `{template}`; write `{{{{` or `}}}}` for a literal brace")]
+    UnbalancedBrace { template: String },
+    /// An environment variable referenced as `$NAME` or `${NAME}` is not set.
+    #[error("Environment variable `{name}` referenced in `{template}` is not set")]
+    UnsetVariable { name: String, template: String },
+    /// A path starts with `~` but the home directory is unknown.
+    #[error("Cannot expand `~` in `{template}`: home directory is unknown")]
+    NoHomeDirectory { template: String },
+    /// Substitution made two terms refer to the same path.
+    #[error(transparent)]
+    Build(#[from] FileRequirementBuildError),
+}
+
+impl FileRequirement {
+    /// Substitute `{name}` placeholders in every path and glob pattern.
+    ///
+    /// `{{` and `}}` stand for literal braces. Placeholders without a value in
+    /// `vars` are an error, as is a substitution that makes two file terms
+    /// refer to the same path.
+    pub fn resolve(
+        &self,
+        vars: &HashMap<String, String>,
+    ) -> Result<FileRequirement, FileRequirementResolveError> {
+        self.substitute_all(|template| substitute(template, vars))
+    }
+
+    /// Expand `$NAME` and `${NAME}` environment variable references in every path
+    /// and glob pattern.
+    ///
+    /// A `$` not followed by a name or `{` is kept literally. Unset variables are
+    /// reported as [`FileRequirementResolveError::UnsetVariable`].
+    pub fn expand_env(&self) -> Result<FileRequirement, FileRequirementResolveError> {
+        self.substitute_all(|template| {
+            expand_vars(template, |name| std::env::var(name).ok()).map_err(|name| {
+                FileRequirementResolveError::UnsetVariable {
+                    name,
+                    template: template.to_owned(),
+                }
+            })
+        })
+    }
+
+    /// Replace a leading `~` component with the current user's home directory in
+    /// every path and glob pattern. `~user` forms are left unchanged.
+    pub fn expand_home(&self) -> Result<FileRequirement, FileRequirementResolveError> {
+        let home = std::env::home_dir();
+        self.substitute_all(|template| match (&home, home_relative(template)) {
+            (_, None) => Ok(template.to_owned()),
+            (Some(home), Some(rest)) => Ok(join_home(home, rest)),
+            (None, Some(_)) => Err(FileRequirementResolveError::NoHomeDirectory {
+                template: template.to_owned(),
+            }),
+        })
+    }
+
+    /// Rewrite every path and glob pattern with `f`, then re-check that no two
+    /// file terms share a path.
+    fn substitute_all<F>(&self, mut f: F) -> Result<FileRequirement, FileRequirementResolveError>
+    where
+        F: FnMut(&str) -> Result<String, FileRequirementResolveError>,
+    {
+        let mut error = None;
+        let resolved = self.map_text(&mut |template| match f(template) {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                error.get_or_insert(e);
+                template.to_owned()
+            }
+        });
+        if let Some(e) = error {
+            return Err(e);
+        }
+
+        let mut seen: HashSet<&Path> = HashSet::new();
+        for path in resolved.paths() {
+            if !seen.insert(path) {
+                return Err(FileRequirementBuildError::DuplicateFile {
+                    path: path.display().to_string(),
+                }
+                .into());
+            }
+        }
+        Ok(resolved)
+    }
+}
+
+fn substitute(
+    template: &str,
+    vars: &HashMap<String, String>,
+) -> Result<String, FileRequirementResolveError> {
+    let unbalanced = || FileRequirementResolveError::UnbalancedBrace {
+        template: template.to_owned(),
+    };
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(idx) = rest.find(['{', '}']) {
+        out.push_str(&rest[..idx]);
+        let tail = &rest[idx..];
+        if let Some(after) = tail.strip_prefix("{{") {
+            out.push('{');
+            rest = after;
+        } else if let Some(after) = tail.strip_prefix("}}") {
+            out.push('}');
+            rest = after;
+        } else if let Some(after) = tail.strip_prefix('{') {
+            let end = after.find('}').ok_or_else(unbalanced)?;
+            let name = &after[..end];
+            let value = vars.get(name).ok_or_else(|| {
+                FileRequirementResolveError::UnresolvedPlaceholder {
+                    name: name.to_owned(),
+                    template: template.to_owned(),
+                }
+            })?;
+            out.push_str(value);
+            rest = &after[end + 1..];
+        } else {
+            return Err(unbalanced());
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// The remainder after a leading `~` or `~/`, or `None` if `template` does not
+/// start with a bare tilde component.
+pub(crate) fn home_relative(template: &str) -> Option<&str> {
+    let rest = template.strip_prefix('~')?;
+    if rest.is_empty() {
+        return Some(rest);
+    }
+    rest.strip_prefix(std::path::MAIN_SEPARATOR)
+        .or_else(|| rest.strip_prefix('/'))
+}
+
+/// `rest` joined onto `home`, with glob metacharacters in `home` escaped so the
+/// result is also valid as a glob pattern.
+pub(crate) fn join_home(home: &Path, rest: &str) -> String {
+    let home = home.to_string_lossy();
+    let home = if rest.contains(['*', '?', '[']) {
+        glob::Pattern::escape(&home)
+    } else {
+        home.into_owned()
+    };
+    if rest.is_empty() {
+        home
+    } else {
+        Path::new(&home).join(rest).to_string_lossy().into_owned()
+    }
+}
+
+/// Expand `$NAME` / `${NAME}` references with `lookup`, or return the name of
+/// the first variable it has no value for.
+pub(crate) fn expand_vars<F>(template: &str, lookup: F) -> Result<String, String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let is_name_char = |c: char| c == '_' || c.is_ascii_alphanumeric();
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(idx) = rest.find('$') {
+        out.push_str(&rest[..idx]);
+        let after = &rest[idx + 1..];
+        let (name, tail) = if let Some(braced) = after.strip_prefix('{')
+            && let Some(end) = braced.find('}')
+        {
+            (&braced[..end], &braced[end + 1..])
+        } else {
+            let end = after.find(|c| !is_name_char(c)).unwrap_or(after.len());
+            (&after[..end], &after[end..])
+        };
+        if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+            out.push('$');
+            rest = after;
+            continue;
+        }
+        out.push_str(&lookup(name).ok_or_else(|| name.to_owned())?);
+        rest = tail;
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FileRequirementResolveError;
+    use crate::{FileRequirement, FileRequirementBuildError};
+    use std::collections::HashMap;
+
+    #[test]
+    fn resolve_substitutes_placeholders_in_paths_and_globs() {
+        let template: FileRequirement =
+            r#"({prefix}.ctab AND ({prefix}.sshash OR {prefix}.ssi) AND glob("{sample}_R*.fq.gz", 2) AND "{{literal}}")"#
+                .parse()
+                .unwrap();
+        let vars: HashMap<String, String> = [("prefix", "idx/gencode"), ("sample", "S1")]
+            .map(|(k, v)| (k.to_owned(), v.to_owned()))
+            .into();
+        assert_eq!(
+            template.resolve(&vars).unwrap().to_string(),
+            r#"(idx/gencode.ctab AND (idx/gencode.sshash OR idx/gencode.ssi) AND glob("S1_R*.fq.gz", 2) AND {literal})"#
+        );
+
+        let err = template.resolve(&HashMap::new()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unresolved placeholder `{prefix}` in `{prefix}.ctab`"
+        );
+
+        let clash: FileRequirement = "({a}.txt AND {b}.txt)".parse().unwrap();
+        let vars: HashMap<String, String> = [("a", "x"), ("b", "x")]
+            .map(|(k, v)| (k.to_owned(), v.to_owned()))
+            .into();
+        assert!(matches!(
+            clash.resolve(&vars),
+            Err(FileRequirementResolveError::Build(
+                FileRequirementBuildError::DuplicateFile { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn expand_vars_handles_bare_braced_and_literal_dollars() {
+        let lookup = |name: &str| (name == "HOME").then(|| "/home/ana".to_owned());
+        assert_eq!(
+            super::expand_vars("$HOME/ref/${HOME}.bin", lookup).unwrap(),
+            "/home/ana/ref//home/ana.bin"
+        );
+        assert_eq!(
+            super::expand_vars("cost$ $1.txt", lookup).unwrap(),
+            "cost$ $1.txt"
+        );
+        assert_eq!(
+            super::expand_vars("${SCRATCH}/tmp", lookup).unwrap_err(),
+            "SCRATCH"
+        );
+    }
+
+    #[test]
+    fn expand_home_only_rewrites_bare_leading_tildes() {
+        assert_eq!(super::home_relative("~"), Some(""));
+        assert_eq!(super::home_relative("~/data/ref.fa"), Some("data/ref.fa"));
+        assert_eq!(super::home_relative("~ana/data"), None);
+        assert_eq!(super::home_relative("data/~"), None);
+
+        let home = std::path::Path::new("/home/a[1]");
+        assert_eq!(super::join_home(home, "ref.fa"), "/home/a[1]/ref.fa");
+        assert_eq!(super::join_home(home, "*.fa"), "/home/a[[]1[]]/*.fa");
+    }
+}
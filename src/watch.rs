@@ -0,0 +1,169 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use notify::{RecursiveMode, Watcher};
+use thiserror::Error;
+
+use crate::{FileRequirement, FileRequirementCheckError};
+
+/// Errors produced while waiting for a requirement expression to be satisfied.
+#[derive(Debug, Error)]
+pub enum WaitError {
+    /// The expression was still unsatisfied when the timeout elapsed.
+    #[error(transparent)]
+    Unsatisfied(#[from] FileRequirementCheckError),
+    /// The filesystem could not be watched for changes.
+    #[error("Cannot watch for file changes: {0}")]
+    Watch(#[from] notify::Error),
+}
+
+impl FileRequirement {
+    /// Block until this requirement expression is satisfied, or fail with the
+    /// last check's error once `timeout` elapses.
+    ///
+    /// Instead of polling, the directories that would contain the expression's
+    /// paths and glob matches are watched, and the expression is re-checked on
+    /// every change; directories that do not exist yet are covered by watching
+    /// their nearest existing ancestor recursively. Terms outside the
+    /// filesystem, such as environment variables, are only re-checked along
+    /// with such changes.
+    pub fn wait_until_satisfied(&self, timeout: Duration) -> Result<(), WaitError> {
+        let deadline = Instant::now() + timeout;
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            // The receiver is gone once waiting is over.
+            let _ = tx.send(event);
+        })?;
+        for (dir, mode) in self.watch_targets() {
+            watcher.watch(&dir, mode)?;
+        }
+
+        loop {
+            let report = self.check_report();
+            if report.is_satisfied() {
+                return Ok(());
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(FileRequirementCheckError::from_report(&report).into());
+            }
+            match rx.recv_timeout(remaining) {
+                Ok(event) => {
+                    event?;
+                    // Coalesce a burst of events into one re-check.
+                    while rx.try_recv().is_ok() {}
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    return Err(FileRequirementCheckError::from_report(&report).into());
+                }
+            }
+        }
+    }
+
+    /// Async counterpart of [`FileRequirement::wait_until_satisfied`], waiting
+    /// on tokio's blocking pool.
+    ///
+    /// Must be called from within a tokio runtime.
+    #[cfg(feature = "tokio")]
+    pub async fn wait_until_satisfied_async(&self, timeout: Duration) -> Result<(), WaitError> {
+        let tree = self.clone();
+        tokio::task::spawn_blocking(move || tree.wait_until_satisfied(timeout))
+            .await
+            .unwrap_or_else(|e| Err(WaitError::Watch(notify::Error::generic(&e.to_string()))))
+    }
+
+    /// Existing directories to watch for this expression: the parent of each
+    /// path and of each glob's literal prefix, or its nearest existing
+    /// ancestor, watched recursively.
+    fn watch_targets(&self) -> Vec<(PathBuf, RecursiveMode)> {
+        let mut parents: Vec<PathBuf> = self
+            .paths()
+            .into_iter()
+            .map(|path| parent_dir(path).to_path_buf())
+            .collect();
+        for pattern in glob_patterns(self) {
+            let literal: PathBuf = Path::new(pattern)
+                .components()
+                .take_while(|c| !c.as_os_str().to_string_lossy().contains(['*', '?', '[']))
+                .collect();
+            parents.push(literal);
+        }
+        let mut targets = Vec::new();
+        for dir in parents {
+            let dir = if dir.as_os_str().is_empty() {
+                PathBuf::from(".")
+            } else {
+                dir
+            };
+            let existing = dir.ancestors().find(|a| a.is_dir()).map(Path::to_path_buf);
+            let target = match existing {
+                Some(existing) if existing == dir => (dir, RecursiveMode::NonRecursive),
+                Some(existing) => (existing, RecursiveMode::Recursive),
+                None => (PathBuf::from("."), RecursiveMode::Recursive),
+            };
+            if !targets.contains(&target) {
+                targets.push(target);
+            }
+        }
+        targets
+    }
+}
+
+/// The patterns of every glob term in `node`.
+fn glob_patterns(node: &FileRequirement) -> Vec<&str> {
+    match node.group() {
+        Some((_, children)) => children.iter().flat_map(glob_patterns).collect(),
+        None => match node.unwrapped() {
+            FileRequirement::Glob { pattern, .. } | FileRequirement::GlobSize { pattern, .. } => {
+                vec![pattern.as_str()]
+            }
+            _ => Vec::new(),
+        },
+    }
+}
+
+/// The directory containing `path`, empty for a bare file name.
+fn parent_dir(path: &Path) -> &Path {
+    path.parent().unwrap_or(Path::new(""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WaitError;
+    use crate::FileRequirementBuilder;
+    use std::fs;
+    use std::thread;
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    #[test]
+    fn wait_returns_once_a_producer_creates_the_files() {
+        let td = tempdir().unwrap();
+        let out = td.path().join("out").join("quant.sf");
+
+        let mut b = FileRequirementBuilder::new();
+        b.require_file(&out).unwrap();
+        let req = b.build();
+        let producer = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            fs::create_dir(out.parent().unwrap()).unwrap();
+            fs::write(&out, "").unwrap();
+        });
+        req.wait_until_satisfied(Duration::from_secs(30)).unwrap();
+        producer.join().unwrap();
+    }
+
+    #[test]
+    fn wait_fails_with_the_last_check_error_on_timeout() {
+        let td = tempdir().unwrap();
+        let mut b = FileRequirementBuilder::new();
+        b.require_file(td.path().join("never.txt")).unwrap();
+        let err = b
+            .build()
+            .wait_until_satisfied(Duration::from_millis(20))
+            .unwrap_err();
+        assert!(matches!(err, WaitError::Unsatisfied(_)));
+    }
+}